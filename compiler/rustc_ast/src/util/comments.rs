@@ -173,19 +173,19 @@ pub fn gather_comments(sm: &SourceMap, path: FileName, src: String) -> Vec<Comme
     let mut comments: Vec<Comment> = Vec::new();
     let mut code_to_the_left = false;
 
-    if let Some(shebang_len) = rustc_lexer::strip_shebang(text) {
+    if let Some(shebang) = rustc_lexer::strip_shebang(text) {
         comments.push(Comment {
             style: CommentStyle::Isolated,
-            lines: vec![text[..shebang_len].to_string()],
+            lines: vec![text[..shebang.len].to_string()],
             pos: start_bpos,
         });
-        pos += shebang_len;
+        pos += shebang.len;
     }
 
     for token in rustc_lexer::tokenize(&text[pos..]) {
         let token_text = &text[pos..pos + token.len];
         match token.kind {
-            rustc_lexer::TokenKind::Whitespace => {
+            rustc_lexer::TokenKind::Whitespace { .. } => {
                 if let Some(mut idx) = token_text.find('\n') {
                     code_to_the_left = false;
                     while let Some(next_newline) = &token_text[idx + 1..].find('\n') {
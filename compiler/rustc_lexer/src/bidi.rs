@@ -0,0 +1,179 @@
+//! Detection of bidirectional text control characters, which can make the
+//! displayed order of the code differ from its logical order ("Trojan
+//! Source", CVE-2021-42574).
+//!
+//! [`bidi_controls`] reports every explicit embedding, override and isolate
+//! control in the source, with the token it is in. Controls which are still
+//! open at the end of their token reorder the code following it on the same
+//! line. Those are the dangerous ones, marked as [`BidiFinding::unclosed`].
+//! A line break ends all controls, so controls in a multi-line comment or
+//! string which are ended by a line break within it are harmless, and so are
+//! all controls in a line comment, as nothing follows it on its line.
+//!
+//! Bidi marks like U+200F RIGHT-TO-LEFT MARK are whitespace in Rust and are
+//! not reported.
+
+use alloc::vec::Vec;
+
+use crate::{tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// An explicit bidirectional formatting character.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BidiControl {
+    /// U+202A LEFT-TO-RIGHT EMBEDDING
+    Lre,
+    /// U+202B RIGHT-TO-LEFT EMBEDDING
+    Rle,
+    /// U+202C POP DIRECTIONAL FORMATTING
+    Pdf,
+    /// U+202D LEFT-TO-RIGHT OVERRIDE
+    Lro,
+    /// U+202E RIGHT-TO-LEFT OVERRIDE
+    Rlo,
+    /// U+2066 LEFT-TO-RIGHT ISOLATE
+    Lri,
+    /// U+2067 RIGHT-TO-LEFT ISOLATE
+    Rli,
+    /// U+2068 FIRST STRONG ISOLATE
+    Fsi,
+    /// U+2069 POP DIRECTIONAL ISOLATE
+    Pdi,
+}
+
+/// How a [`BidiControl`] affects the display of the text following it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum BidiEffect {
+    /// Starts an embedding: the following text is laid out in the given
+    /// direction, but keeps the direction of its strongly directional chars.
+    Embedding(Direction),
+    /// Starts an override: the following chars are all displayed in the given
+    /// direction.
+    Override(Direction),
+    /// Starts an isolate: the following text is laid out in the given
+    /// direction, independently of the surrounding text.
+    Isolate(Direction),
+    /// Ends the last embedding or override.
+    PopFormatting,
+    /// Ends the last isolate, and the embeddings and overrides within it.
+    PopIsolate,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    LeftToRight,
+    RightToLeft,
+    /// The direction of the first strongly directional char of the isolate.
+    FirstStrong,
+}
+
+impl BidiControl {
+    pub fn from_char(c: char) -> Option<BidiControl> {
+        let control = match c {
+            '\u{202A}' => BidiControl::Lre,
+            '\u{202B}' => BidiControl::Rle,
+            '\u{202C}' => BidiControl::Pdf,
+            '\u{202D}' => BidiControl::Lro,
+            '\u{202E}' => BidiControl::Rlo,
+            '\u{2066}' => BidiControl::Lri,
+            '\u{2067}' => BidiControl::Rli,
+            '\u{2068}' => BidiControl::Fsi,
+            '\u{2069}' => BidiControl::Pdi,
+            _ => return None,
+        };
+        Some(control)
+    }
+
+    pub fn to_char(self) -> char {
+        match self {
+            BidiControl::Lre => '\u{202A}',
+            BidiControl::Rle => '\u{202B}',
+            BidiControl::Pdf => '\u{202C}',
+            BidiControl::Lro => '\u{202D}',
+            BidiControl::Rlo => '\u{202E}',
+            BidiControl::Lri => '\u{2066}',
+            BidiControl::Rli => '\u{2067}',
+            BidiControl::Fsi => '\u{2068}',
+            BidiControl::Pdi => '\u{2069}',
+        }
+    }
+
+    pub fn effect(self) -> BidiEffect {
+        use Direction::*;
+        match self {
+            BidiControl::Lre => BidiEffect::Embedding(LeftToRight),
+            BidiControl::Rle => BidiEffect::Embedding(RightToLeft),
+            BidiControl::Pdf => BidiEffect::PopFormatting,
+            BidiControl::Lro => BidiEffect::Override(LeftToRight),
+            BidiControl::Rlo => BidiEffect::Override(RightToLeft),
+            BidiControl::Lri => BidiEffect::Isolate(LeftToRight),
+            BidiControl::Rli => BidiEffect::Isolate(RightToLeft),
+            BidiControl::Fsi => BidiEffect::Isolate(FirstStrong),
+            BidiControl::Pdi => BidiEffect::PopIsolate,
+        }
+    }
+}
+
+/// A bidirectional control character, see [`bidi_controls`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BidiFinding {
+    /// Byte offset of the char in the source.
+    pub offset: usize,
+    pub control: BidiControl,
+    /// Kind of the token containing the char. Outside of literals and
+    /// comments, controls are lexed as [`TokenKind::Unknown`].
+    pub token: TokenKind,
+    /// Whether the control starts an embedding, override or isolate which
+    /// isn't ended within its token and line, so it affects the display of
+    /// the code following the token.
+    pub unclosed: bool,
+}
+
+/// Returns the bidirectional control characters in `src`, see the
+/// [module documentation](self).
+pub fn bidi_controls(src: &str) -> Vec<BidiFinding> {
+    let mut res: Vec<BidiFinding> = Vec::new();
+    for (range, kind) in tokenize_with_offsets(src) {
+        let text = &src[range.clone()];
+        // Indices into `res` of the open controls.
+        let mut open: Vec<usize> = Vec::new();
+        for (i, c) in text.char_indices() {
+            if c == '\n' {
+                for j in open.drain(..) {
+                    res[j].unclosed = false;
+                }
+                continue;
+            }
+            let control = match BidiControl::from_char(c) {
+                Some(control) => control,
+                None => continue,
+            };
+            let is_isolate = |&j: &usize| matches!(res[j].control.effect(), BidiEffect::Isolate(_));
+            match control.effect() {
+                BidiEffect::PopFormatting => {
+                    if matches!(open.last(), Some(j) if !is_isolate(j)) {
+                        res[open.pop().unwrap()].unclosed = false;
+                    }
+                }
+                BidiEffect::PopIsolate => {
+                    if let Some(k) = open.iter().rposition(is_isolate) {
+                        for j in open.drain(k..) {
+                            res[j].unclosed = false;
+                        }
+                    }
+                }
+                _ => open.push(res.len()),
+            }
+            let unclosed = !matches!(control, BidiControl::Pdf | BidiControl::Pdi);
+            res.push(BidiFinding { offset: range.start + i, control, token: kind, unclosed });
+        }
+        if let TokenKind::LineComment { .. } = kind {
+            for j in open {
+                res[j].unclosed = false;
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,90 @@
+use expect_test::{expect, Expect};
+
+use super::*;
+
+fn check(src: &str, expect: Expect) {
+    let findings: Vec<_> = bidi_controls(src)
+        .into_iter()
+        .map(|f| format!("{} {:?} {:?} unclosed={}", f.offset, f.control, f.token, f.unclosed))
+        .collect();
+    expect.assert_eq(&findings.join("\n"));
+}
+
+#[test]
+fn test_no_controls() {
+    check("fn main() { let s = \"abc\"; }", expect![[""]]);
+}
+
+#[test]
+fn test_trojan_source_comment() {
+    // The example of "Trojan Source" which makes the check look commented out.
+    check(
+        "/*\u{202E} } \u{2066}if is_admin\u{2069} \u{2066} begin admins only */",
+        expect![[r#"
+            2 Rlo BlockComment { doc_style: None, terminated: true } unclosed=true
+            8 Lri BlockComment { doc_style: None, terminated: true } unclosed=false
+            22 Pdi BlockComment { doc_style: None, terminated: true } unclosed=false
+            26 Lri BlockComment { doc_style: None, terminated: true } unclosed=true"#]],
+    );
+}
+
+#[test]
+fn test_closed_in_string() {
+    check(
+        "\"\u{202B}abc\u{202C}\" \"\u{202D}x\"",
+        expect![[r#"
+            1 Rle Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 11 } unclosed=false
+            7 Pdf Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 11 } unclosed=false
+            13 Lro Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 6 } unclosed=true"#]],
+    );
+}
+
+#[test]
+fn test_pdf_does_not_close_isolate() {
+    check(
+        "/* \u{2067}\u{202C} */",
+        expect![[r#"
+            3 Rli BlockComment { doc_style: None, terminated: true } unclosed=true
+            6 Pdf BlockComment { doc_style: None, terminated: true } unclosed=false"#]],
+    );
+    check(
+        "\"\u{2067}\u{202A}\u{2069}\"",
+        expect![[r#"
+            1 Rli Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 11 } unclosed=false
+            4 Lre Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 11 } unclosed=false
+            7 Pdi Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 11 } unclosed=false"#]],
+    );
+}
+
+#[test]
+fn test_closed_by_newline() {
+    check(
+        "/* \u{202E}\n*/",
+        expect!["3 Rlo BlockComment { doc_style: None, terminated: true } unclosed=false"],
+    );
+}
+
+#[test]
+fn test_closed_by_end_of_line_comment() {
+    check(
+        "// \u{202E} \u{2066}\nx\u{202E}\n/// \u{2067}",
+        expect![[r#"
+            3 Rlo LineComment { doc_style: None } unclosed=false
+            7 Lri LineComment { doc_style: None } unclosed=false
+            12 Rlo Unknown unclosed=true
+            20 Rli LineComment { doc_style: Some(Outer) } unclosed=false"#]],
+    );
+}
+
+#[test]
+fn test_outside_of_tokens() {
+    check("a\u{202E}b", expect![[r#"1 Rlo Unknown unclosed=true"#]]);
+}
+
+#[test]
+fn test_chars_roundtrip() {
+    for c in ['\u{202A}', '\u{202E}', '\u{2066}', '\u{2069}'] {
+        assert_eq!(BidiControl::from_char(c).unwrap().to_char(), c);
+    }
+    assert_eq!(BidiControl::from_char('\u{200F}'), None);
+}
@@ -0,0 +1,33 @@
+//! Prints the tokens of a Rust source file as JSON lines, see the `json`
+//! module of `rustc_lexer` for the format.
+//!
+//! Usage: `rustc-lex [FILE]`. Reads standard input if no file or `-` is given.
+
+use std::io::{self, Read, Write};
+use std::process;
+
+fn main() {
+    let path = std::env::args().nth(1);
+    let src = match path.as_deref() {
+        None | Some("-") => {
+            let mut src = String::new();
+            io::stdin().read_to_string(&mut src).map(|_| src)
+        }
+        Some(path) => std::fs::read_to_string(path),
+    };
+    let src = match src {
+        Ok(src) => src,
+        Err(err) => {
+            eprintln!("rustc-lex: {}: {}", path.as_deref().unwrap_or("-"), err);
+            process::exit(1);
+        }
+    };
+    let mut out = String::new();
+    rustc_lexer::json::write_tokens(&src, &mut out).unwrap();
+    if let Err(err) = io::stdout().lock().write_all(out.as_bytes()) {
+        if err.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("rustc-lex: {}", err);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,155 @@
+//! Grouping of comments into the logical blocks which comment re-wrapping
+//! tools like rustfmt reflow as a whole.
+//!
+//! Consecutive line comments form a group if they have the same style, start
+//! in the same column and are not separated by a blank line. Every block
+//! comment is a group of its own. Each line of a group knows the range of its
+//! content, without the comment markers and decoration, so tools can reflow
+//! the content and put the decoration back.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize_with_offsets, DocStyle, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// A logical block of comments, see [`comment_groups`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommentGroup {
+    pub kind: CommentGroupKind,
+    /// Range of the comments, from the start of the first to the end of the
+    /// last one.
+    pub range: Range<usize>,
+    /// Column of the start of the group, counted in chars.
+    pub column: usize,
+    /// Whether the group starts after code on the same line, like the
+    /// comment in `let x = 1; // one`.
+    pub trailing: bool,
+    pub lines: Vec<CommentLine>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommentGroupKind {
+    /// Consecutive line comments, one per line.
+    Line { doc_style: Option<DocStyle> },
+    /// A single block comment.
+    Block { doc_style: Option<DocStyle>, terminated: bool },
+}
+
+/// A line of a [`CommentGroup`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommentLine {
+    /// Range of the part of the line belonging to the comment.
+    pub range: Range<usize>,
+    /// Range of the text of the line, without the comment markers, the
+    /// leading `*` of block comment lines and the single space following
+    /// either. Trailing whitespace is excluded as well.
+    pub content: Range<usize>,
+}
+
+/// Returns the groups of comments in `src`, in source order.
+pub fn comment_groups(src: &str) -> Vec<CommentGroup> {
+    let mut res: Vec<CommentGroup> = Vec::new();
+    // Whether the last group can be continued by a line comment.
+    let mut open = false;
+    let mut line_start = 0;
+    let mut code_on_line = false;
+    for (range, kind) in tokenize_with_offsets(src) {
+        let text = &src[range.clone()];
+        match kind {
+            TokenKind::Whitespace { .. } => {
+                let newlines = text.matches('\n').count();
+                if newlines > 0 {
+                    line_start = range.start + text.rfind('\n').unwrap() + 1;
+                    code_on_line = false;
+                }
+                if newlines != 1 {
+                    open = false;
+                }
+            }
+            TokenKind::LineComment { doc_style } => {
+                let column = src[line_start..range.start].chars().count();
+                let kind = CommentGroupKind::Line { doc_style };
+                let marker_len = if doc_style.is_some() { 3 } else { 2 };
+                let line = CommentLine { content: content(src, &range, marker_len), range };
+                match res.last_mut() {
+                    Some(group)
+                        if open
+                            && !code_on_line
+                            && group.kind == kind
+                            && group.column == column =>
+                    {
+                        group.range.end = line.range.end;
+                        group.lines.push(line);
+                    }
+                    _ => res.push(CommentGroup {
+                        kind,
+                        range: line.range.clone(),
+                        column,
+                        trailing: code_on_line,
+                        lines: vec![line],
+                    }),
+                }
+                open = true;
+            }
+            TokenKind::BlockComment { doc_style, terminated } => {
+                res.push(CommentGroup {
+                    kind: CommentGroupKind::Block { doc_style, terminated },
+                    range: range.clone(),
+                    column: src[line_start..range.start].chars().count(),
+                    trailing: code_on_line,
+                    lines: block_comment_lines(src, range.clone(), doc_style, terminated),
+                });
+                open = false;
+                code_on_line = true;
+                if let Some(last_newline) = text.rfind('\n') {
+                    line_start = range.start + last_newline + 1;
+                }
+            }
+            _ => {
+                open = false;
+                code_on_line = true;
+            }
+        }
+    }
+    res
+}
+
+fn block_comment_lines(
+    src: &str,
+    range: Range<usize>,
+    doc_style: Option<DocStyle>,
+    terminated: bool,
+) -> Vec<CommentLine> {
+    let open_len = if doc_style.is_some() { 3 } else { 2 };
+    let end = if terminated { range.end - 2 } else { range.end };
+    let mut res = Vec::new();
+    let mut start = range.start;
+    for (i, line) in src[range.start..end.max(range.start + open_len)].split('\n').enumerate() {
+        let line_range = start..start + line.len();
+        start = line_range.end + 1;
+        let content = if i == 0 {
+            content(src, &line_range, open_len)
+        } else {
+            let indent = line.len() - line.trim_start().len();
+            let decoration = if line.trim_start().starts_with('*') { indent + 1 } else { indent };
+            content(src, &line_range, decoration)
+        };
+        res.push(CommentLine { range: line_range, content });
+    }
+    // Include the closing `*/` in the range of the last line.
+    res.last_mut().unwrap().range.end = range.end;
+    res
+}
+
+/// The content of `range` after skipping `prefix_len` bytes and one space,
+/// without trailing whitespace.
+fn content(src: &str, range: &Range<usize>, prefix_len: usize) -> Range<usize> {
+    let text = &src[range.start + prefix_len..range.end];
+    let text = text.strip_prefix(' ').unwrap_or(text);
+    let start = range.end - text.len();
+    start..start + text.trim_end().len()
+}
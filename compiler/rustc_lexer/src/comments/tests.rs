@@ -0,0 +1,60 @@
+use super::*;
+use expect_test::{expect, Expect};
+
+fn check(src: &str, expect: Expect) {
+    let mut actual = String::new();
+    for group in comment_groups(src) {
+        actual += &format!(
+            "{:?} {:?} column={} trailing={}\n",
+            group.kind, group.range, group.column, group.trailing
+        );
+        for line in group.lines {
+            actual += &format!("    {:?} {:?}\n", &src[line.range], &src[line.content]);
+        }
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn test_line_comments() {
+    check(
+        "// a\n// b\n\n// c\n    // d\n/// e\nlet x = 1; // f\n           // g\n",
+        expect![[r#"
+            Line { doc_style: None } 0..9 column=0 trailing=false
+                "// a" "a"
+                "// b" "b"
+            Line { doc_style: None } 11..15 column=0 trailing=false
+                "// c" "c"
+            Line { doc_style: None } 20..24 column=4 trailing=false
+                "// d" "d"
+            Line { doc_style: Some(Outer) } 25..30 column=0 trailing=false
+                "/// e" "e"
+            Line { doc_style: None } 42..62 column=11 trailing=true
+                "// f" "f"
+                "// g" "g"
+        "#]],
+    );
+}
+
+#[test]
+fn test_block_comments() {
+    check(
+        "/* a */\n  /**\n   * b\n   *  c\r\n   */\nfn f() {} /* d\n e */ // f\n/* g",
+        expect![[r#"
+            Block { doc_style: None, terminated: true } 0..7 column=0 trailing=false
+                "/* a */" "a"
+            Block { doc_style: Some(Outer), terminated: true } 10..35 column=2 trailing=false
+                "/**" ""
+                "   * b" "b"
+                "   *  c\r" " c"
+                "   */" ""
+            Block { doc_style: None, terminated: true } 46..56 column=10 trailing=true
+                "/* d" "d"
+                " e */" "e"
+            Line { doc_style: None } 57..61 column=6 trailing=true
+                "// f" "f"
+            Block { doc_style: None, terminated: false } 62..66 column=0 trailing=false
+                "/* g" "g"
+        "#]],
+    );
+}
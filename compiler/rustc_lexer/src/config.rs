@@ -0,0 +1,106 @@
+//! Options for lexing Rust-like languages.
+
+use crate::Edition;
+
+/// Options for lexing languages which are syntactically close to Rust, such as
+/// DSLs embedded in macros or experiments with future Rust syntax.
+///
+/// The default configuration lexes Rust, so tools which only deal with Rust
+/// can ignore this type and use [`tokenize`](crate::tokenize) directly.
+#[derive(Clone, Copy, Debug)]
+pub struct LexerConfig<'a> {
+    /// Identifiers which, when immediately followed by `"` or `'`, start a
+    /// literal instead of being an [`UnknownPrefix`](crate::TokenKind::UnknownPrefix).
+    /// For example with `sql` registered, `sql"select 1"` is lexed as a single
+    /// [`PrefixedLiteral`](crate::TokenKind::PrefixedLiteral) token.
+    pub literal_prefixes: &'a [&'a str],
+    /// Which characters identifiers consist of. This also applies to
+    /// lifetimes, raw identifiers, literal suffixes and prefixes.
+    pub ident_policy: IdentPolicy,
+    /// Additional strings which start a line comment, e.g. `#` or `--`. Such
+    /// comments are lexed as non-doc [`LineComment`](crate::TokenKind::LineComment)s
+    /// and take precedence over all other tokens starting at the same place.
+    pub line_comment_starts: &'a [&'a str],
+    /// Whether a `/*` inside of a block comment opens a nested comment, as it
+    /// does in Rust. If disabled, the first `*/` closes the comment.
+    pub nested_block_comments: bool,
+    /// Whether [`Whitespace`](crate::TokenKind::Whitespace) tokens record the
+    /// kinds of line endings they contain. Off by default, as it makes lexing
+    /// whitespace slower.
+    pub classify_line_endings: bool,
+    /// Whether [`Whitespace`](crate::TokenKind::Whitespace) tokens record how
+    /// many spaces, tabs and newlines they consist of. Off by default for the
+    /// same reason.
+    pub count_whitespace: bool,
+    /// The edition whose lexical rules apply, see
+    /// [`tokenize_with_edition`](crate::tokenize_with_edition).
+    pub edition: Edition,
+    /// The most `#`s a raw string may be delimited by. Raw strings with more
+    /// are lexed with [`RawStrError::TooManyDelimiters`](crate::RawStrError::TooManyDelimiters).
+    /// Rust allows 65535, tools processing generated code may need more.
+    pub max_raw_str_hashes: u32,
+}
+
+impl LexerConfig<'static> {
+    /// The configuration used for lexing Rust, in the 2021 edition.
+    pub const DEFAULT: LexerConfig<'static> = LexerConfig {
+        literal_prefixes: &[],
+        ident_policy: IdentPolicy::RUST,
+        line_comment_starts: &[],
+        nested_block_comments: true,
+        classify_line_endings: false,
+        count_whitespace: false,
+        edition: Edition::Edition2021,
+        max_raw_str_hashes: u16::MAX as u32,
+    };
+
+    /// The configuration used for lexing Rust 2015.
+    pub const EDITION_2015: LexerConfig<'static> =
+        LexerConfig { edition: Edition::Edition2015, ..LexerConfig::DEFAULT };
+
+    /// The configuration used for lexing Rust 2018.
+    pub const EDITION_2018: LexerConfig<'static> =
+        LexerConfig { edition: Edition::Edition2018, ..LexerConfig::DEFAULT };
+
+    /// The configuration used for lexing Rust 2024.
+    pub const EDITION_2024: LexerConfig<'static> =
+        LexerConfig { edition: Edition::Edition2024, ..LexerConfig::DEFAULT };
+}
+
+impl Default for LexerConfig<'_> {
+    fn default() -> Self {
+        LexerConfig::DEFAULT
+    }
+}
+
+/// Which characters identifiers consist of, see [`LexerConfig::ident_policy`].
+///
+/// Characters allowed in identifiers take precedence over their meaning as
+/// punctuation, so e.g. allowing `-` to continue an identifier lexes `a-b` as
+/// a single identifier.
+#[derive(Clone, Copy, Debug)]
+pub struct IdentPolicy {
+    /// True if the char is valid as the first char of an identifier.
+    pub is_start: fn(char) -> bool,
+    /// True if the char is valid as a non-first char of an identifier.
+    pub is_continue: fn(char) -> bool,
+}
+
+impl IdentPolicy {
+    /// Rust's identifiers, see [`is_id_start`](crate::is_id_start) and
+    /// [`is_id_continue`](crate::is_id_continue).
+    pub const RUST: IdentPolicy =
+        IdentPolicy { is_start: crate::is_id_start, is_continue: crate::is_id_continue };
+
+    /// Identifiers restricted to ASCII letters, digits and `_`.
+    pub const ASCII: IdentPolicy =
+        IdentPolicy { is_start: is_ascii_id_start, is_continue: is_ascii_id_continue };
+}
+
+fn is_ascii_id_start(c: char) -> bool {
+    c == '_' || c.is_ascii_alphabetic()
+}
+
+fn is_ascii_id_continue(c: char) -> bool {
+    c == '_' || c.is_ascii_alphanumeric()
+}
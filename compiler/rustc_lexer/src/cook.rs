@@ -0,0 +1,387 @@
+//! Conversion of literal tokens into the values they denote.
+//!
+//! [`cook_literal`] does everything between lexing a literal and using its
+//! value: it checks the token for lexing errors, unescapes strings and
+//! chars, parses numbers and validates the suffix. Errors carry their byte
+//! range in the token, so they can be reported at the offending escape or
+//! digit.
+
+use alloc::borrow::Cow;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::error::literal_error_kind;
+use crate::unescape::{self, CookedValue, EscapeError, Mode};
+use crate::{Base, LexErrorKind, LiteralKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Suffixes of integer literals.
+const INT_SUFFIXES: &[&str] =
+    &["i8", "i16", "i32", "i64", "i128", "isize", "u8", "u16", "u32", "u64", "u128", "usize"];
+
+/// Suffixes of float literals.
+const FLOAT_SUFFIXES: &[&str] = &["f32", "f64"];
+
+/// The value of a literal, see [`cook_literal`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value<'a> {
+    /// An integer with its base and suffix, which is empty if there is none.
+    Int(u128, Base, &'a str),
+    /// A float with its suffix, which is empty if there is none. Decimal
+    /// integers with a float suffix like `1f32` are floats as well. With the
+    /// `f32` suffix, the value is rounded to `f32` precision.
+    Float(f64, &'a str),
+    /// The value of a string or raw string, borrowed from the token if it
+    /// contains no escapes.
+    Str(Cow<'a, str>),
+    /// The value of a byte string or raw byte string.
+    ByteStr(Vec<u8>),
+    /// The value of a C string or raw C string, without the terminating NUL.
+    CStr(Vec<u8>),
+    Char(char),
+    Byte(u8),
+}
+
+/// A problem found while cooking a literal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CookError {
+    /// Byte range in the text of the token.
+    pub range: Range<usize>,
+    pub kind: CookErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CookErrorKind {
+    /// The token itself is malformed, e.g. unterminated. The range covers
+    /// the whole token.
+    Lex(LexErrorKind),
+    /// A problem in an escape. Warnings don't prevent producing a value.
+    Escape(EscapeError),
+    /// A digit which is not valid in the base of the literal, like the `2` in
+    /// `0b12`.
+    InvalidDigit { base: Base },
+    /// An integer too large for `u128`.
+    IntOverflow,
+    /// A suffix which is not valid for the kind of literal, like in `1.0u8`
+    /// or `"a"suffix`.
+    InvalidSuffix,
+}
+
+impl CookErrorKind {
+    /// Whether the error prevents producing a value.
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            CookErrorKind::Escape(err) => err.is_fatal(),
+            _ => true,
+        }
+    }
+}
+
+/// Returns the value of a literal token with kind `kind`, along with every
+/// problem found in it.
+///
+/// `text` is the text of the token and `suffix_start` its `suffix_start`. The
+/// value is `None` if any of the problems is fatal, see
+/// [`CookErrorKind::is_fatal`].
+pub fn cook_literal(
+    kind: LiteralKind,
+    text: &str,
+    suffix_start: usize,
+) -> (Option<Value<'_>>, Vec<CookError>) {
+    if let Some(err) = literal_error_kind(kind) {
+        let error = CookError { range: 0..text.len(), kind: CookErrorKind::Lex(err) };
+        return (None, vec![error]);
+    }
+    let contents = kind.content_range(text, suffix_start);
+    let suffix = &text[suffix_start..];
+    let mut errors = Vec::new();
+    let value = match kind {
+        LiteralKind::Int { base, .. } => {
+            if base == Base::Decimal && FLOAT_SUFFIXES.contains(&suffix) {
+                cook_float(text, suffix_start, &mut errors)
+            } else {
+                cook_int(text, base, suffix_start, &mut errors)
+            }
+        }
+        LiteralKind::Float { .. } => cook_float(text, suffix_start, &mut errors),
+        LiteralKind::Char { .. } => cook_str(text, contents, Mode::Char, &mut errors),
+        LiteralKind::Byte { .. } => cook_str(text, contents, Mode::Byte, &mut errors),
+        LiteralKind::Str { .. } => cook_str(text, contents, Mode::Str, &mut errors),
+        LiteralKind::ByteStr { .. } => cook_str(text, contents, Mode::ByteStr, &mut errors),
+        LiteralKind::RawStr { .. } => cook_str(text, contents, Mode::RawStr, &mut errors),
+        LiteralKind::RawByteStr { .. } => cook_str(text, contents, Mode::RawByteStr, &mut errors),
+        LiteralKind::CStr { .. } => cook_str(text, contents, Mode::CStr, &mut errors),
+        LiteralKind::RawCStr { .. } => cook_str(text, contents, Mode::RawCStr, &mut errors),
+    };
+    let suffix_valid = match value {
+        Some(Value::Int(..)) => suffix.is_empty() || INT_SUFFIXES.contains(&suffix),
+        Some(Value::Float(..)) => suffix.is_empty() || FLOAT_SUFFIXES.contains(&suffix),
+        _ => suffix.is_empty(),
+    };
+    if !suffix_valid {
+        errors.push(CookError {
+            range: suffix_start..text.len(),
+            kind: CookErrorKind::InvalidSuffix,
+        });
+    }
+    if errors.iter().any(|err| err.kind.is_fatal()) {
+        return (None, errors);
+    }
+    (value, errors)
+}
+
+/// Parses the value of an integer literal token with base `base`, ignoring
+/// the base prefix, `_` separators and the suffix.
+///
+/// `text` is the text of the token and `suffix_start` its `suffix_start`. On
+/// failure, returns every digit which is not valid in the base as a
+/// [`CookErrorKind::InvalidDigit`], and a [`CookErrorKind::IntOverflow`] if
+/// the value doesn't fit into a `u128`, with their ranges in `text`. Lexing
+/// errors like the missing digits in `0x` are not reported, `0x` is 0.
+pub fn parse_int(text: &str, base: Base, suffix_start: usize) -> Result<u128, Vec<CookError>> {
+    let kind = LiteralKind::Int { base, empty_int: None, underscore_after_prefix: false };
+    let contents = kind.content_range(text, suffix_start);
+    let radix = match base {
+        Base::Binary => 2,
+        Base::Octal => 8,
+        Base::Decimal => 10,
+        Base::Hexadecimal => 16,
+    };
+    let mut errors = Vec::new();
+    let mut value: Option<u128> = Some(0);
+    for (idx, c) in text[contents.clone()].char_indices() {
+        if c == '_' {
+            continue;
+        }
+        match c.to_digit(radix) {
+            Some(digit) => {
+                value = value
+                    .and_then(|value| value.checked_mul(radix.into()))
+                    .and_then(|value| value.checked_add(digit.into()));
+            }
+            None => {
+                let range = contents.start + idx..contents.start + idx + c.len_utf8();
+                errors.push(CookError { range, kind: CookErrorKind::InvalidDigit { base } });
+            }
+        }
+    }
+    if value.is_none() {
+        errors.push(CookError { range: contents, kind: CookErrorKind::IntOverflow });
+    }
+    match value {
+        Some(value) if errors.is_empty() => Ok(value),
+        _ => Err(errors),
+    }
+}
+
+fn cook_int<'a>(
+    text: &'a str,
+    base: Base,
+    suffix_start: usize,
+    errors: &mut Vec<CookError>,
+) -> Option<Value<'a>> {
+    match parse_int(text, base, suffix_start) {
+        Ok(value) => Some(Value::Int(value, base, &text[suffix_start..])),
+        Err(int_errors) => {
+            errors.extend(int_errors);
+            None
+        }
+    }
+}
+
+/// The value of a float literal, see [`parse_float`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FloatValue {
+    pub value: f64,
+    /// Whether the literal has the `f32` suffix. `value` is then rounded to
+    /// the nearest `f32` directly from the decimal text, so converting it with
+    /// `as f32` is exact. Rounding to `f64` first could round differently.
+    pub is_f32: bool,
+}
+
+/// Parses the value of a float literal token, or of a decimal integer literal
+/// token with a float suffix like `1f32`, ignoring `_` separators and the
+/// suffix.
+///
+/// `text` is the text of the token and `suffix_start` its `suffix_start`. On
+/// failure, returns every problem with its range in `text`: a
+/// [`CookErrorKind::Lex`] for a non-decimal float or a missing exponent, a
+/// [`CookErrorKind::InvalidDigit`] for anything which is not a decimal digit
+/// where one is expected, and a [`CookErrorKind::InvalidSuffix`] for a suffix
+/// other than `f32` and `f64`. Values too large for the type are infinite.
+pub fn parse_float(text: &str, suffix_start: usize) -> Result<FloatValue, Vec<CookError>> {
+    let prefix_base = match text.get(..2) {
+        Some("0b") => Some(Base::Binary),
+        Some("0o") => Some(Base::Octal),
+        Some("0x") => Some(Base::Hexadecimal),
+        _ => None,
+    };
+    if let Some(base) = prefix_base {
+        let kind = CookErrorKind::Lex(LexErrorKind::NonDecimalFloat(base));
+        return Err(vec![CookError { range: 0..text.len(), kind }]);
+    }
+    let digits = &text[..suffix_start];
+    let suffix = &text[suffix_start..];
+    let mut errors = Vec::new();
+    let invalid_digit = |idx: usize, c: char| CookError {
+        range: idx..idx + c.len_utf8(),
+        kind: CookErrorKind::InvalidDigit { base: Base::Decimal },
+    };
+    let exponent_start = digits.find(['e', 'E']).unwrap_or(digits.len());
+    let mut seen_dot = false;
+    let mut mantissa_digits = 0;
+    for (idx, c) in digits[..exponent_start].char_indices() {
+        match c {
+            '0'..='9' => mantissa_digits += 1,
+            '_' => {}
+            '.' if idx > 0 && !seen_dot => seen_dot = true,
+            c => errors.push(invalid_digit(idx, c)),
+        }
+    }
+    if mantissa_digits == 0 {
+        errors.push(CookError {
+            range: 0..exponent_start,
+            kind: CookErrorKind::Lex(LexErrorKind::EmptyInt),
+        });
+    }
+    if exponent_start < digits.len() {
+        let mut exponent_digits = 0;
+        for (idx, c) in digits[exponent_start + 1..].char_indices() {
+            match c {
+                '0'..='9' => exponent_digits += 1,
+                '_' => {}
+                '+' | '-' if idx == 0 => {}
+                c => errors.push(invalid_digit(exponent_start + 1 + idx, c)),
+            }
+        }
+        if exponent_digits == 0 {
+            errors.push(CookError {
+                range: exponent_start..suffix_start,
+                kind: CookErrorKind::Lex(LexErrorKind::EmptyExponent),
+            });
+        }
+    }
+    if !suffix.is_empty() && !FLOAT_SUFFIXES.contains(&suffix) {
+        errors.push(CookError {
+            range: suffix_start..text.len(),
+            kind: CookErrorKind::InvalidSuffix,
+        });
+    }
+    if !errors.is_empty() {
+        return Err(errors);
+    }
+    // `from_str` accepts what's left once the underscores are gone, and it
+    // saturates to infinity on overflow.
+    let is_f32 = suffix == "f32";
+    let digits = digits.replace('_', "");
+    let value = if is_f32 { digits.parse::<f32>().map(f64::from) } else { digits.parse() };
+    Ok(FloatValue { value: value.expect("validated float literal"), is_f32 })
+}
+
+fn cook_float<'a>(
+    text: &'a str,
+    suffix_start: usize,
+    errors: &mut Vec<CookError>,
+) -> Option<Value<'a>> {
+    match parse_float(text, suffix_start) {
+        Ok(float) => Some(Value::Float(float.value, &text[suffix_start..])),
+        Err(float_errors) => {
+            // The suffix is validated by `cook_literal`.
+            let float_errors = float_errors.into_iter();
+            errors.extend(float_errors.filter(|err| err.kind != CookErrorKind::InvalidSuffix));
+            None
+        }
+    }
+}
+
+/// Where a run of `_` separators in a number is misplaced, see
+/// [`misplaced_separators`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MisplacedSeparator {
+    /// At the start of the number, like `_1` in `-_1`.
+    Leading,
+    /// At the end of the digits, like in `1_` or `1_u8`.
+    Trailing,
+    /// Right after the base prefix, like in `0x_1`.
+    AfterPrefix,
+    /// Right before the decimal point, like in `1_.5`.
+    BeforeDot,
+    /// Right after the decimal point, like in `1._5`.
+    AfterDot,
+    /// Right before the exponent, like in `1_e5`.
+    BeforeExponent,
+    /// At the start of the exponent, like in `1e_5` or `1e+_5`.
+    AfterExponent,
+}
+
+/// Returns the runs of `_` separators in an integer or float literal token
+/// which don't separate digits, with their byte ranges in `text`.
+///
+/// `text` is the text of the token and `suffix_start` its `suffix_start`. The
+/// base is taken from the prefix of `text`. Rust accepts all of these, so
+/// this is meant for style lints and for pointing at separators in
+/// diagnostics.
+pub fn misplaced_separators(
+    text: &str,
+    suffix_start: usize,
+) -> Vec<(Range<usize>, MisplacedSeparator)> {
+    let digits = &text[..suffix_start];
+    let body_start = match digits.get(..2) {
+        Some("0b" | "0o" | "0x") => 2,
+        _ => 0,
+    };
+    let is_decimal = body_start == 0;
+    let mut res = Vec::new();
+    let mut pos = body_start;
+    while let Some(offset) = digits[pos..].find('_') {
+        let start = pos + offset;
+        let end = start + digits[start..].bytes().take_while(|&b| b == b'_').count();
+        pos = end;
+        let prev = digits[..start].chars().next_back();
+        let next = digits[end..].chars().next();
+        let misplaced = match (prev, next) {
+            _ if start == body_start && body_start != 0 => MisplacedSeparator::AfterPrefix,
+            (None, _) => MisplacedSeparator::Leading,
+            (_, None) => MisplacedSeparator::Trailing,
+            (Some('.'), _) => MisplacedSeparator::AfterDot,
+            (_, Some('.')) => MisplacedSeparator::BeforeDot,
+            (Some('e' | 'E' | '+' | '-'), _) if is_decimal => MisplacedSeparator::AfterExponent,
+            (_, Some('e' | 'E')) if is_decimal => MisplacedSeparator::BeforeExponent,
+            _ => continue,
+        };
+        res.push((start..end, misplaced));
+    }
+    res
+}
+
+fn cook_str<'a>(
+    text: &'a str,
+    contents: Range<usize>,
+    mode: Mode,
+    errors: &mut Vec<CookError>,
+) -> Option<Value<'a>> {
+    let literal = &text[contents.clone()];
+    let needs_unescaping = match mode {
+        Mode::Str => literal.contains(['\\', '\r']),
+        Mode::RawStr => literal.contains('\r'),
+        _ => true,
+    };
+    if !needs_unescaping {
+        return Some(Value::Str(Cow::Borrowed(literal)));
+    }
+    let (value, escape_errors) = unescape::unescape_collect(literal, mode);
+    errors.extend(escape_errors.into_iter().map(|(range, err)| CookError {
+        range: contents.start + range.start..contents.start + range.end,
+        kind: CookErrorKind::Escape(err),
+    }));
+    Some(match value? {
+        CookedValue::Char(c) => Value::Char(c),
+        CookedValue::Byte(b) => Value::Byte(b),
+        CookedValue::Str(s) => Value::Str(Cow::Owned(s)),
+        CookedValue::ByteStr(bytes) => Value::ByteStr(bytes),
+        CookedValue::CStr(bytes) => Value::CStr(bytes),
+    })
+}
@@ -0,0 +1,250 @@
+use super::*;
+use crate::{first_token, TokenKind};
+use expect_test::{expect, Expect};
+
+fn check(input: &str, expect: Expect) {
+    let token = first_token(input);
+    let (kind, suffix_start) = match token.kind {
+        TokenKind::Literal { kind, suffix_start } => (kind, suffix_start),
+        kind => panic!("not a literal: {:?}", kind),
+    };
+    let (value, errors) = cook_literal(kind, &input[..token.len], suffix_start);
+    let mut actual = format!("{:?}\n", value);
+    for error in errors {
+        actual += &format!("{:?} {:?}\n", error.kind, error.range);
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn test_int() {
+    check(
+        "0x1F_u32",
+        expect![[r#"
+        Some(Int(31, Hexadecimal, "u32"))
+    "#]],
+    );
+    check(
+        "1_000",
+        expect![[r#"
+        Some(Int(1000, Decimal, ""))
+    "#]],
+    );
+    check(
+        "0b1021",
+        expect![[r#"
+        None
+        InvalidDigit { base: Binary } 4..5
+    "#]],
+    );
+    check(
+        "0xffff_ffff_ffff_ffff_ffff_ffff_ffff_ffff_0",
+        expect![[r#"
+        None
+        IntOverflow 2..43
+    "#]],
+    );
+    check(
+        "1f32",
+        expect![[r#"
+        Some(Float(1.0, "f32"))
+    "#]],
+    );
+    check(
+        "1u7",
+        expect![[r#"
+        None
+        InvalidSuffix 1..3
+    "#]],
+    );
+    check(
+        "0x",
+        expect![[r#"
+        None
+        Lex(EmptyInt) 0..2
+    "#]],
+    );
+}
+
+#[test]
+fn test_parse_int() {
+    assert_eq!(parse_int("0x1F_u32", Base::Hexadecimal, 5), Ok(31));
+    assert_eq!(parse_int("1_000", Base::Decimal, 5), Ok(1000));
+    assert_eq!(parse_int("0o17i8", Base::Octal, 4), Ok(15));
+    assert_eq!(
+        parse_int("0b1021_2", Base::Binary, 8),
+        Err(vec![
+            CookError { range: 4..5, kind: CookErrorKind::InvalidDigit { base: Base::Binary } },
+            CookError { range: 7..8, kind: CookErrorKind::InvalidDigit { base: Base::Binary } },
+        ])
+    );
+    let max = "340282366920938463463374607431768211455";
+    assert_eq!(parse_int(max, Base::Decimal, max.len()), Ok(u128::MAX));
+    let overflow = "340282366920938463463374607431768211456u128";
+    assert_eq!(
+        parse_int(overflow, Base::Decimal, 39),
+        Err(vec![CookError { range: 0..39, kind: CookErrorKind::IntOverflow }])
+    );
+}
+
+#[test]
+fn test_float() {
+    check(
+        "1_0.5e-1f64",
+        expect![[r#"
+        Some(Float(1.05, "f64"))
+    "#]],
+    );
+    check(
+        "1.5u8",
+        expect![[r#"
+        None
+        InvalidSuffix 3..5
+    "#]],
+    );
+}
+
+#[test]
+fn test_parse_float() {
+    let float = |value, is_f32| Ok(FloatValue { value, is_f32 });
+    assert_eq!(parse_float("1_0.5e-1f64", 8), float(1.05, false));
+    assert_eq!(parse_float("2.5E+2", 6), float(250.0, false));
+    assert_eq!(parse_float("1f32", 1), float(1.0, true));
+    assert_eq!(parse_float("0.1f32", 3), float(f64::from(0.1f32), true));
+    assert_eq!(parse_float("1e999", 5), float(f64::INFINITY, false));
+    assert_eq!(
+        parse_float("1.5e_u8", 5),
+        Err(vec![
+            CookError { range: 3..5, kind: CookErrorKind::Lex(LexErrorKind::EmptyExponent) },
+            CookError { range: 5..7, kind: CookErrorKind::InvalidSuffix },
+        ])
+    );
+    assert_eq!(
+        parse_float("1.2.3", 5),
+        Err(vec![CookError {
+            range: 3..4,
+            kind: CookErrorKind::InvalidDigit { base: Base::Decimal }
+        }])
+    );
+    assert_eq!(
+        parse_float("0x1.0", 5),
+        Err(vec![CookError {
+            range: 0..5,
+            kind: CookErrorKind::Lex(LexErrorKind::NonDecimalFloat(Base::Hexadecimal))
+        }])
+    );
+}
+
+#[test]
+fn test_misplaced_separators() {
+    use MisplacedSeparator::*;
+    assert_eq!(misplaced_separators("1_000_000", 9), vec![]);
+    assert_eq!(misplaced_separators("0xff_ff_u8", 8), vec![(7..8, Trailing)]);
+    assert_eq!(misplaced_separators("0x__1", 5), vec![(2..4, AfterPrefix)]);
+    assert_eq!(misplaced_separators("0b1_e", 5), vec![]);
+    assert_eq!(misplaced_separators("_1", 2), vec![(0..1, Leading)]);
+    assert_eq!(misplaced_separators("1_", 2), vec![(1..2, Trailing)]);
+    assert_eq!(
+        misplaced_separators("1_._5_e_5_f64", 10),
+        vec![
+            (1..2, BeforeDot),
+            (3..4, AfterDot),
+            (5..6, BeforeExponent),
+            (7..8, AfterExponent),
+            (9..10, Trailing)
+        ]
+    );
+    assert_eq!(misplaced_separators("1e-_5", 5), vec![(3..4, AfterExponent)]);
+    assert_eq!(misplaced_separators("0o_", 3), vec![(2..3, AfterPrefix)]);
+}
+
+#[test]
+fn test_str() {
+    check(
+        r#""abc""#,
+        expect![[r#"
+        Some(Str("abc"))
+    "#]],
+    );
+    check(
+        r#""a\tb\q""#,
+        expect![[r#"
+        None
+        Escape(InvalidEscape) 5..7
+    "#]],
+    );
+    check(
+        "\"a\\\n  b\"",
+        expect![[r#"
+        Some(Str("ab"))
+    "#]],
+    );
+    check(
+        r##"r#"a\n"#"##,
+        expect![[r#"
+        Some(Str("a\\n"))
+    "#]],
+    );
+    check(
+        r#"b"a\xFF""#,
+        expect![[r#"
+        Some(ByteStr([97, 255]))
+    "#]],
+    );
+    check(
+        r#"c"a\xFF\u{e9}""#,
+        expect![[r#"
+            Some(CStr([97, 255, 195, 169]))
+        "#]],
+    );
+    check(
+        r##"cr#"a\0"#"##,
+        expect![[r#"
+            Some(CStr([97, 92, 48]))
+        "#]],
+    );
+    check(
+        r#"c"a\0b""#,
+        expect![[r#"
+            None
+            Escape(NulInCStr) 3..5
+        "#]],
+    );
+    check(
+        r#""a"suffix"#,
+        expect![[r#"
+        None
+        InvalidSuffix 3..9
+    "#]],
+    );
+    check(
+        r#""abc"#,
+        expect![[r#"
+        None
+        Lex(UnterminatedStr) 0..4
+    "#]],
+    );
+}
+
+#[test]
+fn test_char() {
+    check(
+        r"'\u{1F600}'",
+        expect![[r#"
+        Some(Char('😀'))
+    "#]],
+    );
+    check(
+        r"b'\n'",
+        expect![[r#"
+        Some(Byte(10))
+    "#]],
+    );
+    check(
+        "'ab'",
+        expect![[r#"
+        None
+        Escape(MoreThanOneChar) 1..3
+    "#]],
+    );
+}
@@ -1,29 +1,279 @@
-use std::str::Chars;
+use crate::{Continuation, DocStyle, LexerConfig};
+use core::str::Chars;
+
+/// A sequence of chars which can be lexed by a [`Cursor`].
+///
+/// Offsets and lengths produced by the lexer are measured in the code units of
+/// the source: bytes for `&str` input and `u16`s for
+/// [`Utf16Chars`](crate::utf16::Utf16Chars).
+pub trait Source: Iterator<Item = char> + Clone {
+    /// Returns the number of code units which have not been consumed yet.
+    fn remaining_len(&self) -> usize;
+
+    /// Returns the remaining input if the source is UTF-8 encoded, which
+    /// enables the word-at-a-time fast paths of the `simd` feature.
+    #[cfg(feature = "simd")]
+    fn remaining_utf8(&self) -> Option<&str> {
+        None
+    }
+
+    /// Skips the first `len` bytes of [`Source::remaining_utf8`], which must
+    /// end at a char boundary.
+    #[cfg(feature = "simd")]
+    fn skip_utf8(&mut self, len: usize) {
+        let mut skipped = 0;
+        while skipped < len {
+            skipped += self.next().map_or(len, char::len_utf8);
+        }
+    }
+}
+
+impl Source for Chars<'_> {
+    fn remaining_len(&self) -> usize {
+        self.as_str().len()
+    }
+
+    #[cfg(feature = "simd")]
+    fn remaining_utf8(&self) -> Option<&str> {
+        Some(self.as_str())
+    }
+
+    #[cfg(feature = "simd")]
+    fn skip_utf8(&mut self, len: usize) {
+        *self = self.as_str()[len..].chars();
+    }
+}
 
 /// Peekable iterator over a char sequence.
 ///
 /// Next characters can be peeked via `first` method,
 /// and position can be shifted forward via `bump` method.
-pub(crate) struct Cursor<'a> {
-    initial_len: usize,
+///
+/// Besides lexing Rust with [`Cursor::advance_token`], the cursor provides
+/// the low-level scanning routines the Rust lexer is built from, such as
+/// [`Cursor::eat_identifier`] or [`Cursor::double_quoted_string`], for
+/// lexers of Rust-like languages.
+#[derive(Clone)]
+pub struct Cursor<'a, S = Chars<'a>> {
+    /// Length of the whole input, including the already consumed part.
+    len: usize,
+    /// Offset of the start of the current token.
+    token_start: usize,
+    /// Chars of the current token, starting at `token_start`.
+    token_chars: S,
     /// Iterator over chars. Slightly faster than a &str.
-    chars: Chars<'a>,
+    chars: S,
+    /// The literal or comment the next token continues, see
+    /// [`CursorState::continuation`].
+    pub(crate) continuation: Option<Continuation>,
+    pub(crate) config: &'a LexerConfig<'a>,
     #[cfg(debug_assertions)]
     prev: char,
 }
 
 pub(crate) const EOF_CHAR: char = '\0';
 
+/// Position of a [`Cursor`], from which lexing can be resumed with
+/// [`Cursor::resume`].
+///
+/// The cursor stops at a token boundary, unless the input was split into
+/// chunks in the middle of a literal or comment. In that case the state also
+/// records the [`Continuation`] to lex the rest of it with. The state is plain
+/// data and can be persisted with [`CursorState::to_bytes`] and
+/// [`CursorState::from_bytes`], e.g. to continue lexing a large input in
+/// another task or process.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CursorState {
+    pos: usize,
+    continuation: Option<Continuation>,
+}
+
+impl CursorState {
+    /// Creates a state at `pos`, inside of the literal or comment described
+    /// by `continuation` if there is one.
+    pub fn new(pos: usize, continuation: Option<Continuation>) -> CursorState {
+        CursorState { pos, continuation }
+    }
+
+    /// Offset into the input at which lexing continues.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    /// The literal or comment the first token after [`CursorState::pos`]
+    /// continues, see [`Cursor::continue_token`].
+    pub fn continuation(&self) -> Option<Continuation> {
+        self.continuation
+    }
+
+    /// Encodes the state into a platform-independent byte representation.
+    ///
+    /// The position comes first, followed by a tag for the kind of
+    /// continuation, the doc style of a block comment and the number of
+    /// hashes of a raw string or the depth of a block comment, all integers
+    /// little-endian.
+    pub fn to_bytes(&self) -> [u8; 18] {
+        let (tag, doc_style, payload) = match self.continuation {
+            None => (0, None, 0),
+            Some(Continuation::Str) => (1, None, 0),
+            Some(Continuation::ByteStr) => (2, None, 0),
+            Some(Continuation::CStr) => (3, None, 0),
+            Some(Continuation::RawStr { n_hashes }) => (4, None, u64::from(n_hashes)),
+            Some(Continuation::RawByteStr { n_hashes }) => (5, None, u64::from(n_hashes)),
+            Some(Continuation::RawCStr { n_hashes }) => (6, None, u64::from(n_hashes)),
+            Some(Continuation::BlockComment { doc_style, depth }) => (7, doc_style, depth as u64),
+        };
+        let mut bytes = [0; 18];
+        bytes[..8].copy_from_slice(&(self.pos as u64).to_le_bytes());
+        bytes[8] = tag;
+        bytes[9] = match doc_style {
+            None => 0,
+            Some(DocStyle::Outer) => 1,
+            Some(DocStyle::Inner) => 2,
+        };
+        bytes[10..].copy_from_slice(&payload.to_le_bytes());
+        bytes
+    }
+
+    /// Decodes a state produced by [`CursorState::to_bytes`]. Returns `None` if
+    /// the bytes are malformed or the position or depth don't fit into
+    /// `usize` on this platform.
+    pub fn from_bytes(bytes: [u8; 18]) -> Option<CursorState> {
+        let int = |bytes: &[u8]| u64::from_le_bytes(bytes.try_into().unwrap());
+        let pos = usize::try_from(int(&bytes[..8])).ok()?;
+        let payload = int(&bytes[10..]);
+        let n_hashes = u32::try_from(payload).ok();
+        let continuation = match bytes[8] {
+            0 => None,
+            1 => Some(Continuation::Str),
+            2 => Some(Continuation::ByteStr),
+            3 => Some(Continuation::CStr),
+            4 => Some(Continuation::RawStr { n_hashes: n_hashes? }),
+            5 => Some(Continuation::RawByteStr { n_hashes: n_hashes? }),
+            6 => Some(Continuation::RawCStr { n_hashes: n_hashes? }),
+            7 => {
+                let doc_style = match bytes[9] {
+                    0 => None,
+                    1 => Some(DocStyle::Outer),
+                    2 => Some(DocStyle::Inner),
+                    _ => return None,
+                };
+                let depth = usize::try_from(payload).ok()?;
+                Some(Continuation::BlockComment { doc_style, depth })
+            }
+            _ => return None,
+        };
+        Some(CursorState { pos, continuation })
+    }
+}
+
+/// A saved position of a [`Cursor`], see [`Cursor::checkpoint`].
+///
+/// Unlike a [`CursorState`], a checkpoint can be taken in the middle of a
+/// token and includes the part of the current token consumed so far, but it
+/// is only meaningful to the cursor it was taken from.
+#[derive(Clone)]
+pub struct Checkpoint<S> {
+    token_start: usize,
+    token_chars: S,
+    chars: S,
+    continuation: Option<Continuation>,
+    #[cfg(debug_assertions)]
+    prev: char,
+}
+
 impl<'a> Cursor<'a> {
-    pub(crate) fn new(input: &'a str) -> Cursor<'a> {
+    /// Creates a cursor at the start of `input`.
+    pub fn new(input: &'a str) -> Cursor<'a> {
+        Cursor::with_config(input, &LexerConfig::DEFAULT)
+    }
+
+    /// Creates a cursor at the start of `input` which lexes according to
+    /// `config`.
+    pub fn with_config(input: &'a str, config: &'a LexerConfig<'a>) -> Cursor<'a> {
+        Cursor::from_source(input.chars(), config)
+    }
+
+    /// Creates a cursor which continues lexing `input` from a previously saved
+    /// state. Returns `None` if the saved position is not a char boundary of
+    /// `input`, which means that the state belongs to a different input.
+    ///
+    /// If the state is inside of a literal or comment, the first token is the
+    /// rest of it, as lexed by [`Cursor::continue_token`].
+    pub fn resume(input: &'a str, state: CursorState) -> Option<Cursor<'a>> {
+        Cursor::resume_with_config(input, &LexerConfig::DEFAULT, state)
+    }
+
+    /// Like [`Cursor::resume`], but lexes according to `config`.
+    pub fn resume_with_config(
+        input: &'a str,
+        config: &'a LexerConfig<'a>,
+        state: CursorState,
+    ) -> Option<Cursor<'a>> {
+        let tail = input.get(state.pos..)?;
+        let mut cursor = Cursor::from_source(tail.chars(), config);
+        cursor.len = input.len();
+        cursor.token_start = state.pos;
+        cursor.continuation = state.continuation;
+        Some(cursor)
+    }
+}
+
+impl<'a, S: Source> Cursor<'a, S> {
+    /// Creates a cursor at the start of an arbitrary char source.
+    pub fn from_source(source: S, config: &'a LexerConfig<'a>) -> Cursor<'a, S> {
         Cursor {
-            initial_len: input.len(),
-            chars: input.chars(),
+            len: source.remaining_len(),
+            token_start: 0,
+            token_chars: source.clone(),
+            chars: source,
+            continuation: None,
+            config,
             #[cfg(debug_assertions)]
             prev: EOF_CHAR,
         }
     }
 
+    /// Saves the current position, see [`CursorState`].
+    ///
+    /// This should only be called between tokens, i.e. not while a token is
+    /// being lexed by a caller building on the cursor.
+    pub fn state(&self) -> CursorState {
+        CursorState { pos: self.pos(), continuation: self.continuation }
+    }
+
+    /// Saves the position of the cursor, to which it can be moved back with
+    /// [`Cursor::rewind`], e.g. after trying to lex a raw string prefix which
+    /// turns out to be an identifier.
+    pub fn checkpoint(&self) -> Checkpoint<S> {
+        Checkpoint {
+            token_start: self.token_start,
+            token_chars: self.token_chars.clone(),
+            chars: self.chars.clone(),
+            continuation: self.continuation,
+            #[cfg(debug_assertions)]
+            prev: self.prev,
+        }
+    }
+
+    /// Moves the cursor back to `checkpoint`, which has to be taken from this
+    /// cursor. Rewinding past the start of the current token is allowed and
+    /// restores the token start as well.
+    pub fn rewind(&mut self, checkpoint: Checkpoint<S>) {
+        debug_assert!(
+            checkpoint.chars.remaining_len() <= self.len,
+            "checkpoint of a different cursor"
+        );
+        self.token_start = checkpoint.token_start;
+        self.token_chars = checkpoint.token_chars;
+        self.chars = checkpoint.chars;
+        self.continuation = checkpoint.continuation;
+        #[cfg(debug_assertions)]
+        {
+            self.prev = checkpoint.prev;
+        }
+    }
+
     /// Returns the last eaten symbol (or `'\0'` in release builds).
     /// (For debug assertions only.)
     pub(crate) fn prev(&self) -> char {
@@ -42,36 +292,71 @@ pub(crate) fn prev(&self) -> char {
     /// If requested position doesn't exist, `EOF_CHAR` is returned.
     /// However, getting `EOF_CHAR` doesn't always mean actual end of file,
     /// it should be checked with `is_eof` method.
-    pub(crate) fn first(&self) -> char {
+    pub fn first(&self) -> char {
         // `.next()` optimizes better than `.nth(0)`
         self.chars.clone().next().unwrap_or(EOF_CHAR)
     }
 
     /// Peeks the second symbol from the input stream without consuming it.
-    pub(crate) fn second(&self) -> char {
+    pub fn second(&self) -> char {
         // `.next()` optimizes better than `.nth(1)`
         let mut iter = self.chars.clone();
         iter.next();
         iter.next().unwrap_or(EOF_CHAR)
     }
 
+    /// Peeks the third symbol from the input stream without consuming it.
+    pub fn third(&self) -> char {
+        let mut iter = self.chars.clone();
+        iter.next();
+        iter.next();
+        iter.next().unwrap_or(EOF_CHAR)
+    }
+
     /// Checks if there is nothing more to consume.
-    pub(crate) fn is_eof(&self) -> bool {
-        self.chars.as_str().is_empty()
+    pub fn is_eof(&self) -> bool {
+        self.chars.remaining_len() == 0
+    }
+
+    /// Returns the offset of the cursor into the whole input.
+    pub(crate) fn pos(&self) -> usize {
+        self.len - self.chars.remaining_len()
+    }
+
+    /// Checks if the already consumed part of the current token equals `s`.
+    pub(crate) fn consumed_eq(&self, s: &str) -> bool {
+        let mut consumed = self.token_chars.clone();
+        let mut expected = s.chars();
+        while consumed.remaining_len() > self.chars.remaining_len() {
+            if consumed.next() != expected.next() {
+                return false;
+            }
+        }
+        expected.next().is_none()
+    }
+
+    /// Checks if the input following the cursor starts with `s`.
+    pub(crate) fn rest_starts_with(&self, s: &str) -> bool {
+        let mut chars = self.chars.clone();
+        s.chars().all(|c| chars.next() == Some(c))
     }
 
     /// Returns amount of already consumed symbols.
-    pub(crate) fn len_consumed(&self) -> usize {
-        self.initial_len - self.chars.as_str().len()
+    pub fn len_consumed(&self) -> usize {
+        self.pos() - self.token_start
     }
 
-    /// Resets the number of bytes consumed to 0.
-    pub(crate) fn reset_len_consumed(&mut self) {
-        self.initial_len = self.chars.as_str().len();
+    /// Resets the number of bytes consumed to 0, i.e. starts a new token.
+    ///
+    /// [`Cursor::advance_token`] does this itself, lexers built on the
+    /// primitives of the cursor call it before each token.
+    pub fn reset_len_consumed(&mut self) {
+        self.token_start = self.pos();
+        self.token_chars = self.chars.clone();
     }
 
     /// Moves to the next character.
-    pub(crate) fn bump(&mut self) -> Option<char> {
+    pub fn bump(&mut self) -> Option<char> {
         let c = self.chars.next()?;
 
         #[cfg(debug_assertions)]
@@ -83,11 +368,31 @@ pub(crate) fn bump(&mut self) -> Option<char> {
     }
 
     /// Eats symbols while predicate returns true or until the end of file is reached.
-    pub(crate) fn eat_while(&mut self, mut predicate: impl FnMut(char) -> bool) {
-        // It was tried making optimized version of this for eg. line comments, but
-        // LLVM can inline all of this and compile it down to fast iteration over bytes.
+    pub fn eat_while(&mut self, mut predicate: impl FnMut(char) -> bool) {
+        // With the `simd` feature, callers scanning long tokens like line
+        // comments skip most of them with `skip_prefix` first.
         while predicate(self.first()) && !self.is_eof() {
             self.bump();
         }
     }
+
+    /// Skips the prefix of the remaining input whose length `prefix_len`
+    /// returns, if the source is UTF-8. Used to eat the bulk of a token before
+    /// [`Cursor::eat_while`] eats the rest.
+    #[cfg(feature = "simd")]
+    pub(crate) fn skip_prefix(&mut self, prefix_len: fn(&[u8]) -> usize) {
+        let rest = match self.chars.remaining_utf8() {
+            Some(rest) => rest,
+            None => return,
+        };
+        let len = prefix_len(rest.as_bytes());
+        if len == 0 {
+            return;
+        }
+        #[cfg(debug_assertions)]
+        {
+            self.prev = rest[..len].chars().next_back().unwrap();
+        }
+        self.chars.skip_utf8(len);
+    }
 }
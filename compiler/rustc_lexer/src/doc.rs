@@ -0,0 +1,220 @@
+//! Decoding of doc comments into the Markdown text they contain.
+//!
+//! Doc comments are decorated with comment markers, leading `*`s and
+//! indentation. [`leading_doc_comment`] strips the decoration the same way
+//! rustdoc does and keeps a mapping from the decoded text back to the source,
+//! so results computed on the text can be reported at the right place.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize, tokenize_with_offsets, DocStyle, Token, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// The decoded text of consecutive doc comments, see [`leading_doc_comment`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DocComment {
+    pub style: DocStyle,
+    /// The Markdown text, with one line per doc comment line.
+    pub text: String,
+    /// Sorted `(text offset, source offset)` pairs for the start of every line.
+    line_starts: Vec<(usize, usize)>,
+}
+
+impl DocComment {
+    /// Maps an offset into [`DocComment::text`] to the offset into the source.
+    pub fn source_offset(&self, offset: usize) -> usize {
+        let idx = self.line_starts.partition_point(|&(start, _)| start <= offset) - 1;
+        let (text_start, source_start) = self.line_starts[idx];
+        source_start + offset - text_start
+    }
+
+    /// Maps a range of [`DocComment::text`] to the range in the source.
+    pub fn source_range(&self, range: Range<usize>) -> Range<usize> {
+        self.source_offset(range.start)..self.source_offset(range.end)
+    }
+
+    /// Returns the first paragraph of the text, along with its range in the
+    /// source. Returns `None` if the text is blank.
+    ///
+    /// Lines of the paragraph are separated by `\n`; if they come from
+    /// different comments, the source range spans the markers in between.
+    pub fn summary(&self) -> Option<(&str, Range<usize>)> {
+        let mut range: Option<Range<usize>> = None;
+        let mut offset = 0;
+        for line in self.text.split('\n') {
+            let line_range = offset..offset + line.len();
+            offset = line_range.end + 1;
+            if line.trim().is_empty() {
+                if range.is_some() {
+                    break;
+                }
+                continue;
+            }
+            match &mut range {
+                Some(range) => range.end = line_range.end,
+                None => range = Some(line_range),
+            }
+        }
+        let range = range?;
+        Some((&self.text[range.clone()], self.source_range(range)))
+    }
+
+    /// Lexes `range` of the text as Rust code, e.g. the contents of a
+    /// [`CodeSpan`]. Along with each token, its range in the source is
+    /// returned.
+    pub fn tokenize_range(
+        &self,
+        range: Range<usize>,
+    ) -> impl Iterator<Item = (Token, Range<usize>)> + '_ {
+        let start = range.start;
+        tokenize(&self.text[range]).with_ranges().map(move |(range, token)| {
+            (token, self.source_range(start + range.start..start + range.end))
+        })
+    }
+}
+
+/// Decodes the doc comments at the start of `src`, skipping leading
+/// whitespace and regular comments.
+///
+/// Consecutive doc comments of the same style are merged, as rustdoc does.
+/// Returns `None` if `src` does not start with a doc comment.
+pub fn leading_doc_comment(src: &str) -> Option<DocComment> {
+    let mut style = None;
+    let mut lines = Vec::new();
+    for (range, kind) in tokenize_with_offsets(src) {
+        let (doc_style, is_block) = match kind {
+            kind if kind.is_trivia() => continue,
+            TokenKind::LineComment { doc_style: Some(doc_style) } => (doc_style, false),
+            TokenKind::BlockComment { doc_style: Some(doc_style), .. } => (doc_style, true),
+            _ => break,
+        };
+        if *style.get_or_insert(doc_style) != doc_style {
+            break;
+        }
+        let text = &src[range.clone()];
+        if is_block {
+            block_comment_lines(text, range.start, &mut lines);
+        } else {
+            let content = &text[3..];
+            lines.push((range.start + 3, content.strip_suffix('\r').unwrap_or(content)));
+        }
+    }
+    let style = style?;
+
+    // Remove the indentation common to all non-blank lines.
+    let indent = lines
+        .iter()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(_, line)| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    let mut res = DocComment { style, text: String::new(), line_starts: Vec::new() };
+    for (i, (offset, line)) in lines.into_iter().enumerate() {
+        if i > 0 {
+            res.text.push('\n');
+        }
+        let (offset, line) =
+            if line.trim().is_empty() { (offset, "") } else { (offset + indent, &line[indent..]) };
+        res.line_starts.push((res.text.len(), offset));
+        res.text.push_str(line);
+    }
+    Some(res)
+}
+
+/// Splits the contents of a block doc comment into lines with their source
+/// offsets, dropping blank first and last lines and a column of leading `*`s.
+fn block_comment_lines<'a>(text: &'a str, start: usize, out: &mut Vec<(usize, &'a str)>) {
+    let content = &text[3..];
+    let content = content.strip_suffix("*/").unwrap_or(content);
+    let mut lines = Vec::new();
+    let mut offset = start + 3;
+    for line in content.split('\n') {
+        lines.push((offset, line.strip_suffix('\r').unwrap_or(line)));
+        offset += line.len() + 1;
+    }
+    if lines.len() > 1 && lines[0].1.trim().is_empty() {
+        lines.remove(0);
+    }
+    if lines.len() > 1 && lines[lines.len() - 1].1.trim().is_empty() {
+        lines.pop();
+    }
+    if lines.iter().all(|(_, line)| line.trim_start().starts_with('*')) {
+        for (offset, line) in &mut lines {
+            let stripped = &line.trim_start()[1..];
+            *offset += line.len() - stripped.len();
+            *line = stripped;
+        }
+    }
+    out.extend(lines);
+}
+
+/// An inline code span in Markdown text, see [`code_spans`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeSpan {
+    /// Range of the whole span, including the backticks.
+    pub range: Range<usize>,
+    /// Range of the code, without the backticks and the single space
+    /// CommonMark strips on each side when both are present.
+    pub code: Range<usize>,
+}
+
+/// Finds the inline code spans in Markdown text, such as [`DocComment::text`].
+///
+/// Follows the CommonMark rules: a span opened by a run of backticks is
+/// closed by the next run of the same length, so ``` `` a ` b `` ``` is a
+/// single span containing a backtick. Backslash-escaped backticks don't open
+/// a span.
+pub fn code_spans(text: &str) -> Vec<CodeSpan> {
+    let bytes = text.as_bytes();
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'`' => {
+                let open = backtick_run(bytes, i);
+                i = open.end;
+                // Backslashes have no special meaning inside code spans.
+                let mut j = i;
+                while j < bytes.len() {
+                    if bytes[j] != b'`' {
+                        j += 1;
+                        continue;
+                    }
+                    let close = backtick_run(bytes, j);
+                    j = close.end;
+                    if close.len() == open.len() {
+                        res.push(code_span(text, open.start..close.end, open.len()));
+                        i = close.end;
+                        break;
+                    }
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    res
+}
+
+/// Returns the range of the run of backticks starting at `start`.
+fn backtick_run(bytes: &[u8], start: usize) -> Range<usize> {
+    let len = bytes[start..].iter().take_while(|&&b| b == b'`').count();
+    start..start + len
+}
+
+fn code_span(text: &str, range: Range<usize>, ticks: usize) -> CodeSpan {
+    let mut code = range.start + ticks..range.end - ticks;
+    let content = &text[code.clone()];
+    let is_space = |c: Option<char>| matches!(c, Some(' ') | Some('\n'));
+    if is_space(content.chars().next())
+        && is_space(content.chars().next_back())
+        && !content.trim_matches(|c| c == ' ' || c == '\n').is_empty()
+    {
+        code = code.start + 1..code.end - 1;
+    }
+    CodeSpan { range, code }
+}
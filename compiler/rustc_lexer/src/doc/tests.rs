@@ -0,0 +1,96 @@
+use super::*;
+
+#[test]
+fn test_line_comments() {
+    let src = "/// Summary\n///\n/// More text.\nfn foo() {}";
+    let doc = leading_doc_comment(src).unwrap();
+    assert_eq!(doc.style, DocStyle::Outer);
+    assert_eq!(doc.text, "Summary\n\nMore text.");
+    assert_eq!(doc.source_offset(0), 4);
+    assert_eq!(&src[doc.source_range(9..19)], "More text.");
+}
+
+#[test]
+fn test_common_indentation() {
+    let doc = leading_doc_comment("//!  a\n//!    b\n//!\n").unwrap();
+    assert_eq!(doc.style, DocStyle::Inner);
+    assert_eq!(doc.text, "a\n  b\n");
+}
+
+#[test]
+fn test_block_comments() {
+    let src = "/**\n * First line\n * second line\n */";
+    let doc = leading_doc_comment(src).unwrap();
+    assert_eq!(doc.text, "First line\nsecond line");
+    assert_eq!(&src[doc.source_range(11..22)], "second line");
+
+    let doc = leading_doc_comment("/** Short */").unwrap();
+    assert_eq!(doc.text, "Short ");
+}
+
+#[test]
+fn test_merging() {
+    let src = "// not docs\n/// a\n/** b */\n// c\n/// d\n//! e\n";
+    assert_eq!(leading_doc_comment(src).unwrap().text, "a\nb \nd");
+    assert_eq!(leading_doc_comment("fn foo() {}\n/// a"), None);
+    assert_eq!(leading_doc_comment("// a"), None);
+}
+
+#[test]
+fn test_summary() {
+    let src = "///\n/// The first\n/// paragraph.\n///\n/// The second.\n";
+    let doc = leading_doc_comment(src).unwrap();
+    let (summary, range) = doc.summary().unwrap();
+    assert_eq!(summary, "The first\nparagraph.");
+    assert_eq!(&src[range], "The first\n/// paragraph.");
+
+    assert_eq!(leading_doc_comment("///\n///  \n").unwrap().summary(), None);
+}
+
+#[test]
+fn test_code_spans() {
+    fn check(text: &str, expected: &[&str]) {
+        let actual: Vec<_> = code_spans(text).into_iter().map(|span| &text[span.code]).collect();
+        assert_eq!(actual, expected);
+    }
+    check("Uses `Vec<T>` and `Option`.", &["Vec<T>", "Option"]);
+    check("`` a ` b ``", &["a ` b"]);
+    check("`  `", &["  "]);
+    check(r"\`not code` but `code`", &["but"]);
+    check(r"`a\`", &[r"a\"]);
+    check("``unclosed` `closed`", &[" "]);
+    check("no spans ``", &[]);
+}
+
+#[test]
+fn test_tokenize_code_span() {
+    let src = "/// Returns `x + 1`.\nfn foo() {}";
+    let doc = leading_doc_comment(src).unwrap();
+    let spans = code_spans(&doc.text);
+    assert_eq!(spans.len(), 1);
+    assert_eq!(&src[doc.source_range(spans[0].range.clone())], "`x + 1`");
+    let tokens: Vec<_> = doc
+        .tokenize_range(spans[0].code.clone())
+        .map(|(token, range)| (token.kind, &src[range]))
+        .collect();
+    assert_eq!(
+        tokens,
+        vec![
+            (TokenKind::Ident, "x"),
+            (TokenKind::Whitespace { line_endings: None, counts: None }, " "),
+            (TokenKind::Plus, "+"),
+            (TokenKind::Whitespace { line_endings: None, counts: None }, " "),
+            (
+                TokenKind::Literal {
+                    kind: crate::LiteralKind::Int {
+                        base: crate::Base::Decimal,
+                        empty_int: None,
+                        underscore_after_prefix: false,
+                    },
+                    suffix_start: 1
+                },
+                "1"
+            ),
+        ]
+    );
+}
@@ -0,0 +1,118 @@
+//! Lexing of sources which are not encoded as UTF-8.
+//!
+//! The input is transcoded to UTF-8 before lexing, and offsets into the
+//! transcoded text can be mapped back to exact offsets into the original
+//! bytes. The encoding is detected from a byte order mark, falling back to a
+//! declared encoding.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+pub use encoding_rs::Encoding;
+use encoding_rs::{DecoderResult, UTF_8};
+
+use crate::{tokenize, Token};
+
+#[cfg(test)]
+mod tests;
+
+/// Source text transcoded to UTF-8, see [`DecodedSource::decode`].
+#[derive(Clone, Debug)]
+pub struct DecodedSource {
+    text: String,
+    encoding: &'static Encoding,
+    /// Sorted `(text offset, original offset)` pairs, one for each decoded
+    /// run of chars.
+    offsets: Vec<(usize, usize)>,
+    original_len: usize,
+    had_errors: bool,
+}
+
+impl DecodedSource {
+    /// Decodes `bytes` using the encoding indicated by its byte order mark.
+    /// Without a byte order mark, the `declared` encoding is used, or UTF-8
+    /// if there is none. The byte order mark is not part of the decoded text.
+    ///
+    /// Malformed sequences are decoded as `U+FFFD REPLACEMENT CHARACTER`.
+    pub fn decode(bytes: &[u8], declared: Option<&'static Encoding>) -> DecodedSource {
+        let (encoding, bom_len) = match Encoding::for_bom(bytes) {
+            Some((encoding, bom_len)) => (encoding, bom_len),
+            None => (declared.unwrap_or(UTF_8), 0),
+        };
+        let mut decoder = encoding.new_decoder_without_bom_handling();
+        let mut text = String::with_capacity(bytes.len());
+        let mut offsets = Vec::new();
+        let mut had_errors = false;
+        // Feeding the decoder one byte at a time tells us which input bytes
+        // every char was decoded from. A malformed sequence may only be
+        // detected at the byte after it, so replacement chars are inserted
+        // here, at the offsets reported by the decoder.
+        let mut pos = bom_len;
+        let mut seq_start = bom_len;
+        while pos < bytes.len() {
+            let last = pos + 1 == bytes.len();
+            let text_len = text.len();
+            text.reserve(decoder.max_utf8_buffer_length_without_replacement(1).unwrap());
+            let (result, read) =
+                decoder.decode_to_string_without_replacement(&bytes[pos..pos + 1], &mut text, last);
+            pos += read;
+            if text.len() > text_len {
+                offsets.push((text_len, seq_start));
+                seq_start = pos;
+            }
+            match result {
+                DecoderResult::InputEmpty => {}
+                // The last `good` bytes read follow the malformed sequence,
+                // which is `bad` bytes long.
+                DecoderResult::Malformed(bad, good) => {
+                    let malformed_end = pos - usize::from(good);
+                    offsets.push((text.len(), malformed_end - usize::from(bad)));
+                    text.push('\u{FFFD}');
+                    seq_start = malformed_end;
+                    had_errors = true;
+                }
+                DecoderResult::OutputFull => unreachable!("space was reserved"),
+            }
+        }
+        DecodedSource { text, encoding, offsets, original_len: bytes.len(), had_errors }
+    }
+
+    /// The decoded text.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The encoding the text was decoded from.
+    pub fn encoding(&self) -> &'static Encoding {
+        self.encoding
+    }
+
+    /// Returns true if malformed sequences were replaced while decoding.
+    pub fn had_errors(&self) -> bool {
+        self.had_errors
+    }
+
+    /// Maps an offset into the decoded text, which must be at a char
+    /// boundary, to the offset into the original bytes.
+    pub fn original_offset(&self, offset: usize) -> usize {
+        assert!(self.text.is_char_boundary(offset));
+        if offset == self.text.len() {
+            return self.original_len;
+        }
+        match self.offsets.binary_search_by_key(&offset, |&(text_offset, _)| text_offset) {
+            Ok(idx) => self.offsets[idx].1,
+            // Several chars were decoded at once, all of them are attributed
+            // to the start of the sequence they were decoded from.
+            Err(idx) => self.offsets[idx - 1].1,
+        }
+    }
+
+    /// Lexes the decoded text. Along with each token, its range in the
+    /// original bytes is returned.
+    pub fn tokenize(&self) -> impl Iterator<Item = (Token, Range<usize>)> + '_ {
+        tokenize(&self.text).with_ranges().map(move |(range, token)| {
+            (token, self.original_offset(range.start)..self.original_offset(range.end))
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use super::*;
+use crate::TokenKind;
+use encoding_rs::{UTF_16LE, WINDOWS_1252};
+
+fn original_ranges(source: &DecodedSource) -> Vec<(TokenKind, Range<usize>)> {
+    source.tokenize().map(|(token, range)| (token.kind, range)).collect()
+}
+
+#[test]
+fn test_latin1() {
+    // `caf\u{e9} = 1` in windows-1252.
+    let source = DecodedSource::decode(b"caf\xe9 = 1", Some(WINDOWS_1252));
+    assert_eq!(source.text(), "caf\u{e9} = 1");
+    assert_eq!(source.encoding(), WINDOWS_1252);
+    assert!(!source.had_errors());
+    assert_eq!(
+        original_ranges(&source)[..2],
+        [
+            (TokenKind::Ident, 0..4),
+            (TokenKind::Whitespace { line_endings: None, counts: None }, 4..5)
+        ]
+    );
+}
+
+#[test]
+fn test_utf16_with_bom() {
+    let mut bytes = vec![0xFF, 0xFE];
+    bytes.extend("x \u{1F980}".encode_utf16().flat_map(|unit| unit.to_le_bytes()));
+    let source = DecodedSource::decode(&bytes, None);
+    assert_eq!(source.encoding(), UTF_16LE);
+    assert_eq!(source.text(), "x \u{1F980}");
+    assert_eq!(source.original_offset(1), 4);
+    assert_eq!(source.original_offset(2), 6);
+    assert_eq!(source.original_offset(6), 10);
+    let ranges: Vec<_> = original_ranges(&source).into_iter().map(|(_, range)| range).collect();
+    assert_eq!(ranges, [2..4, 4..6, 6..10]);
+}
+
+#[test]
+fn test_malformed_utf8() {
+    let source = DecodedSource::decode(b"a\xffb", None);
+    assert_eq!(source.text(), "a\u{FFFD}b");
+    assert!(source.had_errors());
+    assert_eq!(source.original_offset(4), 2);
+}
+
+#[test]
+fn test_malformed_utf8_before_char() {
+    // A truncated sequence, then `\u{e9}`.
+    let source = DecodedSource::decode(b"\xc3\xc3\xa9x", None);
+    assert_eq!(source.text(), "\u{FFFD}\u{e9}x");
+    assert_eq!(source.original_offset(3), 1);
+    assert_eq!(source.original_offset(5), 3);
+    // The replacement char and `a` are decoded in a single call.
+    let source = DecodedSource::decode(b"\xe2\x82ab", None);
+    assert_eq!(source.text(), "\u{FFFD}ab");
+    assert_eq!(source.original_offset(3), 2);
+    assert_eq!(source.original_offset(4), 3);
+}
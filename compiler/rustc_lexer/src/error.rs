@@ -0,0 +1,538 @@
+//! Lexing errors as structured data.
+//!
+//! The lexer itself never fails: problems are encoded in the payloads of the
+//! tokens, like `terminated: false`. [`tokenize_checked`] collects them into
+//! a separate list for consumers which only care about the errors.
+//!
+//! [`lex_diagnostics`] goes further and combines the problems found by the
+//! lexer, by unescaping literals and by the auxiliary scans into a single
+//! list of [`LexDiagnostic`]s.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::lines::{stray_boms, StrayBomKind};
+use crate::unescape::{self, EscapeError, Mode};
+use crate::{
+    tokenize, tokenize_with_edition, Base, Edition, LiteralKind, MissingDigits, RawStrError, Token,
+    TokenKind,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A problem found while lexing, see [`tokenize_checked`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LexError {
+    /// Byte range of the offending token in the input.
+    pub range: Range<usize>,
+    pub kind: LexErrorKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum LexErrorKind {
+    /// `/* comment` without the closing `*/`.
+    UnterminatedBlockComment,
+    /// `'a` without the closing quote, which isn't a lifetime.
+    UnterminatedChar,
+    /// `b'a` without the closing quote.
+    UnterminatedByte,
+    /// `"abc` without the closing quote.
+    UnterminatedStr,
+    /// `b"abc` without the closing quote.
+    UnterminatedByteStr,
+    /// `c"abc` without the closing quote.
+    UnterminatedCStr,
+    /// A malformed raw string or raw byte string.
+    RawStr(RawStrError),
+    /// A base prefix without digits, e.g. `0x`.
+    EmptyInt,
+    /// An exponent without digits, e.g. `1e`.
+    EmptyExponent,
+    /// A float with a base prefix, e.g. `0b1.0`.
+    NonDecimalFloat(Base),
+    /// A lifetime starting with a digit, e.g. `'1a`.
+    LifetimeStartsWithNumber,
+    /// An identifier containing characters like emoji, which are not allowed
+    /// in identifiers.
+    InvalidIdent,
+    /// A reserved prefix like `foo#` or `foo"`.
+    UnknownPrefix,
+    /// A guarded string like `#"foo"#`, which is reserved since Rust 2024.
+    ReservedGuardedStr,
+    /// A character which doesn't start any token.
+    UnknownChar(char),
+}
+
+impl LexErrorKind {
+    /// The code of the error rustc reports for this problem, e.g. `"E0758"`,
+    /// or `None` if rustc reports it without a code.
+    ///
+    /// This mirrors the codes used by `rustc_parse::lexer` and has to be kept
+    /// in sync with it.
+    pub fn rustc_error_code(&self) -> Option<&'static str> {
+        match self {
+            LexErrorKind::UnterminatedBlockComment => Some("E0758"),
+            LexErrorKind::UnterminatedChar => Some("E0762"),
+            LexErrorKind::UnterminatedByte => Some("E0763"),
+            LexErrorKind::UnterminatedStr => Some("E0765"),
+            LexErrorKind::UnterminatedByteStr => Some("E0766"),
+            LexErrorKind::UnterminatedCStr => None,
+            LexErrorKind::RawStr(RawStrError::NoTerminator { .. }) => Some("E0748"),
+            LexErrorKind::RawStr(
+                RawStrError::InvalidStarter { .. } | RawStrError::TooManyDelimiters { .. },
+            ) => None,
+            LexErrorKind::EmptyInt => Some("E0768"),
+            LexErrorKind::EmptyExponent
+            | LexErrorKind::NonDecimalFloat(_)
+            | LexErrorKind::LifetimeStartsWithNumber
+            | LexErrorKind::InvalidIdent
+            | LexErrorKind::UnknownPrefix
+            | LexErrorKind::ReservedGuardedStr
+            | LexErrorKind::UnknownChar(_) => None,
+        }
+    }
+
+    /// Long-form help text explaining the error and how to fix it, in the
+    /// style of `rustc --explain`.
+    pub fn explain(&self) -> &'static str {
+        match self {
+            LexErrorKind::UnterminatedBlockComment => {
+                "A block comment started with `/*` must be closed with `*/`. Block comments \
+                 nest, so each `/*` inside the comment needs its own `*/` as well."
+            }
+            LexErrorKind::UnterminatedChar => {
+                "A character literal started with `'` must be closed with another `'`. If you \
+                 meant to write a lifetime, it has to start with a letter or `_`."
+            }
+            LexErrorKind::UnterminatedByte => {
+                "A byte literal started with `b'` must be closed with `'`."
+            }
+            LexErrorKind::UnterminatedStr
+            | LexErrorKind::UnterminatedByteStr
+            | LexErrorKind::UnterminatedCStr => {
+                "A string literal must be closed with `\"`. Quotes inside the string have to be \
+                 escaped as `\\\"`, which also means that a string ending in a backslash needs \
+                 to escape it as `\\\\`."
+            }
+            LexErrorKind::RawStr(RawStrError::InvalidStarter { .. }) => {
+                "A raw string starts with `r`, followed by any number of `#` and a `\"`. Other \
+                 characters are not allowed between the `r` and the opening quote."
+            }
+            LexErrorKind::RawStr(RawStrError::NoTerminator { .. }) => {
+                "A raw string has to be closed by a `\"` followed by as many `#` as the string \
+                 was opened with, e.g. `r##\"...\"##`. Add `#`s to the opening delimiter if the \
+                 string itself contains `\"#`."
+            }
+            LexErrorKind::RawStr(RawStrError::TooManyDelimiters { .. }) => {
+                "A raw string can be delimited by at most 65535 `#` characters, unless the \
+                 lexer is configured with a different limit."
+            }
+            LexErrorKind::EmptyInt => {
+                "A number with a base prefix like `0x`, `0o` or `0b` must contain at least one \
+                 digit after the prefix, e.g. `0x0`."
+            }
+            LexErrorKind::EmptyExponent => {
+                "The exponent of a float literal, started by `e` or `E`, must contain at least \
+                 one digit, e.g. `1e0`."
+            }
+            LexErrorKind::NonDecimalFloat(_) => {
+                "Float literals can only be written in decimal. Write the value in decimal, or \
+                 convert it at runtime, e.g. with `f64::from_bits`."
+            }
+            LexErrorKind::LifetimeStartsWithNumber => {
+                "Like other identifiers, the name of a lifetime can't start with a digit."
+            }
+            LexErrorKind::InvalidIdent => {
+                "Identifiers can only contain characters with the Unicode `XID_Continue` \
+                 property and must start with a character with the `XID_Start` property or `_`. \
+                 Emoji and most symbols are not allowed."
+            }
+            LexErrorKind::UnknownPrefix => {
+                "Since the 2021 edition, an identifier directly followed by `#`, `\"` or `'` is \
+                 reserved for new kinds of literals. Insert a space between the identifier and \
+                 the following character."
+            }
+            LexErrorKind::ReservedGuardedStr => {
+                "Since the 2024 edition, a string directly preceded by `#`, like `#\"foo\"#`, is \
+                 reserved for guarded string literals. Insert a space between the `#` and the \
+                 string, or write a raw string like `r#\"foo\"#` if that is what was meant."
+            }
+            LexErrorKind::UnknownChar(_) => {
+                "This character can't start any token. Characters outside of comments and \
+                 literals have to be part of Rust's syntax; look-alikes of ASCII punctuation, \
+                 like unicode quotes or dashes, have to be replaced by their ASCII version."
+            }
+        }
+    }
+}
+
+/// Like [`tokenize`], but additionally returns every problem found in the
+/// tokens as a [`LexError`].
+pub fn tokenize_checked(input: &str) -> (Vec<Token>, Vec<LexError>) {
+    let mut tokens = Vec::new();
+    let mut errors = Vec::new();
+    for (range, token) in tokenize(input).with_ranges() {
+        if let Some(kind) = error_kind(token.kind, &input[range.clone()]) {
+            errors.push(LexError { range, kind });
+        }
+        tokens.push(token);
+    }
+    (tokens, errors)
+}
+
+/// Returns where the digits of a number token of the given kind are missing,
+/// along with the resulting problem.
+fn missing_digits(kind: TokenKind) -> Option<(MissingDigits, LexErrorKind)> {
+    match kind {
+        TokenKind::Literal { kind: LiteralKind::Int { empty_int: Some(missing), .. }, .. } => {
+            Some((missing, LexErrorKind::EmptyInt))
+        }
+        TokenKind::Literal {
+            kind: LiteralKind::Float { empty_exponent: Some(missing), .. },
+            ..
+        } => Some((missing, LexErrorKind::EmptyExponent)),
+        _ => None,
+    }
+}
+
+/// Summary of the problems found by [`tokenize_into_vec`](crate::tokenize_into_vec).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorSummary {
+    /// Number of tokens with a problem.
+    pub error_count: usize,
+    /// The first problem in the input.
+    pub first_error: Option<LexError>,
+}
+
+impl ErrorSummary {
+    pub fn has_errors(&self) -> bool {
+        self.error_count > 0
+    }
+}
+
+/// Returns the problem encoded in a token of the given kind and text, if any.
+pub(crate) fn error_kind(kind: TokenKind, text: &str) -> Option<LexErrorKind> {
+    let kind = match kind {
+        TokenKind::BlockComment { terminated: false, .. } => LexErrorKind::UnterminatedBlockComment,
+        TokenKind::Literal { kind, .. } | TokenKind::PrefixedLiteral { kind, .. } => {
+            return literal_error_kind(kind);
+        }
+        TokenKind::Lifetime { starts_with_number: true } => LexErrorKind::LifetimeStartsWithNumber,
+        TokenKind::InvalidIdent => LexErrorKind::InvalidIdent,
+        TokenKind::UnknownPrefix { .. } => LexErrorKind::UnknownPrefix,
+        TokenKind::GuardedStr { .. } => LexErrorKind::ReservedGuardedStr,
+        TokenKind::Unknown => LexErrorKind::UnknownChar(text.chars().next()?),
+        _ => return None,
+    };
+    Some(kind)
+}
+
+pub(crate) fn literal_error_kind(kind: LiteralKind) -> Option<LexErrorKind> {
+    let kind = match kind {
+        LiteralKind::Char { terminated: false } => LexErrorKind::UnterminatedChar,
+        LiteralKind::Byte { terminated: false } => LexErrorKind::UnterminatedByte,
+        LiteralKind::Str { terminated: false, .. } => LexErrorKind::UnterminatedStr,
+        LiteralKind::ByteStr { terminated: false, .. } => LexErrorKind::UnterminatedByteStr,
+        LiteralKind::CStr { terminated: false, .. } => LexErrorKind::UnterminatedCStr,
+        LiteralKind::RawStr { err: Some(err), .. }
+        | LiteralKind::RawByteStr { err: Some(err), .. }
+        | LiteralKind::RawCStr { err: Some(err), .. } => LexErrorKind::RawStr(err),
+        LiteralKind::Int { empty_int: Some(_), .. } => LexErrorKind::EmptyInt,
+        LiteralKind::Float { empty_exponent: Some(_), .. } => LexErrorKind::EmptyExponent,
+        LiteralKind::Float { base, .. } if base != Base::Decimal => {
+            LexErrorKind::NonDecimalFloat(base)
+        }
+        _ => return None,
+    };
+    Some(kind)
+}
+
+/// How serious a problem is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Severity {
+    /// The code is accepted, but likely doesn't mean what was intended.
+    Warning,
+    /// The code is rejected.
+    Error,
+}
+
+/// What a [`LexDiagnostic`] is about.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DiagnosticCode {
+    /// A problem found by the lexer.
+    Lex(LexErrorKind),
+    /// A problem found while unescaping a literal.
+    Escape(EscapeError),
+    /// A byte order mark after the start of the input.
+    StrayBom(StrayBomKind),
+}
+
+impl DiagnosticCode {
+    pub fn severity(&self) -> Severity {
+        match self {
+            DiagnosticCode::Lex(_) | DiagnosticCode::StrayBom(_) => Severity::Error,
+            DiagnosticCode::Escape(err) => err.severity(),
+        }
+    }
+
+    /// See [`LexErrorKind::rustc_error_code`]. rustc reports escape errors and
+    /// stray byte order marks without a code.
+    pub fn rustc_error_code(&self) -> Option<&'static str> {
+        match self {
+            DiagnosticCode::Lex(kind) => kind.rustc_error_code(),
+            DiagnosticCode::Escape(_) | DiagnosticCode::StrayBom(_) => None,
+        }
+    }
+
+    /// See [`LexErrorKind::explain`] and [`EscapeError::explain`].
+    pub fn explain(&self) -> &'static str {
+        match self {
+            DiagnosticCode::Lex(kind) => kind.explain(),
+            DiagnosticCode::Escape(err) => err.explain(),
+            DiagnosticCode::StrayBom(_) => {
+                "A byte order mark (U+FEFF) is only allowed at the very start of a file. One in \
+                 the middle usually comes from concatenating files which each start with one, \
+                 and can be removed."
+            }
+        }
+    }
+
+    /// A stable key identifying the message, e.g. for looking up
+    /// translations.
+    pub fn message_key(&self) -> &'static str {
+        match self {
+            DiagnosticCode::Lex(kind) => match kind {
+                LexErrorKind::UnterminatedBlockComment => "lex-unterminated-block-comment",
+                LexErrorKind::UnterminatedChar => "lex-unterminated-char",
+                LexErrorKind::UnterminatedByte => "lex-unterminated-byte",
+                LexErrorKind::UnterminatedStr => "lex-unterminated-str",
+                LexErrorKind::UnterminatedByteStr => "lex-unterminated-byte-str",
+                LexErrorKind::UnterminatedCStr => "lex-unterminated-c-str",
+                LexErrorKind::RawStr(RawStrError::InvalidStarter { .. }) => {
+                    "lex-raw-str-invalid-starter"
+                }
+                LexErrorKind::RawStr(RawStrError::NoTerminator { .. }) => {
+                    "lex-raw-str-no-terminator"
+                }
+                LexErrorKind::RawStr(RawStrError::TooManyDelimiters { .. }) => {
+                    "lex-raw-str-too-many-delimiters"
+                }
+                LexErrorKind::EmptyInt => "lex-empty-int",
+                LexErrorKind::EmptyExponent => "lex-empty-exponent",
+                LexErrorKind::NonDecimalFloat(_) => "lex-non-decimal-float",
+                LexErrorKind::LifetimeStartsWithNumber => "lex-lifetime-starts-with-number",
+                LexErrorKind::InvalidIdent => "lex-invalid-ident",
+                LexErrorKind::UnknownPrefix => "lex-unknown-prefix",
+                LexErrorKind::ReservedGuardedStr => "lex-reserved-guarded-str",
+                LexErrorKind::UnknownChar(_) => "lex-unknown-char",
+            },
+            DiagnosticCode::Escape(err) => match err {
+                EscapeError::ZeroChars => "escape-zero-chars",
+                EscapeError::MoreThanOneChar => "escape-more-than-one-char",
+                EscapeError::LoneSlash => "escape-lone-slash",
+                EscapeError::InvalidEscape => "escape-invalid-escape",
+                EscapeError::BareCarriageReturn => "escape-bare-carriage-return",
+                EscapeError::BareCarriageReturnInRawString => {
+                    "escape-bare-carriage-return-in-raw-string"
+                }
+                EscapeError::EscapeOnlyChar => "escape-escape-only-char",
+                EscapeError::TooShortHexEscape => "escape-too-short-hex-escape",
+                EscapeError::InvalidCharInHexEscape => "escape-invalid-char-in-hex-escape",
+                EscapeError::OutOfRangeHexEscape => "escape-out-of-range-hex-escape",
+                EscapeError::NoBraceInUnicodeEscape => "escape-no-brace-in-unicode-escape",
+                EscapeError::InvalidCharInUnicodeEscape => "escape-invalid-char-in-unicode-escape",
+                EscapeError::EmptyUnicodeEscape => "escape-empty-unicode-escape",
+                EscapeError::UnclosedUnicodeEscape => "escape-unclosed-unicode-escape",
+                EscapeError::LeadingUnderscoreUnicodeEscape => {
+                    "escape-leading-underscore-unicode-escape"
+                }
+                EscapeError::OverlongUnicodeEscape => "escape-overlong-unicode-escape",
+                EscapeError::LoneSurrogateUnicodeEscape => "escape-lone-surrogate-unicode-escape",
+                EscapeError::OutOfRangeUnicodeEscape => "escape-out-of-range-unicode-escape",
+                EscapeError::UnicodeEscapeInByte => "escape-unicode-escape-in-byte",
+                EscapeError::NonAsciiCharInByte => "escape-non-ascii-char-in-byte",
+                EscapeError::NonAsciiCharInByteString => "escape-non-ascii-char-in-byte-string",
+                EscapeError::NulInCStr => "escape-nul-in-c-str",
+                EscapeError::UnskippedWhitespaceWarning => "escape-unskipped-whitespace",
+                EscapeError::MultipleSkippedLinesWarning => "escape-multiple-skipped-lines",
+            },
+            DiagnosticCode::StrayBom(StrayBomKind::ProbableConcatenation) => {
+                "stray-bom-probable-concatenation"
+            }
+            DiagnosticCode::StrayBom(StrayBomKind::Other) => "stray-bom",
+        }
+    }
+}
+
+/// A replacement fixing the problem of a [`LexDiagnostic`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Suggestion {
+    /// Byte range of the input to replace.
+    pub range: Range<usize>,
+    pub replacement: String,
+}
+
+/// A problem found in the input, see [`lex_diagnostics`].
+///
+/// The severity and message key follow from the code, so they are only
+/// serialized for the convenience of other consumers.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct LexDiagnostic {
+    /// Byte range of the problem in the input.
+    pub range: Range<usize>,
+    pub code: DiagnosticCode,
+    pub severity: Severity,
+    /// See [`DiagnosticCode::message_key`].
+    pub message_key: &'static str,
+    pub suggestion: Option<Suggestion>,
+}
+
+/// The fields a [`LexDiagnostic`] is deserialized from.
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DiagnosticFields {
+    range: Range<usize>,
+    code: DiagnosticCode,
+    suggestion: Option<Suggestion>,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LexDiagnostic {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let fields = DiagnosticFields::deserialize(deserializer)?;
+        Ok(LexDiagnostic::new(fields.range, fields.code, fields.suggestion))
+    }
+}
+
+impl LexDiagnostic {
+    fn new(range: Range<usize>, code: DiagnosticCode, suggestion: Option<Suggestion>) -> Self {
+        LexDiagnostic {
+            range,
+            code,
+            severity: code.severity(),
+            message_key: code.message_key(),
+            suggestion,
+        }
+    }
+}
+
+impl From<LexError> for LexDiagnostic {
+    fn from(error: LexError) -> Self {
+        LexDiagnostic::new(error.range, DiagnosticCode::Lex(error.kind), None)
+    }
+}
+
+/// Lexes `input` and returns all problems found by the lexer, in the escapes
+/// of literals, and by [`stray_boms`], ordered by their position.
+pub fn lex_diagnostics(input: &str) -> Vec<LexDiagnostic> {
+    lex_diagnostics_with_edition(input, Edition::Edition2021)
+}
+
+/// Like [`lex_diagnostics`], but lexes `input` as Rust `edition`, see
+/// [`tokenize_with_edition`].
+pub fn lex_diagnostics_with_edition(input: &str, edition: Edition) -> Vec<LexDiagnostic> {
+    let boms = stray_boms(input);
+    let mut res = Vec::new();
+    for (range, token) in tokenize_with_edition(input, edition).with_ranges() {
+        let text = &input[range.clone()];
+        if let TokenKind::GuardedStr { n_hashes, .. } = token.kind {
+            // Separating the `#`s from the string keeps the meaning the code
+            // had before Rust 2024.
+            let at = range.start + n_hashes as usize;
+            let suggestion = Suggestion { range: at..at, replacement: String::from(" ") };
+            let code = DiagnosticCode::Lex(LexErrorKind::ReservedGuardedStr);
+            res.push(LexDiagnostic::new(range, code, Some(suggestion)));
+            continue;
+        }
+        if let Some((missing, kind)) = missing_digits(token.kind) {
+            // Point at the prefix or exponent, and complete it with a digit.
+            let at = range.start + missing.expected_at as usize;
+            let suggestion = Suggestion { range: at..at, replacement: String::from("0") };
+            let range = range.start + missing.start as usize..at;
+            res.push(LexDiagnostic::new(range, DiagnosticCode::Lex(kind), Some(suggestion)));
+            continue;
+        }
+        match error_kind(token.kind, text) {
+            Some(LexErrorKind::UnknownChar('\u{FEFF}')) => {
+                if let Some(bom) = boms.iter().find(|bom| bom.offset == range.start) {
+                    let suggestion = match bom.kind {
+                        StrayBomKind::ProbableConcatenation => {
+                            Some(Suggestion { range: range.clone(), replacement: String::new() })
+                        }
+                        StrayBomKind::Other => None,
+                    };
+                    res.push(LexDiagnostic::new(
+                        range,
+                        DiagnosticCode::StrayBom(bom.kind),
+                        suggestion,
+                    ));
+                }
+            }
+            Some(kind @ LexErrorKind::RawStr(err)) => {
+                let suggestion = err.terminator_fix(range.len()).map(|(offset, replacement)| {
+                    let at = range.start + offset;
+                    Suggestion { range: at..at, replacement }
+                });
+                res.push(LexDiagnostic::new(range, DiagnosticCode::Lex(kind), suggestion));
+            }
+            Some(kind) => res.push(LexError { range, kind }.into()),
+            None => {
+                if let TokenKind::Literal { kind, suffix_start } = token.kind {
+                    escape_diagnostics(kind, text, suffix_start, range.start, &mut res);
+                }
+            }
+        }
+    }
+    res
+}
+
+/// Unescapes the contents of a literal token, which has no lexing errors,
+/// and collects the problems found.
+fn escape_diagnostics(
+    kind: LiteralKind,
+    text: &str,
+    suffix_start: usize,
+    offset: usize,
+    out: &mut Vec<LexDiagnostic>,
+) {
+    let mode = match kind {
+        LiteralKind::Char { .. } => Mode::Char,
+        LiteralKind::Byte { .. } => Mode::Byte,
+        LiteralKind::Str { .. } => Mode::Str,
+        LiteralKind::ByteStr { .. } => Mode::ByteStr,
+        LiteralKind::RawStr { .. } => Mode::RawStr,
+        LiteralKind::RawByteStr { .. } => Mode::RawByteStr,
+        LiteralKind::CStr { .. } => Mode::CStr,
+        LiteralKind::RawCStr { .. } => Mode::RawCStr,
+        LiteralKind::Int { .. } | LiteralKind::Float { .. } => return,
+    };
+    let contents = kind.content_range(text, suffix_start);
+    let literal = &text[contents.clone()];
+    let mut suggested_byte_str = false;
+    unescape::unescape_literal(literal, mode, &mut |range, result| {
+        if let Err(err) = result {
+            let original = &literal[range.clone()];
+            let range = offset + contents.start + range.start..offset + contents.start + range.end;
+            let suggestion = if err == EscapeError::NonAsciiCharInByteString {
+                // Raw strings have no escapes, so the whole literal has to
+                // become a byte string. Suggest that once, not for every char.
+                let replacement = unescape::to_escaped_byte_str_literal(literal);
+                let literal_range = offset..offset + suffix_start;
+                let suggestion = Suggestion { range: literal_range, replacement };
+                Some(suggestion).filter(|_| !core::mem::replace(&mut suggested_byte_str, true))
+            } else {
+                let replacement = unescape::suggest_replacement(&err, mode, original);
+                replacement.map(|replacement| Suggestion { range: range.clone(), replacement })
+            };
+            out.push(LexDiagnostic::new(range, DiagnosticCode::Escape(err), suggestion));
+        }
+    });
+}
@@ -0,0 +1,170 @@
+use super::*;
+use expect_test::{expect, Expect};
+
+fn check(src: &str, expect: Expect) {
+    let (tokens, errors) = tokenize_checked(src);
+    assert_eq!(tokens.iter().map(|token| token.len).sum::<usize>(), src.len());
+    let actual: String = errors
+        .iter()
+        .map(|error| format!("{:?} {:?}\n", &src[error.range.clone()], error.kind))
+        .collect();
+    expect.assert_eq(&actual)
+}
+
+#[test]
+fn test_no_errors() {
+    check("fn main() { let x = r#\"a\"#; 'a: loop {} }", expect![[r#""#]]);
+}
+
+#[test]
+fn test_errors() {
+    check(
+        "0x 1e 0b1.0 '1a foo# 🦀 ¤ r#~ b'a",
+        expect![[r#"
+            "0x" EmptyInt
+            "1e" EmptyExponent
+            "0b1.0" NonDecimalFloat(Binary)
+            "'1a" LifetimeStartsWithNumber
+            "foo" UnknownPrefix
+            "🦀" InvalidIdent
+            "¤" UnknownChar('¤')
+            "r#~" RawStr(InvalidStarter { bad_char: '~' })
+            "b'a" UnterminatedByte
+        "#]],
+    );
+    check(
+        "\"abc",
+        expect![[r#"
+        "\"abc" UnterminatedStr
+    "#]],
+    );
+    check(
+        "/* a",
+        expect![[r#"
+        "/* a" UnterminatedBlockComment
+    "#]],
+    );
+}
+
+#[test]
+fn test_lex_diagnostics() {
+    let src = "\"a\\qb\" \"\\\n\n x\" 0x\n\u{FEFF}'a";
+    let actual: String = lex_diagnostics(src)
+        .iter()
+        .map(|diag| {
+            format!(
+                "{:?} {:?} {} {:?}\n",
+                &src[diag.range.clone()],
+                diag.severity,
+                diag.message_key,
+                diag.suggestion.as_ref().map(|s| (s.range.clone(), &s.replacement))
+            )
+        })
+        .collect();
+    expect![[r#"
+        "\\q" Error escape-invalid-escape Some((2..4, "\\\\q"))
+        "\\\n\n " Warning escape-multiple-skipped-lines None
+        "0x" Error lex-empty-int Some((17..17, "0"))
+        "\u{feff}" Error stray-bom-probable-concatenation Some((18..21, ""))
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn test_lex_diagnostics_raw_byte_str() {
+    let src = "br#\"\\é\"ü\"#_x";
+    let diags = lex_diagnostics(src);
+    assert_eq!(diags.len(), 2);
+    assert_eq!(diags[0].range, 5..7);
+    assert_eq!(diags[1].range, 8..10);
+    let suggestion = diags[0].suggestion.as_ref().unwrap();
+    assert_eq!(suggestion.range, 0..12);
+    assert_eq!(suggestion.replacement, r#"b"\\\xC3\xA9\"\xC3\xBC""#);
+    assert_eq!(diags[1].suggestion, None);
+}
+
+#[test]
+fn test_lex_diagnostics_missing_digits() {
+    let src = "0x_u8 1.5e+_f32 2E";
+    let actual: String = lex_diagnostics(src)
+        .iter()
+        .map(|diag| {
+            format!(
+                "{:?} {} {:?}\n",
+                &src[diag.range.clone()],
+                diag.message_key,
+                diag.suggestion.as_ref().map(|s| (s.range.clone(), &s.replacement))
+            )
+        })
+        .collect();
+    expect![[r#"
+        "0x_" lex-empty-int Some((3..3, "0"))
+        "e+_" lex-empty-exponent Some((12..12, "0"))
+        "E" lex-empty-exponent Some((18..18, "0"))
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn test_lex_diagnostics_raw_str_terminator() {
+    for (src, fixed) in [("x r##\"a\"#", "x r##\"a\"##"), ("x r#\"a", "x r#\"a\"#")] {
+        let diags = lex_diagnostics(src);
+        assert_eq!(diags.len(), 1, "{}", src);
+        let suggestion = diags[0].suggestion.as_ref().unwrap();
+        let mut actual = String::from(src);
+        actual.replace_range(suggestion.range.clone(), &suggestion.replacement);
+        assert_eq!(actual, fixed);
+    }
+}
+
+#[test]
+fn test_lex_diagnostics_guarded_str() {
+    let src = "x!(##\"a\"## #\"b\")";
+    assert!(lex_diagnostics(src).is_empty());
+    let actual: String = lex_diagnostics_with_edition(src, Edition::Edition2024)
+        .iter()
+        .map(|diag| {
+            format!(
+                "{:?} {} {:?}\n",
+                &src[diag.range.clone()],
+                diag.message_key,
+                diag.suggestion.as_ref().map(|s| (s.range.clone(), &s.replacement))
+            )
+        })
+        .collect();
+    expect![[r###"
+        "##\"a\"##" lex-reserved-guarded-str Some((5..5, " "))
+        "#\"b\"" lex-reserved-guarded-str Some((12..12, " "))
+    "###]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn test_rustc_error_code() {
+    let codes: Vec<_> = ["/*", "'\\n", "\"a", "r#\"a", "r#~\"a\"#", "0x", "1e", "\\q"]
+        .iter()
+        .map(|src| lex_diagnostics(src)[0].code.rustc_error_code())
+        .collect();
+    assert_eq!(
+        codes,
+        [
+            Some("E0758"),
+            Some("E0762"),
+            Some("E0765"),
+            Some("E0748"),
+            None,
+            Some("E0768"),
+            None,
+            None
+        ]
+    );
+}
+
+#[test]
+fn test_explain() {
+    let diags = lex_diagnostics("0x \"\\q\"");
+    expect![[r#"
+        A number with a base prefix like `0x`, `0o` or `0b` must contain at least one digit after the prefix, e.g. `0x0`.
+        Only the escapes `\n`, `\r`, `\t`, `\\`, `\0`, `\'`, `\"`, `\x..` and `\u{..}` are supported. To write a literal backslash, escape it as `\\`, or use a raw string, in which backslashes have no special meaning.
+    "#]].assert_eq(&format!("{}\n{}\n", diags[0].code.explain(), diags[1].code.explain()));
+}
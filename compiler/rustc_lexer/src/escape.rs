@@ -0,0 +1,132 @@
+//! Escaping of values into literal text, the inverse of [`unescape`].
+//!
+//! The functions return the complete literal including its quotes, which
+//! unescapes to the given value. Only what has to be escaped is, plus control
+//! chars and the bidirectional formatting chars which rustc rejects in
+//! literals, so the literal stays readable and on one line.
+//!
+//! [`unescape`]: crate::unescape
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::bidi::BidiControl;
+
+#[cfg(test)]
+mod tests;
+
+/// Returns a string literal with the value `s`.
+pub fn escape_str(s: &str) -> String {
+    let mut res = String::with_capacity(s.len() + 2);
+    res.push('"');
+    for c in s.chars() {
+        push_escaped_char(&mut res, c, '"');
+    }
+    res.push('"');
+    res
+}
+
+/// Like [`escape_str`], but returns a raw string literal like `r#"a"b"#` if
+/// `s` contains quotes or backslashes, and no other chars which have to be
+/// escaped.
+pub fn escape_str_prefer_raw(s: &str) -> String {
+    let needs_raw = s.contains(['"', '\\']);
+    if needs_raw && s.chars().all(|c| matches!(c, '"' | '\\') || !needs_escape(c)) {
+        raw_literal("r", s)
+    } else {
+        escape_str(s)
+    }
+}
+
+/// Returns a char literal with the value `c`.
+pub fn escape_char(c: char) -> String {
+    let mut res = String::with_capacity(4);
+    res.push('\'');
+    push_escaped_char(&mut res, c, '\'');
+    res.push('\'');
+    res
+}
+
+/// Returns a byte string literal with the value `bytes`.
+pub fn escape_byte_str(bytes: &[u8]) -> String {
+    let mut res = String::with_capacity(bytes.len() + 3);
+    res.push_str("b\"");
+    for &b in bytes {
+        push_escaped_byte(&mut res, b, b'"');
+    }
+    res.push('"');
+    res
+}
+
+/// Like [`escape_byte_str`], but returns a raw byte string literal like
+/// `br#"a"b"#` if `bytes` contain quotes or backslashes, and no other bytes
+/// which have to be escaped.
+pub fn escape_byte_str_prefer_raw(bytes: &[u8]) -> String {
+    let needs_raw = bytes.iter().any(|&b| matches!(b, b'"' | b'\\'));
+    let printable = bytes.iter().all(|&b| matches!(b, b' '..=b'~'));
+    match core::str::from_utf8(bytes) {
+        Ok(s) if needs_raw && printable => raw_literal("br", s),
+        _ => escape_byte_str(bytes),
+    }
+}
+
+/// Returns a byte literal with the value `b`.
+pub fn escape_byte(b: u8) -> String {
+    let mut res = String::with_capacity(5);
+    res.push_str("b'");
+    push_escaped_byte(&mut res, b, b'\'');
+    res.push('\'');
+    res
+}
+
+/// Whether `c` is escaped regardless of the quotes of the literal.
+fn needs_escape(c: char) -> bool {
+    c.is_control() || BidiControl::from_char(c).is_some()
+}
+
+/// Appends `c` to `out`, escaped if necessary for a literal in `quote`s.
+fn push_escaped_char(out: &mut String, c: char, quote: char) {
+    match c {
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        '\0' => out.push_str("\\0"),
+        c if c == quote => {
+            out.push('\\');
+            out.push(c);
+        }
+        c if needs_escape(c) => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        c => out.push(c),
+    }
+}
+
+/// Appends `b` to `out`, escaped if necessary for a literal in `quote`s.
+fn push_escaped_byte(out: &mut String, b: u8, quote: u8) {
+    match b {
+        b'\\' => out.push_str("\\\\"),
+        b'\n' => out.push_str("\\n"),
+        b'\r' => out.push_str("\\r"),
+        b'\t' => out.push_str("\\t"),
+        b'\0' => out.push_str("\\0"),
+        b if b == quote => {
+            out.push('\\');
+            out.push(char::from(b));
+        }
+        b' '..=b'~' => out.push(char::from(b)),
+        b => out.push_str(&format!("\\x{:02x}", b)),
+    }
+}
+
+/// Returns the raw literal with the given prefix and contents, delimited by
+/// the fewest `#`s which don't occur after a quote in `s`.
+fn raw_literal(prefix: &str, s: &str) -> String {
+    let n_hashes = s
+        .split('"')
+        .skip(1)
+        .map(|after_quote| after_quote.bytes().take_while(|&b| b == b'#').count() + 1)
+        .max()
+        .unwrap_or(0);
+    let hashes = "#".repeat(n_hashes);
+    format!("{}{}\"{}\"{}", prefix, hashes, s, hashes)
+}
@@ -0,0 +1,79 @@
+use super::*;
+use crate::cook::{cook_literal, Value};
+use crate::{first_token, TokenKind};
+
+/// Lexes and cooks `literal`, which has to be a single literal token.
+fn cook(literal: &str) -> Value<'_> {
+    let token = first_token(literal);
+    assert_eq!(token.len, literal.len(), "{}", literal);
+    let (kind, suffix_start) = match token.kind {
+        TokenKind::Literal { kind, suffix_start } => (kind, suffix_start),
+        kind => panic!("not a literal: {:?}", kind),
+    };
+    let (value, errors) = cook_literal(kind, literal, suffix_start);
+    assert!(errors.is_empty(), "{}: {:?}", literal, errors);
+    value.unwrap()
+}
+
+#[test]
+fn test_escape_str() {
+    assert_eq!(escape_str("abc"), r#""abc""#);
+    assert_eq!(escape_str("a\"b'c\\"), r#""a\"b'c\\""#);
+    assert_eq!(escape_str("a\nb\r\t\0"), r#""a\nb\r\t\0""#);
+    assert_eq!(escape_str("é\u{7f}\u{202e}"), r#""é\u{7f}\u{202e}""#);
+    for s in ["", "abc", "a\"b'c\\", "\n\r\t\0", "é🦀\u{1}\u{7f}\u{85}\u{202e}\u{2066}"] {
+        assert_eq!(cook(&escape_str(s)), Value::Str(s.into()), "{:?}", s);
+    }
+}
+
+#[test]
+fn test_escape_str_prefer_raw() {
+    assert_eq!(escape_str_prefer_raw("abc"), r#""abc""#);
+    assert_eq!(escape_str_prefer_raw(r"C:\dir"), r#"r"C:\dir""#);
+    assert_eq!(escape_str_prefer_raw(r#"say "hi""#), r##"r#"say "hi""#"##);
+    assert_eq!(escape_str_prefer_raw(r##"a"#b"c"##), r###"r##"a"#b"c"##"###);
+    // Raw strings can't contain a carriage return.
+    assert_eq!(escape_str_prefer_raw("\"\r"), r#""\"\r""#);
+    for s in [r"C:\dir", r#"say "hi""#, r##"a"#b"c"##, r#"""#, "\"#"] {
+        assert_eq!(cook(&escape_str_prefer_raw(s)), Value::Str(s.into()), "{:?}", s);
+    }
+}
+
+#[test]
+fn test_escape_char() {
+    assert_eq!(escape_char('a'), "'a'");
+    assert_eq!(escape_char('"'), "'\"'");
+    assert_eq!(escape_char('\''), r"'\''");
+    assert_eq!(escape_char('\u{2067}'), r"'\u{2067}'");
+    for c in ['a', '"', '\'', '\\', '\n', '\0', 'é', '🦀', '\u{7f}', '\u{2067}'] {
+        assert_eq!(cook(&escape_char(c)), Value::Char(c), "{:?}", c);
+    }
+}
+
+#[test]
+fn test_escape_byte_str() {
+    assert_eq!(escape_byte_str(b"abc"), r#"b"abc""#);
+    assert_eq!(escape_byte_str(b"a\"\\\n\x7f\xff"), r#"b"a\"\\\n\x7f\xff""#);
+    assert_eq!(escape_byte_str_prefer_raw(br#"a"\"#), r##"br#"a"\"#"##);
+    assert_eq!(escape_byte_str_prefer_raw(b"\"\xff"), r#"b"\"\xff""#);
+    let cases: [&[u8]; 4] = [b"", b"a\"\\\n\x7f\xff", br#"a"\"#, b"\x00\x01\x80"];
+    for bytes in cases {
+        assert_eq!(cook(&escape_byte_str(bytes)), Value::ByteStr(bytes.to_vec()), "{:?}", bytes);
+        assert_eq!(
+            cook(&escape_byte_str_prefer_raw(bytes)),
+            Value::ByteStr(bytes.to_vec()),
+            "{:?}",
+            bytes
+        );
+    }
+}
+
+#[test]
+fn test_escape_byte() {
+    assert_eq!(escape_byte(b'a'), "b'a'");
+    assert_eq!(escape_byte(b'\''), r"b'\''");
+    assert_eq!(escape_byte(0xff), r"b'\xff'");
+    for b in 0..=u8::MAX {
+        assert_eq!(cook(&escape_byte(b)), Value::Byte(b), "{:?}", b);
+    }
+}
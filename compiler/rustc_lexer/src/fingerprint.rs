@@ -0,0 +1,267 @@
+//! Stable hashing of tokens.
+//!
+//! Tokens are hashed in an explicit encoding instead of through their derived
+//! `Hash` implementations, which change whenever a variant or field is added.
+//! Each token kind is written as a fixed tag byte followed by its fields, with
+//! integers as little-endian `u64`s, `bool`s as a byte and `Option`s as a
+//! byte for `Some` or `None` followed by the value. Tags of removed kinds are
+//! not reused, and changing the encoding of existing kinds requires bumping
+//! [`ENCODING_VERSION`].
+
+use crate::{
+    Base, DocStyle, LineEndingKinds, LiteralKind, MissingDigits, PrefixFollower, RawStrError,
+    TokenKind, WhitespaceCounts,
+};
+
+/// A hash of a token's kind and text, see [`tokenize_with_fingerprints`].
+///
+/// Unlike [`DefaultHasher`](std::collections::hash_map::DefaultHasher), the
+/// hash doesn't depend on the platform, the process or the version of this
+/// crate, so fingerprints can be persisted and compared between runs. Equal
+/// tokens have equal fingerprints, different tokens very likely have
+/// different ones.
+///
+/// [`tokenize_with_fingerprints`]: crate::tokenize_with_fingerprints
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fingerprint(pub u64);
+
+impl Fingerprint {
+    /// Computes the fingerprint of a token with the given kind and text.
+    pub fn of(kind: TokenKind, text: &str) -> Fingerprint {
+        let mut hasher = StableHasher(FNV_OFFSET_BASIS);
+        hasher.byte(ENCODING_VERSION);
+        hasher.token_kind(kind);
+        hasher.int(text.len() as u64);
+        hasher.write(text.as_bytes());
+        Fingerprint(hasher.0)
+    }
+}
+
+/// The version of the encoding of tokens, see the [module docs](self).
+const ENCODING_VERSION: u8 = 1;
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// FNV-1a over the encoding of tokens.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = (self.0 ^ u64::from(byte)).wrapping_mul(FNV_PRIME);
+        }
+    }
+
+    fn byte(&mut self, byte: u8) {
+        self.write(&[byte]);
+    }
+
+    fn bool(&mut self, b: bool) {
+        self.byte(b as u8);
+    }
+
+    fn int(&mut self, n: impl Into<u64>) {
+        self.write(&n.into().to_le_bytes());
+    }
+
+    fn option<T>(&mut self, value: Option<T>, f: impl FnOnce(&mut Self, T)) {
+        match value {
+            None => self.byte(0),
+            Some(value) => {
+                self.byte(1);
+                f(self, value);
+            }
+        }
+    }
+
+    fn token_kind(&mut self, kind: TokenKind) {
+        use TokenKind::*;
+        match kind {
+            LineComment { doc_style } => {
+                self.byte(0);
+                self.option(doc_style, Self::doc_style);
+            }
+            BlockComment { doc_style, terminated } => {
+                self.byte(1);
+                self.option(doc_style, Self::doc_style);
+                self.bool(terminated);
+            }
+            Whitespace { line_endings, counts } => {
+                self.byte(2);
+                self.option(line_endings, Self::line_endings);
+                self.option(counts, Self::whitespace_counts);
+            }
+            Ident => self.byte(3),
+            InvalidIdent => self.byte(4),
+            RawIdent => self.byte(5),
+            UnknownPrefix { followed_by } => {
+                self.byte(6);
+                self.byte(match followed_by {
+                    PrefixFollower::Str => 0,
+                    PrefixFollower::Char => 1,
+                    PrefixFollower::Pound => 2,
+                });
+            }
+            Literal { kind, suffix_start } => {
+                self.byte(7);
+                self.literal_kind(kind);
+                self.int(suffix_start as u64);
+            }
+            PrefixedLiteral { prefix, kind, suffix_start } => {
+                self.byte(8);
+                self.int(prefix as u64);
+                self.literal_kind(kind);
+                self.int(suffix_start as u64);
+            }
+            GuardedStr { n_hashes, terminated } => {
+                self.byte(9);
+                self.int(n_hashes);
+                self.bool(terminated);
+            }
+            Lifetime { starts_with_number } => {
+                self.byte(10);
+                self.bool(starts_with_number);
+            }
+            RawLifetime => self.byte(11),
+            Semi => self.byte(12),
+            Comma => self.byte(13),
+            Dot => self.byte(14),
+            OpenParen => self.byte(15),
+            CloseParen => self.byte(16),
+            OpenBrace => self.byte(17),
+            CloseBrace => self.byte(18),
+            OpenBracket => self.byte(19),
+            CloseBracket => self.byte(20),
+            At => self.byte(21),
+            Pound => self.byte(22),
+            Tilde => self.byte(23),
+            Question => self.byte(24),
+            Colon => self.byte(25),
+            Dollar => self.byte(26),
+            Eq => self.byte(27),
+            Bang => self.byte(28),
+            Lt => self.byte(29),
+            Gt => self.byte(30),
+            Minus => self.byte(31),
+            And => self.byte(32),
+            Or => self.byte(33),
+            Plus => self.byte(34),
+            Star => self.byte(35),
+            Slash => self.byte(36),
+            Caret => self.byte(37),
+            Percent => self.byte(38),
+            Unknown => self.byte(39),
+        }
+    }
+
+    fn literal_kind(&mut self, kind: LiteralKind) {
+        use LiteralKind::*;
+        match kind {
+            Int { base, empty_int, underscore_after_prefix } => {
+                self.byte(0);
+                self.base(base);
+                self.option(empty_int, Self::missing_digits);
+                self.bool(underscore_after_prefix);
+            }
+            Float { base, empty_exponent, hex_exponent } => {
+                self.byte(1);
+                self.base(base);
+                self.option(empty_exponent, Self::missing_digits);
+                self.option(hex_exponent, Self::int);
+            }
+            Char { terminated } => {
+                self.byte(2);
+                self.bool(terminated);
+            }
+            Byte { terminated } => {
+                self.byte(3);
+                self.bool(terminated);
+            }
+            Str { terminated, first_newline } => self.string(4, terminated, first_newline),
+            ByteStr { terminated, first_newline } => self.string(5, terminated, first_newline),
+            RawStr { n_hashes, err, first_newline } => {
+                self.raw_string(6, n_hashes, err, first_newline)
+            }
+            RawByteStr { n_hashes, err, first_newline } => {
+                self.raw_string(7, n_hashes, err, first_newline)
+            }
+            CStr { terminated, first_newline } => self.string(8, terminated, first_newline),
+            RawCStr { n_hashes, err, first_newline } => {
+                self.raw_string(9, n_hashes, err, first_newline)
+            }
+        }
+    }
+
+    fn string(&mut self, tag: u8, terminated: bool, first_newline: Option<u32>) {
+        self.byte(tag);
+        self.bool(terminated);
+        self.option(first_newline, Self::int);
+    }
+
+    fn raw_string(
+        &mut self,
+        tag: u8,
+        n_hashes: u32,
+        err: Option<RawStrError>,
+        first_newline: Option<u32>,
+    ) {
+        self.byte(tag);
+        self.int(n_hashes);
+        self.option(err, Self::raw_str_error);
+        self.option(first_newline, Self::int);
+    }
+
+    fn raw_str_error(&mut self, err: RawStrError) {
+        match err {
+            RawStrError::InvalidStarter { bad_char } => {
+                self.byte(0);
+                self.int(bad_char);
+            }
+            RawStrError::NoTerminator { expected, found, possible_terminator_offset } => {
+                self.byte(1);
+                self.int(expected);
+                self.int(found);
+                self.option(possible_terminator_offset, Self::int);
+            }
+            RawStrError::TooManyDelimiters { found, max } => {
+                self.byte(2);
+                self.int(found);
+                self.int(max);
+            }
+        }
+    }
+
+    fn doc_style(&mut self, doc_style: DocStyle) {
+        self.byte(match doc_style {
+            DocStyle::Outer => 0,
+            DocStyle::Inner => 1,
+        });
+    }
+
+    fn base(&mut self, base: Base) {
+        self.byte(match base {
+            Base::Binary => 0,
+            Base::Octal => 1,
+            Base::Hexadecimal => 2,
+            Base::Decimal => 3,
+        });
+    }
+
+    fn missing_digits(&mut self, missing: MissingDigits) {
+        self.int(missing.start);
+        self.int(missing.expected_at);
+    }
+
+    fn line_endings(&mut self, kinds: LineEndingKinds) {
+        self.bool(kinds.lf);
+        self.bool(kinds.crlf);
+        self.bool(kinds.cr);
+    }
+
+    fn whitespace_counts(&mut self, counts: WhitespaceCounts) {
+        self.int(counts.spaces);
+        self.int(counts.tabs);
+        self.int(counts.newlines);
+    }
+}
@@ -0,0 +1,132 @@
+//! Experimental lexing of format strings like `f"a {b} c"`.
+//!
+//! Format strings are not part of Rust. This mode exists to prototype string
+//! interpolation without forking the lexer: [`tokenize_format_strings`] lexes
+//! like [`tokenize`](crate::tokenize), except that `f"` starts a
+//! [`FormatStr`], which records where its interpolations are.
+//!
+//! Inside a format string, `{{` and `}}` are escaped braces and `{` starts an
+//! interpolation, which ends at the matching `}`. Braces nest, and an
+//! interpolation may contain string literals, whose quotes and braces don't
+//! count. Escapes outside of interpolations are skipped like in a regular
+//! string.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{first_token, Cursor, Token};
+
+#[cfg(test)]
+mod tests;
+
+/// A format string token, see [`lex_format_str`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FormatStr {
+    /// Length of the token, including the `f` and the suffix.
+    pub len: usize,
+    /// Whether the closing quote was found. An unterminated format string
+    /// extends to the end of the input.
+    pub terminated: bool,
+    /// Byte ranges of the interpolations in the token, without their braces.
+    /// An interpolation which is not closed is not recorded.
+    pub interpolations: Vec<Range<usize>>,
+    /// Offset of the suffix in the token, like for
+    /// [`Literal`](crate::TokenKind::Literal).
+    pub suffix_start: usize,
+}
+
+/// A token lexed by [`tokenize_format_strings`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FormatToken {
+    /// A token which is not a format string.
+    Token(Token),
+    FormatStr(FormatStr),
+}
+
+/// Lexes the format string at the start of `input`, or returns `None` if
+/// `input` doesn't start with `f"`.
+pub fn lex_format_str(input: &str) -> Option<FormatStr> {
+    if !input.starts_with("f\"") {
+        return None;
+    }
+    let mut cursor = Cursor::new(input);
+    cursor.bump();
+    cursor.bump();
+    let mut interpolations = Vec::new();
+    let terminated = loop {
+        match cursor.bump() {
+            None => break false,
+            Some('"') => break true,
+            Some('\\') if matches!(cursor.first(), '\\' | '"') => {
+                cursor.bump();
+            }
+            Some('{') if cursor.first() == '{' => {
+                cursor.bump();
+            }
+            Some('}') if cursor.first() == '}' => {
+                cursor.bump();
+            }
+            Some('{') => {
+                let start = cursor.len_consumed();
+                if !eat_interpolation(&mut cursor) {
+                    break false;
+                }
+                interpolations.push(start..cursor.len_consumed() - 1);
+            }
+            Some(_) => {}
+        }
+    };
+    let suffix_start = cursor.len_consumed();
+    if terminated {
+        cursor.eat_literal_suffix();
+    }
+    Some(FormatStr { len: cursor.len_consumed(), terminated, interpolations, suffix_start })
+}
+
+/// Eats an interpolation after its opening `{`, including the closing `}`.
+/// Returns false if the input ends first.
+fn eat_interpolation(cursor: &mut Cursor<'_>) -> bool {
+    let mut depth = 1;
+    loop {
+        match cursor.bump() {
+            None => return false,
+            Some('{') => depth += 1,
+            Some('}') => {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+            Some('"') => {
+                if !cursor.double_quoted_string() {
+                    return false;
+                }
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+/// Like [`tokenize`](crate::tokenize), but lexes `f"` as the start of a
+/// [`FormatStr`].
+pub fn tokenize_format_strings(input: &str) -> impl Iterator<Item = FormatToken> + '_ {
+    let mut rest = input;
+    core::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let (token, len) = match lex_format_str(rest) {
+            Some(format_str) => {
+                let len = format_str.len;
+                (FormatToken::FormatStr(format_str), len)
+            }
+            None => {
+                let token = first_token(rest);
+                let len = token.len;
+                (FormatToken::Token(token), len)
+            }
+        };
+        rest = &rest[len..];
+        Some(token)
+    })
+}
@@ -0,0 +1,85 @@
+use super::*;
+use expect_test::{expect, Expect};
+
+fn check(src: &str, expect: Expect) {
+    let mut pos = 0;
+    let actual: String = tokenize_format_strings(src)
+        .map(|token| match token {
+            FormatToken::Token(token) => {
+                let text = &src[pos..pos + token.len];
+                pos += token.len;
+                format!("{:?} {:?}\n", token.kind, text)
+            }
+            FormatToken::FormatStr(format_str) => {
+                let text = &src[pos..pos + format_str.len];
+                pos += format_str.len;
+                let interpolations: Vec<_> =
+                    format_str.interpolations.iter().map(|range| &text[range.clone()]).collect();
+                format!(
+                    "FormatStr {:?} terminated={} suffix={:?} {:?}\n",
+                    text,
+                    format_str.terminated,
+                    &text[format_str.suffix_start..],
+                    interpolations
+                )
+            }
+        })
+        .collect();
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn test_interpolations() {
+    check(
+        r#"f"a {b} {{c}} {d.e(1)}\"{f}""#,
+        expect![[r#"
+            FormatStr "f\"a {b} {{c}} {d.e(1)}\\\"{f}\"" terminated=true suffix="" ["b", "d.e(1)", "f"]
+        "#]],
+    );
+}
+
+#[test]
+fn test_nested() {
+    check(
+        r#"f"{ {x} } {"}"} {"{{" }"_s"#,
+        expect![[r#"
+            FormatStr "f\"{ {x} } {\"}\"} {\"{{\" }\"_s" terminated=true suffix="_s" [" {x} ", "\"}\"", "\"{{\" "]
+        "#]],
+    );
+}
+
+#[test]
+fn test_other_tokens() {
+    check(
+        r#"x(f, "{y}", elf"z")"#,
+        expect![[r#"
+            Ident "x"
+            OpenParen "("
+            Ident "f"
+            Comma ","
+            Whitespace { line_endings: None, counts: None } " "
+            Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 5 } "\"{y}\""
+            Comma ","
+            Whitespace { line_endings: None, counts: None } " "
+            UnknownPrefix { followed_by: Str } "elf"
+            Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } "\"z\""
+            CloseParen ")"
+        "#]],
+    );
+}
+
+#[test]
+fn test_unterminated() {
+    check(
+        r#"f"a {b} {c"#,
+        expect![[r#"
+            FormatStr "f\"a {b} {c" terminated=false suffix="" ["b"]
+        "#]],
+    );
+    check(
+        r#"f"{"}"#,
+        expect![[r#"
+            FormatStr "f\"{\"}" terminated=false suffix="" []
+        "#]],
+    );
+}
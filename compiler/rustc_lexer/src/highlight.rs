@@ -0,0 +1,93 @@
+//! Classification of tokens for syntax highlighting.
+//!
+//! [`highlight`] assigns a coarse [`HighlightClass`] to every token of a
+//! source, which is all an editor or a documentation generator needs for
+//! basic highlighting. The source is lexed and identifiers are told apart
+//! from keywords according to the edition, e.g. `async` is a keyword since
+//! Rust 2018.
+
+use core::ops::Range;
+
+use crate::keywords::is_keyword;
+use crate::{strip_shebang, tokenize_with_edition, Edition, LiteralKind, TokenKind};
+
+pub mod ansi;
+#[cfg(feature = "html")]
+pub mod html;
+pub mod lsp;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HighlightClass {
+    /// A strict or reserved keyword of the edition, including `true` and
+    /// `false`.
+    Keyword,
+    /// An identifier which is not a keyword, including raw identifiers.
+    Ident,
+    Lifetime,
+    /// A comment which is not a doc comment, or a shebang.
+    Comment,
+    DocComment,
+    /// A string, character or byte literal, including its suffix.
+    String,
+    /// An integer or float literal, including its suffix.
+    Number,
+    /// Operators and delimiters.
+    Punctuation,
+    Whitespace,
+    /// A token which is an error, like an unknown char or an invalid
+    /// identifier.
+    Invalid,
+}
+
+/// A classified token, see [`highlight`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Highlight {
+    pub class: HighlightClass,
+    /// Byte range of the token in the source.
+    pub range: Range<usize>,
+}
+
+/// Returns the class of a token of the given kind and text.
+pub fn classify(kind: TokenKind, text: &str, edition: Edition) -> HighlightClass {
+    match kind {
+        TokenKind::LineComment { doc_style } | TokenKind::BlockComment { doc_style, .. } => {
+            if doc_style.is_some() {
+                HighlightClass::DocComment
+            } else {
+                HighlightClass::Comment
+            }
+        }
+        TokenKind::Whitespace { .. } => HighlightClass::Whitespace,
+        TokenKind::Ident if is_keyword(text, edition) => HighlightClass::Keyword,
+        TokenKind::Ident | TokenKind::RawIdent => HighlightClass::Ident,
+        TokenKind::Lifetime { starts_with_number: false } | TokenKind::RawLifetime => {
+            HighlightClass::Lifetime
+        }
+        TokenKind::Literal { kind, .. } | TokenKind::PrefixedLiteral { kind, .. } => match kind {
+            LiteralKind::Int { .. } | LiteralKind::Float { .. } => HighlightClass::Number,
+            _ => HighlightClass::String,
+        },
+        TokenKind::InvalidIdent
+        | TokenKind::UnknownPrefix { .. }
+        | TokenKind::GuardedStr { .. }
+        | TokenKind::Lifetime { starts_with_number: true }
+        | TokenKind::Unknown => HighlightClass::Invalid,
+        _ => HighlightClass::Punctuation,
+    }
+}
+
+/// Classifies every token of `src`. The ranges of the highlights cover the
+/// whole source, a shebang is highlighted as a [`HighlightClass::Comment`].
+pub fn highlight(src: &str, edition: Edition) -> impl Iterator<Item = Highlight> + '_ {
+    let shebang_len = strip_shebang(src).map_or(0, |shebang| shebang.len);
+    let shebang = Some(Highlight { class: HighlightClass::Comment, range: 0..shebang_len })
+        .filter(|_| shebang_len > 0);
+    let tokens = tokenize_with_edition(&src[shebang_len..], edition).with_ranges();
+    shebang.into_iter().chain(tokens.map(move |(range, token)| {
+        let range = shebang_len + range.start..shebang_len + range.end;
+        Highlight { class: classify(token.kind, &src[range.clone()], edition), range }
+    }))
+}
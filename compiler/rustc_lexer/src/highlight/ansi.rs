@@ -0,0 +1,127 @@
+//! Rendering of highlighted source for terminals.
+//!
+//! [`write_ansi`] colors tokens with ANSI escape sequences taken from a
+//! [`Palette`]. Styles are reset at the end of every line, so the output can
+//! be cut into lines, e.g. to show a snippet in a diagnostic, and is still
+//! shown correctly by pagers. Control chars in the source other than tabs
+//! and line breaks are written escaped, like `\x1b`, so the source can't
+//! send escape sequences of its own to the terminal.
+
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use super::{highlight, HighlightClass};
+use crate::Edition;
+
+#[cfg(test)]
+mod tests;
+
+/// The styles of the highlight classes, given as the parameters of an SGR
+/// escape sequence like `"1;35"` for bold magenta. Tokens with an empty style
+/// are written as is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Palette {
+    pub keyword: &'static str,
+    pub ident: &'static str,
+    pub lifetime: &'static str,
+    pub comment: &'static str,
+    pub doc_comment: &'static str,
+    pub string: &'static str,
+    pub number: &'static str,
+    pub punctuation: &'static str,
+    pub invalid: &'static str,
+}
+
+impl Palette {
+    /// A palette using the basic colors, which all terminals support.
+    pub const DEFAULT: Palette = Palette {
+        keyword: "35",
+        ident: "",
+        lifetime: "36",
+        comment: "90",
+        doc_comment: "34",
+        string: "32",
+        number: "33",
+        punctuation: "",
+        invalid: "1;31",
+    };
+
+    /// Returns the style of tokens of the given class.
+    pub fn style(&self, class: HighlightClass) -> &'static str {
+        match class {
+            HighlightClass::Keyword => self.keyword,
+            HighlightClass::Ident => self.ident,
+            HighlightClass::Lifetime => self.lifetime,
+            HighlightClass::Comment => self.comment,
+            HighlightClass::DocComment => self.doc_comment,
+            HighlightClass::String => self.string,
+            HighlightClass::Number => self.number,
+            HighlightClass::Punctuation => self.punctuation,
+            HighlightClass::Invalid => self.invalid,
+            HighlightClass::Whitespace => "",
+        }
+    }
+}
+
+impl Default for Palette {
+    fn default() -> Palette {
+        Palette::DEFAULT
+    }
+}
+
+/// Writes `src` colored according to `palette` to `out`, see the
+/// [module documentation](self).
+pub fn write_ansi(
+    src: &str,
+    edition: Edition,
+    palette: &Palette,
+    out: &mut impl Write,
+) -> fmt::Result {
+    for highlight in highlight(src, edition) {
+        let text = &src[highlight.range];
+        let style = palette.style(highlight.class);
+        if style.is_empty() {
+            write_escaped(text, out)?;
+            continue;
+        }
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                out.write_char('\n')?;
+            }
+            let content = line.strip_suffix('\r').unwrap_or(line);
+            if !content.is_empty() {
+                write!(out, "\x1b[{}m", style)?;
+                write_escaped(content, out)?;
+                out.write_str("\x1b[0m")?;
+            }
+            out.write_str(&line[content.len()..])?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes `text` with control chars other than `\t`, `\n` and `\r` escaped.
+fn write_escaped(text: &str, out: &mut impl Write) -> fmt::Result {
+    let is_escaped = |c: char| c.is_control() && !matches!(c, '\t' | '\n' | '\r');
+    if !text.contains(is_escaped) {
+        return out.write_str(text);
+    }
+    for c in text.chars() {
+        if !is_escaped(c) {
+            out.write_char(c)?;
+        } else if c.is_ascii() {
+            write!(out, "\\x{:02x}", c as u32)?;
+        } else {
+            write!(out, "\\u{{{:x}}}", c as u32)?;
+        }
+    }
+    Ok(())
+}
+
+/// Returns `src` colored according to `palette`, see [`write_ansi`].
+pub fn to_ansi(src: &str, edition: Edition, palette: &Palette) -> String {
+    let mut out = String::with_capacity(src.len() * 2);
+    // Writing to a `String` can't fail.
+    write_ansi(src, edition, palette, &mut out).unwrap();
+    out
+}
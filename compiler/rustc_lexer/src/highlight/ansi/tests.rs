@@ -0,0 +1,28 @@
+use super::*;
+use expect_test::expect;
+
+#[test]
+fn test_to_ansi() {
+    let src = "let s = \"a\r\nb\"; /* x\n */ 'l";
+    let ansi = to_ansi(src, Edition::Edition2021, &Palette::DEFAULT);
+    expect![[r#"
+        "\u{1b}[35mlet\u{1b}[0m s = \u{1b}[32m\"a\u{1b}[0m\r\n\u{1b}[32mb\"\u{1b}[0m; \u{1b}[90m/* x\u{1b}[0m\n\u{1b}[90m */\u{1b}[0m \u{1b}[36m'l\u{1b}[0m"
+    "#]].assert_debug_eq(&ansi);
+}
+
+#[test]
+fn test_control_chars() {
+    // A literal ESC in a string and a C1 control char in a comment.
+    let src = "\"\x1b[2J\"\t// \u{9b}";
+    let ansi = to_ansi(src, Edition::Edition2021, &Palette::DEFAULT);
+    assert_eq!(ansi, "\x1b[32m\"\\x1b[2J\"\x1b[0m\t\x1b[90m// \\u{9b}\x1b[0m");
+    let plain = Palette { string: "", comment: "", ..Palette::DEFAULT };
+    assert_eq!(to_ansi(src, Edition::Edition2021, &plain), "\"\\x1b[2J\"\t// \\u{9b}");
+}
+
+#[test]
+fn test_palette() {
+    let palette = Palette { keyword: "1", ..Palette::default() };
+    assert_eq!(palette.style(HighlightClass::Keyword), "1");
+    assert_eq!(to_ansi("fn x", Edition::Edition2021, &palette), "\x1b[1mfn\x1b[0m x");
+}
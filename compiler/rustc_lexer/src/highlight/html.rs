@@ -0,0 +1,74 @@
+//! Rendering of highlighted source as HTML.
+//!
+//! [`write_html`] wraps every token but whitespace in a
+//! `<span class="...">`, with the class given by [`css_class`]. The text is
+//! escaped and whitespace is kept as is, so the output is meant to be put
+//! into a `<pre>` element, or any element styled with `white-space: pre`.
+
+use alloc::string::String;
+use core::fmt::{self, Write};
+
+use super::{highlight, HighlightClass};
+use crate::Edition;
+
+#[cfg(test)]
+mod tests;
+
+/// Returns the CSS class of the spans of tokens of the given class, or `None`
+/// for whitespace, which isn't wrapped in a span.
+pub fn css_class(class: HighlightClass) -> Option<&'static str> {
+    let css_class = match class {
+        HighlightClass::Keyword => "kw",
+        HighlightClass::Ident => "ident",
+        HighlightClass::Lifetime => "lifetime",
+        HighlightClass::Comment => "comment",
+        HighlightClass::DocComment => "doccomment",
+        HighlightClass::String => "string",
+        HighlightClass::Number => "number",
+        HighlightClass::Punctuation => "punct",
+        HighlightClass::Invalid => "invalid",
+        HighlightClass::Whitespace => return None,
+    };
+    Some(css_class)
+}
+
+/// Writes `src` as highlighted HTML to `out`, see the
+/// [module documentation](self).
+pub fn write_html(src: &str, edition: Edition, out: &mut impl Write) -> fmt::Result {
+    for highlight in highlight(src, edition) {
+        let text = &src[highlight.range];
+        match css_class(highlight.class) {
+            Some(css_class) => {
+                write!(out, "<span class=\"{}\">", css_class)?;
+                write_escaped(text, out)?;
+                out.write_str("</span>")?;
+            }
+            None => write_escaped(text, out)?,
+        }
+    }
+    Ok(())
+}
+
+/// Returns `src` as highlighted HTML, see [`write_html`].
+pub fn to_html(src: &str, edition: Edition) -> String {
+    let mut out = String::with_capacity(src.len() * 2);
+    // Writing to a `String` can't fail.
+    write_html(src, edition, &mut out).unwrap();
+    out
+}
+
+fn write_escaped(text: &str, out: &mut impl Write) -> fmt::Result {
+    let mut rest = text;
+    while let Some(i) = rest.find(['&', '<', '>', '"', '\''].as_ref()) {
+        out.write_str(&rest[..i])?;
+        out.write_str(match rest.as_bytes()[i] {
+            b'&' => "&amp;",
+            b'<' => "&lt;",
+            b'>' => "&gt;",
+            b'"' => "&quot;",
+            _ => "&#39;",
+        })?;
+        rest = &rest[i + 1..];
+    }
+    out.write_str(rest)
+}
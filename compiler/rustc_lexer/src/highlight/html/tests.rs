@@ -0,0 +1,12 @@
+use super::*;
+use expect_test::expect;
+
+#[test]
+fn test_to_html() {
+    let src = "fn f<'a>() -> &'a str {\n    \"<a href='x'>&</a>\" // a < b\n}\n";
+    expect![[r#"
+        <span class="kw">fn</span> <span class="ident">f</span><span class="punct">&lt;</span><span class="lifetime">&#39;a</span><span class="punct">&gt;</span><span class="punct">(</span><span class="punct">)</span> <span class="punct">-</span><span class="punct">&gt;</span> <span class="punct">&amp;</span><span class="lifetime">&#39;a</span> <span class="ident">str</span> <span class="punct">{</span>
+            <span class="string">&quot;&lt;a href=&#39;x&#39;&gt;&amp;&lt;/a&gt;&quot;</span> <span class="comment">// a &lt; b</span>
+        <span class="punct">}</span>
+    "#]].assert_eq(&to_html(src, Edition::Edition2021));
+}
@@ -0,0 +1,106 @@
+//! Conversion of highlights into LSP semantic tokens.
+//!
+//! [`semantic_tokens`] produces the `data` of a `SemanticTokens` response:
+//! five integers per token, the line delta, the start column delta, the
+//! length, the index of the token type in [`TOKEN_TYPES`] and the bit set of
+//! modifiers from [`TOKEN_MODIFIERS`]. Tokens spanning several lines, like
+//! block comments, are split at line breaks, so clients don't need
+//! multiline token support. Whitespace and invalid tokens are left out.
+//!
+//! Lines are separated by `\n` or `\r\n`. Columns and lengths are measured
+//! in the units of the negotiated [`PositionEncoding`].
+
+use alloc::vec::Vec;
+
+use super::{highlight, HighlightClass};
+use crate::Edition;
+
+#[cfg(test)]
+mod tests;
+
+/// The token types legend, to be announced in the server capabilities.
+pub const TOKEN_TYPES: &[&str] =
+    &["keyword", "variable", "typeParameter", "comment", "string", "number", "operator"];
+
+/// The token modifiers legend, to be announced in the server capabilities.
+pub const TOKEN_MODIFIERS: &[&str] = &["documentation"];
+
+/// Modifier bit set of doc comments.
+const DOCUMENTATION: u32 = 1 << 0;
+
+/// The unit of columns and lengths.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionEncoding {
+    /// Bytes of UTF-8, `"utf-8"` in LSP.
+    Utf8,
+    /// Code units of UTF-16, the default of LSP.
+    Utf16,
+}
+
+impl PositionEncoding {
+    fn len(self, s: &str) -> u32 {
+        let len = match self {
+            PositionEncoding::Utf8 => s.len(),
+            PositionEncoding::Utf16 => s.chars().map(char::len_utf16).sum(),
+        };
+        len as u32
+    }
+}
+
+/// Returns the index into [`TOKEN_TYPES`] and the modifiers of a class, or
+/// `None` if tokens of the class are left out.
+fn token_type(class: HighlightClass) -> Option<(u32, u32)> {
+    let token_type = match class {
+        HighlightClass::Keyword => (0, 0),
+        HighlightClass::Ident => (1, 0),
+        HighlightClass::Lifetime => (2, 0),
+        HighlightClass::Comment => (3, 0),
+        HighlightClass::DocComment => (3, DOCUMENTATION),
+        HighlightClass::String => (4, 0),
+        HighlightClass::Number => (5, 0),
+        HighlightClass::Punctuation => (6, 0),
+        HighlightClass::Whitespace | HighlightClass::Invalid => return None,
+    };
+    Some(token_type)
+}
+
+/// Returns the delta-encoded semantic tokens of `src`, see the
+/// [module documentation](self).
+pub fn semantic_tokens(src: &str, edition: Edition, encoding: PositionEncoding) -> Vec<u32> {
+    let mut data = Vec::new();
+    let mut line = 0;
+    // The column of `col_pos`, which is on `line`.
+    let mut col = 0;
+    let mut col_pos = 0;
+    let (mut prev_line, mut prev_col) = (0, 0);
+    for highlight in highlight(src, edition) {
+        let token_type = token_type(highlight.class);
+        let mut start = highlight.range.start;
+        for (i, piece) in src[highlight.range].split('\n').enumerate() {
+            if i > 0 {
+                line += 1;
+                col = 0;
+            } else {
+                col += encoding.len(&src[col_pos..start]);
+            }
+            col_pos = start;
+            start += piece.len() + 1;
+            let piece = piece.strip_suffix('\r').unwrap_or(piece);
+            let (token_type, modifiers) = match token_type {
+                Some(token_type) if !piece.is_empty() => token_type,
+                _ => continue,
+            };
+            let delta_col = if line == prev_line { col - prev_col } else { col };
+            data.extend_from_slice(&[
+                line - prev_line,
+                delta_col,
+                encoding.len(piece),
+                token_type,
+                modifiers,
+            ]);
+            prev_line = line;
+            prev_col = col;
+        }
+    }
+    data
+}
@@ -0,0 +1,32 @@
+use super::*;
+
+#[test]
+fn test_semantic_tokens() {
+    let src = "fn f() {\r\n    /* a\n  é */ \"é\" + 1\n}";
+    #[rustfmt::skip]
+    let expected = [
+        0, 0, 2, 0, 0, // fn
+        0, 3, 1, 1, 0, // f
+        0, 1, 1, 6, 0, // (
+        0, 1, 1, 6, 0, // )
+        0, 2, 1, 6, 0, // {
+        1, 4, 4, 3, 0, // /* a
+        1, 0, 6, 3, 0, //   é */
+        0, 7, 3, 4, 0, // "é"
+        0, 4, 1, 6, 0, // +
+        0, 2, 1, 5, 0, // 1
+        1, 0, 1, 6, 0, // }
+    ];
+    assert_eq!(semantic_tokens(src, Edition::Edition2021, PositionEncoding::Utf16), expected);
+
+    let utf8 = semantic_tokens(src, Edition::Edition2021, PositionEncoding::Utf8);
+    // `  é */` and `"é"` are a byte longer, and `"é"` starts a byte later.
+    assert_eq!(utf8[30..40], [1, 0, 7, 3, 0, 0, 8, 4, 4, 0]);
+}
+
+#[test]
+fn test_semantic_tokens_doc_comment() {
+    let src = "//! \u{1F980}\nx";
+    let expected = [0, 0, 6, 3, DOCUMENTATION, 1, 0, 1, 1, 0];
+    assert_eq!(semantic_tokens(src, Edition::Edition2021, PositionEncoding::Utf16), expected);
+}
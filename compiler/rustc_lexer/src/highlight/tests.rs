@@ -0,0 +1,95 @@
+use super::*;
+use alloc::format;
+use alloc::string::String;
+use expect_test::{expect, Expect};
+
+fn check(src: &str, edition: Edition, expect: Expect) {
+    let actual: String = highlight(src, edition)
+        .filter(|highlight| highlight.class != HighlightClass::Whitespace)
+        .map(|highlight| format!("{:?} {:?}\n", &src[highlight.range], highlight.class))
+        .collect();
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn test_highlight() {
+    check(
+        "#!/usr/bin/env rustx\n/// Doc\nfn f<'a>(x: &'a u8) -> bool { /* c */ x == b'a' || 1.5e3 > 2 }",
+        Edition::Edition2021,
+        expect![[r##"
+            "#!/usr/bin/env rustx" Comment
+            "/// Doc" DocComment
+            "fn" Keyword
+            "f" Ident
+            "<" Punctuation
+            "'a" Lifetime
+            ">" Punctuation
+            "(" Punctuation
+            "x" Ident
+            ":" Punctuation
+            "&" Punctuation
+            "'a" Lifetime
+            "u8" Ident
+            ")" Punctuation
+            "-" Punctuation
+            ">" Punctuation
+            "bool" Ident
+            "{" Punctuation
+            "/* c */" Comment
+            "x" Ident
+            "=" Punctuation
+            "=" Punctuation
+            "b'a'" String
+            "|" Punctuation
+            "|" Punctuation
+            "1.5e3" Number
+            ">" Punctuation
+            "2" Number
+            "}" Punctuation
+        "##]],
+    );
+}
+
+#[test]
+fn test_highlight_edition() {
+    check(
+        "async dyn r#try 🦀",
+        Edition::Edition2015,
+        expect![[r#"
+            "async" Ident
+            "dyn" Ident
+            "r#try" Ident
+            "🦀" Invalid
+        "#]],
+    );
+    check(
+        "async dyn r#try 🦀",
+        Edition::Edition2018,
+        expect![[r#"
+            "async" Keyword
+            "dyn" Keyword
+            "r#try" Ident
+            "🦀" Invalid
+        "#]],
+    );
+    // Prefixes are only reserved since Rust 2021.
+    check(
+        "f\"a\" c\"b\"",
+        Edition::Edition2015,
+        expect![[r#"
+            "f" Ident
+            "\"a\"" String
+            "c" Ident
+            "\"b\"" String
+        "#]],
+    );
+    check(
+        "f\"a\" c\"b\"",
+        Edition::Edition2021,
+        expect![[r#"
+            "f" Invalid
+            "\"a\"" String
+            "c\"b\"" String
+        "#]],
+    );
+}
@@ -0,0 +1,175 @@
+//! Support for relexing a document after an edit.
+//!
+//! An edit only changes the tokens around it, so analyses which cache
+//! results per token can keep the entries of the unchanged tokens, provided
+//! they know which tokens those are. [`IncrementalLexer`] goes further and
+//! only relexes the tokens around the edit in the first place.
+
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize, Cursor, Token, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// A change to a document: the bytes in `range` of the old text were replaced
+/// by `new_len` bytes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub new_len: usize,
+}
+
+impl TextEdit {
+    /// Maps an offset in the old text which is not inside of the edited range
+    /// to the new text.
+    pub fn map_offset(&self, offset: usize) -> usize {
+        if offset <= self.range.start {
+            offset
+        } else {
+            debug_assert!(offset >= self.range.end);
+            offset - self.range.end + self.range.start + self.new_len
+        }
+    }
+}
+
+/// Maps the tokens of the old text to the tokens of the new text after
+/// `edit`.
+///
+/// For every old token, the result holds the index of the same token in
+/// `new`, or `None` if the token was affected by the edit. Tokens are the
+/// same if they have the same kind and length and lie entirely before or
+/// entirely after the edited range; tokens adjacent to the edit may be
+/// extended by it, in which case their lengths differ and they are not
+/// mapped.
+pub fn map_token_identities(old: &[Token], new: &[Token], edit: &TextEdit) -> Vec<Option<usize>> {
+    let mut res = vec![None; old.len()];
+    let same = |a: &Token, b: &Token| a.kind == b.kind && a.len == b.len;
+
+    // Unchanged prefix.
+    let mut end = 0;
+    let mut prefix = 0;
+    while prefix < old.len().min(new.len()) && same(&old[prefix], &new[prefix]) {
+        end += old[prefix].len;
+        if end > edit.range.start {
+            break;
+        }
+        res[prefix] = Some(prefix);
+        prefix += 1;
+    }
+
+    // Unchanged suffix, not overlapping the prefix.
+    let old_len: usize = old.iter().map(|token| token.len).sum();
+    let mut start = old_len;
+    let (mut i, mut j) = (old.len(), new.len());
+    while i > prefix && j > prefix && same(&old[i - 1], &new[j - 1]) {
+        start -= old[i - 1].len;
+        if start < edit.range.end {
+            break;
+        }
+        res[i - 1] = Some(j - 1);
+        i -= 1;
+        j -= 1;
+    }
+    res
+}
+
+/// A document along with its tokens, which are kept up to date by relexing
+/// only the damaged region on every edit.
+#[derive(Clone, Debug)]
+pub struct IncrementalLexer {
+    text: String,
+    tokens: Vec<Token>,
+    /// Offset of every token in `text`.
+    starts: Vec<usize>,
+}
+
+/// The tokens replaced by an edit, see [`IncrementalLexer::edit`]. All other
+/// tokens are unchanged, apart from the offsets of the ones after the edit.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TokenChange {
+    /// Indices of the replaced tokens in the old token list.
+    pub old: Range<usize>,
+    /// Indices of the tokens replacing them in the new token list.
+    pub new: Range<usize>,
+}
+
+impl IncrementalLexer {
+    pub fn new(text: String) -> IncrementalLexer {
+        let (starts, tokens) =
+            tokenize(&text).with_ranges().map(|(range, token)| (range.start, token)).unzip();
+        IncrementalLexer { text, tokens, starts }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// Returns the byte range of the token with index `idx`.
+    pub fn token_range(&self, idx: usize) -> Range<usize> {
+        self.starts[idx]..self.starts[idx] + self.tokens[idx].len
+    }
+
+    /// Replaces the bytes in `edit.range` by `replacement` and relexes the
+    /// tokens around the edit.
+    ///
+    /// Lexing restarts after the last whitespace before the edit, as the
+    /// tokens between it and the edit may be joined with the edited text, like
+    /// the `r` of `r#` when a `"` is typed after it. It stops at the first
+    /// token boundary after the edit which was a token boundary before,
+    /// since the lexer only needs the offset to continue from a boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `replacement` is not `edit.new_len` bytes long or the edit is
+    /// not at char boundaries.
+    pub fn edit(&mut self, edit: &TextEdit, replacement: &str) -> TokenChange {
+        assert_eq!(edit.new_len, replacement.len(), "length of the replacement");
+        self.text.replace_range(edit.range.clone(), replacement);
+
+        // The token containing the start of the edit, or ending at it.
+        let mut first = self.starts.partition_point(|&start| start < edit.range.start);
+        first = first.saturating_sub(1);
+        while first > 0 && !matches!(self.tokens[first - 1].kind, TokenKind::Whitespace { .. }) {
+            first -= 1;
+        }
+        let restart = self.starts.get(first).copied().unwrap_or(0);
+
+        let edit_end = edit.range.start + edit.new_len;
+        let mut cursor = Cursor::new(&self.text[restart..]);
+        let mut new_tokens = Vec::new();
+        let mut new_starts = Vec::new();
+        let mut pos = restart;
+        let old_end = loop {
+            if pos >= edit_end {
+                let old_pos = pos - edit.new_len + edit.range.len();
+                if let Ok(idx) = self.starts.binary_search(&old_pos) {
+                    break idx;
+                }
+            }
+            if cursor.is_eof() {
+                break self.tokens.len();
+            }
+            let token = cursor.advance_token();
+            new_starts.push(pos);
+            pos += token.len;
+            new_tokens.push(token);
+        };
+
+        let new_end = first + new_tokens.len();
+        self.tokens.splice(first..old_end, new_tokens);
+        self.starts.splice(first..old_end, new_starts);
+        // The remaining tokens start at or after the end of the edit.
+        for start in &mut self.starts[new_end..] {
+            *start = *start + edit.new_len - edit.range.len();
+        }
+        TokenChange { old: first..old_end, new: first..new_end }
+    }
+}
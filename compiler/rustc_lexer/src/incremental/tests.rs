@@ -0,0 +1,102 @@
+use super::*;
+use crate::tokenize;
+
+fn check(old: &str, edit: TextEdit, replacement: &str, expected: &[Option<usize>]) {
+    let mut new = old.to_string();
+    new.replace_range(edit.range.clone(), replacement);
+    assert_eq!(edit.new_len, replacement.len());
+    let old_tokens: Vec<_> = tokenize(old).collect();
+    let new_tokens: Vec<_> = tokenize(&new).collect();
+    assert_eq!(map_token_identities(&old_tokens, &new_tokens, &edit), expected);
+}
+
+#[test]
+fn test_map_token_identities() {
+    // `a + b` -> `a + cd`
+    check(
+        "a + b",
+        TextEdit { range: 4..5, new_len: 2 },
+        "cd",
+        &[Some(0), Some(1), Some(2), Some(3), None],
+    );
+    // `a + b` -> `a - b`
+    check(
+        "a + b",
+        TextEdit { range: 2..3, new_len: 1 },
+        "-",
+        &[Some(0), Some(1), None, Some(3), Some(4)],
+    );
+    // An insertion extending the preceding identifier.
+    check(
+        "ab + c",
+        TextEdit { range: 2..2, new_len: 1 },
+        "x",
+        &[None, Some(1), Some(2), Some(3), Some(4)],
+    );
+    // Opening a string literal swallows everything after it.
+    check(
+        "a; b; c",
+        TextEdit { range: 3..3, new_len: 1 },
+        "\"",
+        &[Some(0), Some(1), Some(2), None, None, None, None],
+    );
+    // Inserting tokens shifts the indices of the following ones.
+    check(
+        "f(x)",
+        TextEdit { range: 2..2, new_len: 3 },
+        "y, ",
+        &[Some(0), Some(1), Some(5), Some(6)],
+    );
+}
+
+#[test]
+fn test_map_offset() {
+    let edit = TextEdit { range: 2..5, new_len: 1 };
+    assert_eq!(edit.map_offset(1), 1);
+    assert_eq!(edit.map_offset(2), 2);
+    assert_eq!(edit.map_offset(5), 3);
+    assert_eq!(edit.map_offset(9), 7);
+}
+
+/// Applies the edits one after the other, checking the tokens against lexing
+/// the whole text after every edit.
+fn check_incremental(text: &str, edits: &[(Range<usize>, &str)]) -> Vec<TokenChange> {
+    let mut lexer = IncrementalLexer::new(text.to_string());
+    let mut changes = Vec::new();
+    for (range, replacement) in edits {
+        let edit = TextEdit { range: range.clone(), new_len: replacement.len() };
+        changes.push(lexer.edit(&edit, replacement));
+        let expected: Vec<_> = tokenize(lexer.text()).collect();
+        assert_eq!(lexer.tokens(), expected, "{:?}", lexer.text());
+        let mut pos = 0;
+        for (idx, token) in expected.iter().enumerate() {
+            assert_eq!(lexer.token_range(idx), pos..pos + token.len);
+            pos += token.len;
+        }
+    }
+    changes
+}
+
+#[test]
+fn test_incremental_lexer() {
+    let changes = check_incremental(
+        "fn f(x: u8) {}",
+        &[(5..5, "y: u8, "), (0..2, "pub fn"), (18..18, "\""), (18..19, "")],
+    );
+    assert_eq!(
+        changes,
+        [
+            TokenChange { old: 2..4, new: 2..10 },
+            TokenChange { old: 0..1, new: 0..3 },
+            TokenChange { old: 12..20, new: 12..15 },
+            TokenChange { old: 12..15, new: 12..20 },
+        ]
+    );
+}
+
+#[test]
+fn test_incremental_joins() {
+    check_incremental("let s = r#  ;", &[(10..10, "\""), (11..11, "\"#"), (9..10, "")]);
+    check_incremental("a /* b */ c", &[(2..3, ""), (0..0, "/*"), (0..2, "")]);
+    check_incremental("", &[(0..0, "x"), (0..0, "'"), (2..2, "'"), (0..3, "")]);
+}
@@ -0,0 +1,81 @@
+//! Detection of invisible characters in identifiers and literals.
+//!
+//! Zero-width spaces and joiners, fillers and similar code points render as
+//! nothing, so two identifiers or strings which look the same in review can
+//! differ. The lexer accepts them silently as part of [`TokenKind::Ident`]
+//! and [`TokenKind::Literal`] tokens, [`invisible_chars`] reports them.
+//!
+//! Bidirectional controls are reported by [`bidi`](crate::bidi) instead.
+
+use alloc::vec::Vec;
+
+use crate::{strip_bom, tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// An invisible character, see [`invisible_chars`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InvisibleChar {
+    /// Byte offset of the char in the source.
+    pub offset: usize,
+    pub c: char,
+    /// Kind of the token containing the char.
+    pub token: TokenKind,
+}
+
+/// Returns whether `c` is a code point which renders as nothing (or as blank
+/// space) and isn't whitespace or a bidirectional control.
+pub fn is_invisible(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00AD}' // SOFT HYPHEN
+            | '\u{034F}' // COMBINING GRAPHEME JOINER
+            | '\u{061C}' // ARABIC LETTER MARK
+            | '\u{115F}'..='\u{1160}' // HANGUL CHOSEONG/JUNGSEONG FILLER
+            | '\u{17B4}'..='\u{17B5}' // KHMER VOWEL INHERENT AQ/AA
+            | '\u{180B}'..='\u{180F}' // MONGOLIAN VARIATION SELECTORS, VOWEL SEPARATOR
+            | '\u{200B}'..='\u{200D}' // ZERO WIDTH SPACE, NON-JOINER, JOINER
+            | '\u{2060}'..='\u{2064}' // WORD JOINER, invisible math operators
+            | '\u{206A}'..='\u{206F}' // deprecated format characters
+            | '\u{3164}' // HANGUL FILLER
+            | '\u{FE00}'..='\u{FE0F}' // VARIATION SELECTORS
+            | '\u{FEFF}' // ZERO WIDTH NO-BREAK SPACE
+            | '\u{FFA0}' // HALFWIDTH HANGUL FILLER
+            | '\u{E0001}' // LANGUAGE TAG
+            | '\u{E0020}'..='\u{E007F}' // TAG characters
+            | '\u{E0100}'..='\u{E01EF}' // VARIATION SELECTORS SUPPLEMENT
+    )
+}
+
+/// Returns the invisible characters (see [`is_invisible`]) in the
+/// identifiers, lifetimes and literals of `src`, as well as those lexed as
+/// [`TokenKind::Unknown`] tokens of their own. Comments are not checked.
+///
+/// A byte order mark at the start of `src` is not reported.
+pub fn invisible_chars(src: &str) -> Vec<InvisibleChar> {
+    let mut res = Vec::new();
+    for (range, kind) in tokenize_with_offsets(src) {
+        let checked = matches!(
+            kind,
+            TokenKind::Ident
+                | TokenKind::InvalidIdent
+                | TokenKind::RawIdent
+                | TokenKind::UnknownPrefix { .. }
+                | TokenKind::Literal { .. }
+                | TokenKind::PrefixedLiteral { .. }
+                | TokenKind::Lifetime { .. }
+                | TokenKind::RawLifetime
+                | TokenKind::Unknown
+        );
+        if checked {
+            for (i, c) in src[range.clone()].char_indices() {
+                let offset = range.start + i;
+                if is_invisible(c) && !(offset == 0 && strip_bom(src).is_some()) {
+                    res.push(InvisibleChar { offset, c, token: kind });
+                }
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,47 @@
+use expect_test::{expect, Expect};
+
+use super::*;
+
+fn check(src: &str, expect: Expect) {
+    let findings: Vec<_> = invisible_chars(src)
+        .into_iter()
+        .map(|f| format!("{} {:?} {:?}", f.offset, f.c, f.token))
+        .collect();
+    expect.assert_eq(&findings.join("\n"));
+}
+
+#[test]
+fn test_visible() {
+    check("fn main() { let s = \"abc\"; }", expect![[""]]);
+}
+
+#[test]
+fn test_idents() {
+    check(
+        "let a\u{200D}b = 1; let \u{3164} = 2;",
+        expect![[r#"
+        5 '\u{200d}' Ident
+        19 'ㅤ' Ident"#]],
+    );
+}
+
+#[test]
+fn test_literals() {
+    check(
+        "\"a\u{200B}b\" b'\u{FEFF}' 1\u{2060}",
+        expect![[r#"
+        2 '\u{200b}' Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 7 }
+        10 '\u{feff}' Literal { kind: Byte { terminated: true }, suffix_start: 6 }
+        16 '\u{2060}' Unknown"#]],
+    );
+}
+
+#[test]
+fn test_comments_are_skipped() {
+    check("// a\u{200B}b\n/* \u{200C} */", expect![[""]]);
+}
+
+#[test]
+fn test_leading_bom() {
+    check("\u{FEFF}fn f() {} \u{FEFF}", expect![[r#"13 '\u{feff}' Unknown"#]]);
+}
@@ -0,0 +1,66 @@
+//! Token streams as JSON lines, for tools which are not written in Rust.
+//!
+//! [`write_tokens`] writes one JSON object per token and line, e.g. for the
+//! first and the literal token of `let x = 42;`:
+//!
+//! ```text
+//! {"kind":"Ident","start":0,"end":3,"text":"let"}
+//! {"kind":"Literal","literal":"Int","start":8,"end":10,"text":"42"}
+//! ```
+//!
+//! `kind` is the name of the [`TokenKind`] variant and `literal` the name of
+//! the [`LiteralKind`](crate::LiteralKind) variant of literals. `start` and
+//! `end` are byte offsets into the input. A byte order mark, shebang and
+//! frontmatter are skipped, see [`strip_prelude`]. The `rustc-lex` binary of
+//! this crate prints the tokens of a file in this format.
+
+use alloc::format;
+use core::fmt::{self, Write};
+
+use crate::{strip_prelude, tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Writes the tokens of `src` to `out` as JSON lines, see the
+/// [module documentation](self).
+pub fn write_tokens(src: &str, out: &mut impl Write) -> fmt::Result {
+    let start = strip_prelude(src).start;
+    let rest = &src[start..];
+    for (range, token_kind) in tokenize_with_offsets(rest) {
+        write!(out, "{{\"kind\":\"{}\"", variant_name(&format!("{:?}", token_kind)))?;
+        if let TokenKind::Literal { kind, .. } | TokenKind::PrefixedLiteral { kind, .. } =
+            token_kind
+        {
+            write!(out, ",\"literal\":\"{}\"", variant_name(&format!("{:?}", kind)))?;
+        }
+        write!(out, ",\"start\":{},\"end\":{},\"text\":", start + range.start, start + range.end)?;
+        write_json_str(&rest[range], out)?;
+        out.write_str("}\n")?;
+    }
+    Ok(())
+}
+
+/// The name of the variant in the `Debug` output of a fieldless or
+/// struct-like enum variant.
+fn variant_name(debug: &str) -> &str {
+    let end = debug.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(debug.len());
+    &debug[..end]
+}
+
+/// Writes `s` as a JSON string literal.
+pub(crate) fn write_json_str(s: &str, out: &mut impl Write) -> fmt::Result {
+    out.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => out.write_str("\\\"")?,
+            '\\' => out.write_str("\\\\")?,
+            '\n' => out.write_str("\\n")?,
+            '\r' => out.write_str("\\r")?,
+            '\t' => out.write_str("\\t")?,
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32)?,
+            c => out.write_char(c)?,
+        }
+    }
+    out.write_char('"')
+}
@@ -0,0 +1,24 @@
+use super::*;
+use alloc::string::String;
+use expect_test::expect;
+
+#[test]
+fn test_write_tokens() {
+    let mut out = String::new();
+    write_tokens("#!/bin/run\nlet s = \"a\\\"\tb\"; 1u8\r\n", &mut out).unwrap();
+    expect![[r#"
+        {"kind":"Whitespace","start":10,"end":11,"text":"\n"}
+        {"kind":"Ident","start":11,"end":14,"text":"let"}
+        {"kind":"Whitespace","start":14,"end":15,"text":" "}
+        {"kind":"Ident","start":15,"end":16,"text":"s"}
+        {"kind":"Whitespace","start":16,"end":17,"text":" "}
+        {"kind":"Eq","start":17,"end":18,"text":"="}
+        {"kind":"Whitespace","start":18,"end":19,"text":" "}
+        {"kind":"Literal","literal":"Str","start":19,"end":26,"text":"\"a\\\"\tb\""}
+        {"kind":"Semi","start":26,"end":27,"text":";"}
+        {"kind":"Whitespace","start":27,"end":28,"text":" "}
+        {"kind":"Literal","literal":"Int","start":28,"end":31,"text":"1u8"}
+        {"kind":"Whitespace","start":31,"end":33,"text":"\r\n"}
+    "#]]
+    .assert_eq(&out);
+}
@@ -0,0 +1,106 @@
+//! Classification of identifiers into keywords, per edition.
+//!
+//! The lexer doesn't tell keywords apart from identifiers: both are lexed as
+//! [`Ident`](crate::TokenKind::Ident). [`classify_ident`] does, following the
+//! keyword lists of `rustc_span::symbol`, which this module has to be kept in
+//! sync with. [`is_valid_identifier`] and [`needs_raw_ident`] build on it
+//! for code generators which need to know how to spell an identifier.
+
+use crate::{is_ident, Edition};
+
+#[cfg(test)]
+mod tests;
+
+/// Keywords which can't be used as identifiers in any edition.
+pub const STRICT_KEYWORDS_2015: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while",
+];
+
+/// Keywords which can't be used as identifiers since Rust 2018.
+pub const STRICT_KEYWORDS_2018: &[&str] = &["async", "await", "dyn"];
+
+/// Keywords reserved for future use in all editions.
+pub const RESERVED_KEYWORDS_2015: &[&str] = &[
+    "abstract", "become", "box", "do", "final", "macro", "override", "priv", "typeof", "unsized",
+    "virtual", "yield",
+];
+
+/// Keywords reserved for future use since Rust 2018.
+pub const RESERVED_KEYWORDS_2018: &[&str] = &["try"];
+
+/// Keywords reserved for future use since Rust 2024.
+pub const RESERVED_KEYWORDS_2024: &[&str] = &["gen"];
+
+/// Keywords which can't be written as raw identifiers either.
+const NOT_RAW: &[&str] = &["crate", "self", "Self", "super"];
+
+/// Identifiers which are keywords only in some contexts, like `union` in
+/// `union U { .. }`. `dyn` is a weak keyword in Rust 2015 as well.
+pub const WEAK_KEYWORDS: &[&str] = &["auto", "default", "macro_rules", "raw", "union"];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IdentClass {
+    /// A keyword which can't be used as an identifier, like `fn`.
+    StrictKeyword,
+    /// A keyword reserved for future use, which can't be used as an
+    /// identifier either, like `yield`.
+    ReservedKeyword,
+    /// An identifier with a special meaning in some contexts, like `union`.
+    WeakKeyword,
+    /// A plain identifier.
+    Ident,
+}
+
+/// Returns the class of the identifier `ident` in `edition`.
+pub fn classify_ident(ident: &str, edition: Edition) -> IdentClass {
+    let since_2018 = edition >= Edition::Edition2018;
+    let since_2024 = edition >= Edition::Edition2024;
+    if STRICT_KEYWORDS_2015.contains(&ident)
+        || (since_2018 && STRICT_KEYWORDS_2018.contains(&ident))
+    {
+        IdentClass::StrictKeyword
+    } else if RESERVED_KEYWORDS_2015.contains(&ident)
+        || (since_2018 && RESERVED_KEYWORDS_2018.contains(&ident))
+        || (since_2024 && RESERVED_KEYWORDS_2024.contains(&ident))
+    {
+        IdentClass::ReservedKeyword
+    } else if WEAK_KEYWORDS.contains(&ident) || ident == "dyn" {
+        IdentClass::WeakKeyword
+    } else {
+        IdentClass::Ident
+    }
+}
+
+/// Whether `ident` is a strict or reserved keyword in `edition`, i.e. can't
+/// be used as an identifier.
+pub fn is_keyword(ident: &str, edition: Edition) -> bool {
+    matches!(
+        classify_ident(ident, edition),
+        IdentClass::StrictKeyword | IdentClass::ReservedKeyword
+    )
+}
+
+/// Whether `ident` must be written as a raw identifier like `r#match` in
+/// `edition`, because it is a keyword.
+///
+/// Returns `false` for the keywords which can't be raw identifiers, like
+/// `self`, and for strings which aren't identifiers at all, so this has to be
+/// combined with [`is_valid_identifier`].
+pub fn needs_raw_ident(ident: &str, edition: Edition) -> bool {
+    is_ident(ident) && is_keyword(ident, edition) && !NOT_RAW.contains(&ident)
+}
+
+/// Whether `s`, which may be a raw identifier like `r#match`, can be used as
+/// the name of an item or a variable in `edition`.
+///
+/// This is the case if `s` lexes as a single identifier which is neither a
+/// keyword nor `_`, or as a raw identifier which is allowed to be raw.
+pub fn is_valid_identifier(s: &str, edition: Edition) -> bool {
+    match s.strip_prefix("r#") {
+        Some(ident) => is_ident(ident) && ident != "_" && !NOT_RAW.contains(&ident),
+        None => is_ident(s) && s != "_" && !is_keyword(s, edition),
+    }
+}
@@ -0,0 +1,55 @@
+use super::*;
+
+#[test]
+fn test_classify_ident() {
+    use Edition::*;
+    use IdentClass::*;
+    let cases = [
+        ("fn", Edition2015, StrictKeyword),
+        ("Self", Edition2015, StrictKeyword),
+        ("yield", Edition2015, ReservedKeyword),
+        ("union", Edition2021, WeakKeyword),
+        ("async", Edition2015, Ident),
+        ("async", Edition2018, StrictKeyword),
+        ("dyn", Edition2015, WeakKeyword),
+        ("dyn", Edition2021, StrictKeyword),
+        ("try", Edition2015, Ident),
+        ("try", Edition2018, ReservedKeyword),
+        ("gen", Edition2021, Ident),
+        ("gen", Edition2024, ReservedKeyword),
+        ("foo", Edition2021, Ident),
+        ("Fn", Edition2021, Ident),
+    ];
+    for (ident, edition, class) in cases {
+        assert_eq!(classify_ident(ident, edition), class, "{} in {:?}", ident, edition);
+    }
+    assert!(is_keyword("await", Edition2018));
+    assert!(!is_keyword("union", Edition2021));
+}
+
+#[test]
+fn test_is_valid_identifier() {
+    use Edition::*;
+    for ident in ["foo", "_foo", "r#match", "r#async", "Ünïcödé", "async", "r#union"] {
+        assert!(is_valid_identifier(ident, Edition2015), "{}", ident);
+    }
+    for ident in
+        ["", "_", "match", "self", "r#self", "r#_", "r#", "1a", "a-b", "a b", "🦀", "r#r#a"]
+    {
+        assert!(!is_valid_identifier(ident, Edition2015), "{}", ident);
+    }
+    assert!(!is_valid_identifier("async", Edition2018));
+}
+
+#[test]
+fn test_needs_raw_ident() {
+    use Edition::*;
+    assert!(needs_raw_ident("match", Edition2015));
+    assert!(needs_raw_ident("yield", Edition2015));
+    assert!(!needs_raw_ident("async", Edition2015));
+    assert!(needs_raw_ident("async", Edition2018));
+    assert!(!needs_raw_ident("union", Edition2021));
+    assert!(!needs_raw_ident("foo", Edition2021));
+    assert!(!needs_raw_ident("self", Edition2021));
+    assert!(!needs_raw_ident("r#match", Edition2021));
+}
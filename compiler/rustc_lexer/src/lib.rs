@@ -20,22 +20,79 @@
 //! [`rustc_parse::lexer`]: ../rustc_parse/lexer/index.html
 // We want to be able to build this crate with a stable compiler, so no
 // `#![feature]` attributes should be added.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
+extern crate alloc;
+
+pub mod bidi;
+pub mod comments;
+mod config;
+pub mod cook;
 mod cursor;
+pub mod doc;
+#[cfg(feature = "encoding")]
+pub mod encoding;
+mod error;
+pub mod escape;
+mod fingerprint;
+#[cfg(feature = "experimental")]
+pub mod format_str;
+pub mod highlight;
+pub mod incremental;
+pub mod invisible;
+pub mod json;
+pub mod keywords;
+pub mod license;
+pub mod lines;
+pub mod markdown;
+pub mod metavar;
+pub mod minify;
+#[cfg(feature = "nfc")]
+pub mod nfc;
+pub mod normalize;
+pub mod pipeline;
+#[cfg(feature = "query")]
+pub mod query;
+pub mod repl;
+pub mod rich;
+pub mod rules;
+#[cfg(feature = "security")]
+pub mod security;
+pub mod segments;
+pub mod stream;
+#[cfg(feature = "simd")]
+mod swar;
+pub mod tree;
 pub mod unescape;
+pub mod utf16;
 
 #[cfg(test)]
 mod tests;
 
 use self::LiteralKind::*;
 use self::TokenKind::*;
-use crate::cursor::{Cursor, EOF_CHAR};
-use std::convert::TryFrom;
+pub use crate::config::{IdentPolicy, LexerConfig};
+use crate::cursor::EOF_CHAR;
+pub use crate::cursor::{Checkpoint, Cursor, CursorState, Source};
+pub use crate::error::{
+    lex_diagnostics, lex_diagnostics_with_edition, tokenize_checked, DiagnosticCode, ErrorSummary,
+    LexDiagnostic, LexError, LexErrorKind, Severity, Suggestion,
+};
+pub use crate::fingerprint::Fingerprint;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+use core::convert::TryFrom;
+use core::fmt;
+use core::ops::Range;
+use core::str::Chars;
 
 /// Parsed token.
 /// It doesn't contain information about data that has been parsed,
 /// only the type of the token and its size.
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Token {
     pub kind: TokenKind,
     pub len: usize,
@@ -47,9 +104,15 @@ fn new(kind: TokenKind, len: usize) -> Token {
     }
 }
 
+// `Token` is passed around a lot, so make sure it doesn't grow by accident.
+// It is 56 bytes, older compilers lay `TokenKind` out less tightly.
+#[cfg(all(target_arch = "x86_64", target_pointer_width = "64"))]
+const _: () = assert!(core::mem::size_of::<Token>() <= 64);
+
 /// Enum representing common lexeme types.
 // perf note: Changing all `usize` to `u32` doesn't change performance. See #77629
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum TokenKind {
     // Multi-char tokens:
     /// "// comment"
@@ -59,8 +122,10 @@ pub enum TokenKind {
     /// Block comments can be recursive, so the sequence like `/* /* */`
     /// will not be considered terminated and will result in a parsing error.
     BlockComment { doc_style: Option<DocStyle>, terminated: bool },
-    /// Any whitespace characters sequence.
-    Whitespace,
+    /// Any whitespace characters sequence. `line_endings` is only recorded if
+    /// [`LexerConfig::classify_line_endings`] is enabled and `counts` only if
+    /// [`LexerConfig::count_whitespace`] is, they are `None` otherwise.
+    Whitespace { line_endings: Option<LineEndingKinds>, counts: Option<WhitespaceCounts> },
     /// "ident" or "continue"
     /// At this step keywords are also considered identifiers.
     Ident,
@@ -73,12 +138,31 @@ pub enum TokenKind {
     /// lexed as its own distinct token). In Rust 2021 and later, reserved
     /// prefixes are reported as errors; in earlier editions, they result in a
     /// (allowed by default) lint, and are treated as regular identifier
-    /// tokens.
-    UnknownPrefix,
+    /// tokens. [`tokenize_with_edition`] lexes them as [`Ident`](TokenKind::Ident)
+    /// for those editions.
+    ///
+    /// The token covers exactly the prefix, so its length is the length of
+    /// the prefix. `followed_by` tells what the prefix is attached to.
+    UnknownPrefix { followed_by: PrefixFollower },
     /// "12_u8", "1.0e-40", "b"123"". See `LiteralKind` for more details.
     Literal { kind: LiteralKind, suffix_start: usize },
+    /// A literal with a custom prefix registered in
+    /// [`LexerConfig::literal_prefixes`], e.g. `sql"select 1"`. `prefix` is the
+    /// index of the prefix in that list, and `suffix_start` is relative to the
+    /// start of the token, like for `Literal`. Never produced when lexing Rust.
+    PrefixedLiteral { prefix: usize, kind: LiteralKind, suffix_start: usize },
+    /// A guarded string like `#"foo"#`, which is reserved syntax since Rust
+    /// 2024. `n_hashes` is the number of `#` before the opening quote. Like
+    /// `rustc`, the string itself is lexed as a regular string, so escapes
+    /// are skipped and the closing quote is followed by up to `n_hashes` `#`
+    /// and a suffix. Only produced for Rust 2024, earlier editions lex a
+    /// [`Pound`](TokenKind::Pound) token for each `#` followed by a string.
+    GuardedStr { n_hashes: u32, terminated: bool },
     /// "'a"
     Lifetime { starts_with_number: bool },
+    /// "'r#a", a lifetime which may be named like a keyword. Before Rust 2021,
+    /// this is lexed as the lifetime `'r` followed by `#` and an identifier.
+    RawLifetime,
 
     // One-char tokens:
     /// ";"
@@ -140,50 +224,284 @@ pub enum TokenKind {
     Unknown,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+impl TokenKind {
+    /// Whether the token is whitespace or a comment which is not a doc
+    /// comment, i.e. has no meaning for the parser.
+    pub fn is_trivia(&self) -> bool {
+        match self {
+            Whitespace { .. } => true,
+            LineComment { doc_style } | BlockComment { doc_style, .. } => doc_style.is_none(),
+            _ => false,
+        }
+    }
+
+    /// A short description of the kind of token for use in messages, like
+    /// "open parenthesis" or "raw string literal". This is also what the
+    /// `Display` implementation prints.
+    pub fn description(&self) -> &'static str {
+        match self {
+            LineComment { doc_style: None } => "line comment",
+            LineComment { doc_style: Some(DocStyle::Outer) } => "doc comment",
+            LineComment { doc_style: Some(DocStyle::Inner) } => "inner doc comment",
+            BlockComment { doc_style: None, .. } => "block comment",
+            BlockComment { doc_style: Some(DocStyle::Outer), .. } => "block doc comment",
+            BlockComment { doc_style: Some(DocStyle::Inner), .. } => "inner block doc comment",
+            Whitespace { .. } => "whitespace",
+            Ident => "identifier",
+            InvalidIdent => "invalid identifier",
+            RawIdent => "raw identifier",
+            UnknownPrefix { .. } => "unknown prefix",
+            Literal { kind, .. } | PrefixedLiteral { kind, .. } => kind.description(),
+            GuardedStr { .. } => "guarded string",
+            Lifetime { .. } => "lifetime",
+            RawLifetime => "raw lifetime",
+            Semi => "semicolon",
+            Comma => "comma",
+            Dot => "dot",
+            OpenParen => "open parenthesis",
+            CloseParen => "close parenthesis",
+            OpenBrace => "open brace",
+            CloseBrace => "close brace",
+            OpenBracket => "open bracket",
+            CloseBracket => "close bracket",
+            At => "at sign",
+            Pound => "pound sign",
+            Tilde => "tilde",
+            Question => "question mark",
+            Colon => "colon",
+            Dollar => "dollar sign",
+            Eq => "equals sign",
+            Bang => "exclamation mark",
+            Lt => "less-than sign",
+            Gt => "greater-than sign",
+            Minus => "minus sign",
+            And => "ampersand",
+            Or => "vertical bar",
+            Plus => "plus sign",
+            Star => "asterisk",
+            Slash => "slash",
+            Caret => "caret",
+            Percent => "percent sign",
+            Unknown => "unknown token",
+        }
+    }
+}
+
+impl fmt::Display for TokenKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DocStyle {
     Outer,
     Inner,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// What an [`UnknownPrefix`](TokenKind::UnknownPrefix) is directly followed
+/// by, i.e. what kind of literal it would be the prefix of.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PrefixFollower {
+    /// A string, like in `foo"bar"`.
+    Str,
+    /// A char literal or a lifetime, like in `foo'a'` or `foo'a`.
+    Char,
+    /// A `#`, like in `foo#bar` or `foo#"bar"#`.
+    Pound,
+}
+
+/// The kinds of line endings in a [`Whitespace`](TokenKind::Whitespace) token,
+/// or in a whole file, see [`lines::LineEndings::kinds`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LineEndingKinds {
+    /// Contains a `\n` which is not preceded by `\r`.
+    pub lf: bool,
+    /// Contains a `\r\n`.
+    pub crlf: bool,
+    /// Contains a `\r` which is not followed by `\n`.
+    pub cr: bool,
+}
+
+/// The composition of a [`Whitespace`](TokenKind::Whitespace) token. Other
+/// whitespace chars, like form feeds or U+200E LEFT-TO-RIGHT MARK, are not
+/// counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WhitespaceCounts {
+    pub spaces: u32,
+    pub tabs: u32,
+    /// The number of line endings, counting `\r\n` once.
+    pub newlines: u32,
+}
+
+impl LineEndingKinds {
+    /// Whether more than one kind of line ending is used.
+    pub fn is_mixed(&self) -> bool {
+        (self.lf as u8 + self.crlf as u8 + self.cr as u8) > 1
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LiteralKind {
     /// "12_u8", "0o100", "0b120i99"
-    Int { base: Base, empty_int: bool },
+    ///
+    /// `empty_int` is set if the base prefix is not followed by any digits,
+    /// as in "0x". `underscore_after_prefix` is true if the base prefix is
+    /// directly followed by an underscore, as in "0x_FF".
+    Int { base: Base, empty_int: Option<MissingDigits>, underscore_after_prefix: bool },
     /// "12.34f32", "0b100.100"
-    Float { base: Base, empty_exponent: bool },
+    ///
+    /// `empty_exponent` is set if the exponent has no digits, as in "1e+".
+    /// `hex_exponent` is the offset of the `p` of a hexadecimal float with a
+    /// binary exponent like "0x1.8p3", which Rust doesn't support.
+    Float { base: Base, empty_exponent: Option<MissingDigits>, hex_exponent: Option<u32> },
     /// "'a'", "'\\'", "'''", "';"
     Char { terminated: bool },
     /// "b'a'", "b'\\'", "b'''", "b';"
     Byte { terminated: bool },
     /// ""abc"", ""abc"
-    Str { terminated: bool },
+    ///
+    /// For all string kinds, `first_newline` is the offset of the first
+    /// newline in the literal from the start of the token, if it spans
+    /// multiple lines.
+    Str { terminated: bool, first_newline: Option<u32> },
     /// "b"abc"", "b"abc"
-    ByteStr { terminated: bool },
+    ByteStr { terminated: bool, first_newline: Option<u32> },
     /// "r"abc"", "r#"abc"#", "r####"ab"###"c"####", "r#"a"
-    RawStr { n_hashes: u16, err: Option<RawStrError> },
+    RawStr { n_hashes: u32, err: Option<RawStrError>, first_newline: Option<u32> },
     /// "br"abc"", "br#"abc"#", "br####"ab"###"c"####", "br#"a"
-    RawByteStr { n_hashes: u16, err: Option<RawStrError> },
+    RawByteStr { n_hashes: u32, err: Option<RawStrError>, first_newline: Option<u32> },
+    /// "c"abc"", "c"abc"
+    CStr { terminated: bool, first_newline: Option<u32> },
+    /// "cr"abc"", "cr#"abc"#", "cr####"ab"###"c"####", "cr#"a"
+    RawCStr { n_hashes: u32, err: Option<RawStrError>, first_newline: Option<u32> },
+}
+
+impl LiteralKind {
+    /// A short description of the kind of literal for use in messages, like
+    /// "raw string literal". This is also what the `Display` implementation
+    /// prints.
+    pub fn description(&self) -> &'static str {
+        match self {
+            Int { .. } => "integer literal",
+            Float { .. } => "float literal",
+            Char { .. } => "character literal",
+            Byte { .. } => "byte literal",
+            Str { .. } => "string literal",
+            ByteStr { .. } => "byte string literal",
+            RawStr { .. } => "raw string literal",
+            RawByteStr { .. } => "raw byte string literal",
+            CStr { .. } => "C string literal",
+            RawCStr { .. } => "raw C string literal",
+        }
+    }
+
+    /// Returns the byte range of the contents of a literal token with this
+    /// kind, i.e. without prefix, quotes, hashes and suffix.
+    ///
+    /// `text` is the text of the token and `suffix_start` its `suffix_start`.
+    /// For a [`TokenKind::PrefixedLiteral`], `text` and `suffix_start` have to
+    /// exclude the custom prefix. For numbers, the base prefix like `0x` is
+    /// not part of the contents. Unterminated literals extend up to the end
+    /// of `text`.
+    pub fn content_range(&self, text: &str, suffix_start: usize) -> Range<usize> {
+        let quoted = |prefix_len: usize, terminated: bool| {
+            let end = if terminated { suffix_start - 1 } else { suffix_start };
+            prefix_len.min(end)..end
+        };
+        match *self {
+            Int { base, .. } | Float { base, .. } => {
+                let start = if base == Base::Decimal { 0 } else { 2 };
+                start.min(suffix_start)..suffix_start
+            }
+            Char { terminated } => quoted(1, terminated),
+            Byte { terminated } => quoted(2, terminated),
+            Str { terminated, .. } => quoted(1, terminated),
+            ByteStr { terminated, .. } | CStr { terminated, .. } => quoted(2, terminated),
+            RawStr { .. } | RawByteStr { .. } | RawCStr { .. } => {
+                // Count the hashes instead of using `n_hashes`, which is 0 if
+                // there are too many of them.
+                let prefix_len = if matches!(self, RawStr { .. }) { 1 } else { 2 };
+                let literal = &text[..suffix_start];
+                let n_hashes = literal[prefix_len..].bytes().take_while(|&b| b == b'#').count();
+                let start = (prefix_len + n_hashes + 1).min(suffix_start);
+                let mut end = suffix_start;
+                let contents = &literal[start..];
+                let trimmed = contents.trim_end_matches('#');
+                if contents.len() - trimmed.len() == n_hashes && trimmed.ends_with('"') {
+                    end -= n_hashes + 1;
+                }
+                start..end
+            }
+        }
+    }
+}
+
+impl fmt::Display for LiteralKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.description())
+    }
+}
+
+/// The part of a number which lacks its digits, like the `0x` of `0xu8` or
+/// the `e+` of `1.5e+`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MissingDigits {
+    /// Offset of the base prefix or of the `e` in the token.
+    pub start: u32,
+    /// Offset in the token at which the digits were expected, after the
+    /// prefix or exponent sign and any underscores.
+    pub expected_at: u32,
 }
 
 /// Error produced validating a raw string. Represents cases like:
 /// - `r##~"abcde"##`: `InvalidStarter`
-/// - `r###"abcde"##`: `NoTerminator { expected: 3, found: 2, possible_terminator_offset: Some(11)`
-/// - Too many `#`s (>65535): `TooManyDelimiters`
-// perf note: It doesn't matter that this makes `Token` 36 bytes bigger. See #77629
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+/// - `r###"abcde"##`: `NoTerminator { expected: 3, found: 2, possible_terminator_offset: Some(11) }`
+/// - Too many `#`s (>65535 by default): `TooManyDelimiters`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RawStrError {
     /// Non `#` characters exist between `r` and `"` eg. `r#~"..`
     InvalidStarter { bad_char: char },
     /// The string was never terminated. `possible_terminator_offset` is the number of characters after `r` or `br` where they
-    /// may have intended to terminate it.
-    NoTerminator { expected: usize, found: usize, possible_terminator_offset: Option<usize> },
-    /// More than 65535 `#`s exist.
-    TooManyDelimiters { found: usize },
+    /// may have intended to terminate it: the `"` followed by the most `#`s,
+    /// preferring ones not followed by an identifier or quote like `"#a`, or
+    /// else a `"` ending the input, see [`raw_str_terminator_candidates`] for
+    /// the alternatives.
+    NoTerminator { expected: u32, found: u32, possible_terminator_offset: Option<u32> },
+    /// More `#`s than [`LexerConfig::max_raw_str_hashes`] exist. `max` is
+    /// that limit.
+    TooManyDelimiters { found: u32, max: u32 },
+}
+
+impl RawStrError {
+    /// For [`RawStrError::NoTerminator`], returns the offset and the text to
+    /// insert there to terminate the string: the missing `#`s after the best
+    /// candidate terminator, or a complete terminator at the end of the
+    /// token, which is `token_len` bytes long, if there is no candidate.
+    pub fn terminator_fix(&self, token_len: usize) -> Option<(usize, String)> {
+        match *self {
+            RawStrError::NoTerminator { expected, found, possible_terminator_offset } => {
+                let missing = "#".repeat((expected - found) as usize);
+                Some(match possible_terminator_offset {
+                    Some(offset) => ((offset + found) as usize, missing),
+                    None => (token_len, format!("\"{}", missing)),
+                })
+            }
+            _ => None,
+        }
+    }
 }
 
 /// Base of numeric literal encoding according to its prefix.
-#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Base {
     /// Literal starts with "0b".
     Binary,
@@ -195,29 +513,187 @@ pub enum Base {
     Decimal,
 }
 
+/// An edition of Rust. It decides which syntax is reserved, like prefixes
+/// and guarded strings, and the interpretation of some tokens, e.g. which
+/// identifiers are keywords.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Edition {
+    Edition2015,
+    Edition2018,
+    Edition2021,
+    Edition2024,
+}
+
+/// Returns the length of the UTF-8 byte order mark at the start of `input`,
+/// if there is one.
+///
+/// `rustc` skips a leading BOM before anything else, so files starting with
+/// both have the BOM first. To skip both, strip the BOM and then call
+/// [`strip_shebang`] on the rest of the input.
+pub fn strip_bom(input: &str) -> Option<usize> {
+    if input.starts_with('\u{FEFF}') {
+        Some('\u{FEFF}'.len_utf8())
+    } else {
+        None
+    }
+}
+
 /// `rustc` allows files to have a shebang, e.g. "#!/usr/bin/rustrun",
 /// but shebang isn't a part of rust syntax.
-pub fn strip_shebang(input: &str) -> Option<usize> {
+pub fn strip_shebang(input: &str) -> Option<Shebang> {
+    shebang_decision(input, |_| ()).1
+}
+
+/// A shebang line, see [`strip_shebang`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Shebang {
+    /// The length of the shebang line, without its line break.
+    pub len: usize,
+    /// Byte range of the interpreter command after the `#!`, without
+    /// surrounding whitespace, e.g. `/usr/bin/env run-cargo-script`.
+    pub interpreter: Range<usize>,
+    /// Whether the line is ended by a line break rather than the end of input.
+    pub ends_with_newline: bool,
+}
+
+/// Single-file packages, e.g. scripts run with `cargo -Zscript`, may have a
+/// frontmatter with their manifest after the shebang:
+///
+/// ```text
+/// #!/usr/bin/env -S cargo -Zscript
+/// ---cargo
+/// [dependencies]
+/// regex = "1"
+/// ---
+/// fn main() {}
+/// ```
+///
+/// The frontmatter is opened by a line of at least three dashes, optionally
+/// followed by an info string, and closed by a line of the same number of
+/// dashes. Only whitespace may precede it, besides a shebang.
+///
+/// Returns the number of bytes to skip to get past the frontmatter, i.e. the
+/// offset of the line break ending the closing fence. This includes the
+/// shebang, if there is one. Returns `None` if there is no frontmatter or it
+/// is not closed.
+pub fn strip_frontmatter(input: &str) -> Option<usize> {
+    let start = strip_shebang(input).map_or(0, |shebang| shebang.len);
+    frontmatter(input, 0, start).map(|range| range.end)
+}
+
+/// Finds a frontmatter starting after `start`, see [`strip_frontmatter`].
+/// The first line starts after the byte order mark of length `bom_len`.
+fn frontmatter(input: &str, bom_len: usize, start: usize) -> Option<Range<usize>> {
+    let rest = &input[start..];
+    let pos = start + rest.len() - rest.trim_start_matches(is_whitespace).len();
+    // The opening fence has to start a line.
+    if pos > bom_len && !input[..pos].ends_with('\n') {
+        return None;
+    }
+    let opening = &input[pos..];
+    let dashes = opening.len() - opening.trim_start_matches('-').len();
+    if dashes < 3 {
+        return None;
+    }
+    let mut line_end = pos + opening.find('\n')?;
+    while line_end < input.len() {
+        let line_start = line_end + 1;
+        line_end = input[line_start..].find('\n').map_or(input.len(), |i| line_start + i);
+        let line = input[line_start..line_end].trim_end_matches(is_whitespace);
+        if line.len() == dashes && line.bytes().all(|b| b == b'-') {
+            return Some(pos..line_end);
+        }
+    }
+    None
+}
+
+/// What [`strip_prelude`] found before the Rust code of a file. All offsets
+/// are relative to the whole input, including those of the shebang.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Prelude {
+    /// Whether the input starts with a UTF-8 byte order mark.
+    pub bom: bool,
+    pub shebang: Option<Shebang>,
+    /// Byte range of the frontmatter, from the opening fence to the end of
+    /// the closing one.
+    pub frontmatter: Option<Range<usize>>,
+    /// The offset where the Rust tokens begin.
+    pub start: usize,
+}
+
+/// Skips everything before the Rust code of a file: a byte order mark, a
+/// shebang and a frontmatter, in this order. See [`strip_bom`],
+/// [`strip_shebang`] and [`strip_frontmatter`].
+pub fn strip_prelude(input: &str) -> Prelude {
+    let bom_len = strip_bom(input).unwrap_or(0);
+    let shebang = strip_shebang(&input[bom_len..]).map(|shebang| Shebang {
+        len: shebang.len,
+        interpreter: bom_len + shebang.interpreter.start..bom_len + shebang.interpreter.end,
+        ends_with_newline: shebang.ends_with_newline,
+    });
+    let shebang_end = bom_len + shebang.as_ref().map_or(0, |shebang| shebang.len);
+    let frontmatter = frontmatter(input, bom_len, shebang_end);
+    let start = frontmatter.as_ref().map_or(shebang_end, |frontmatter| frontmatter.end);
+    Prelude { bom: bom_len > 0, shebang, frontmatter, start }
+}
+
+/// Like [`strip_shebang`], but also reports why the decision was made.
+pub fn explain_strip_shebang(input: &str) -> ShebangTrace {
+    let mut peeked = Vec::new();
+    let (rule, stripped) = shebang_decision(input, |token| peeked.push(token));
+    ShebangTrace { stripped, rule, peeked }
+}
+
+/// Explanation of a [`strip_shebang`] decision.
+#[derive(Debug)]
+pub struct ShebangTrace {
+    /// The result of [`strip_shebang`].
+    pub stripped: Option<Shebang>,
+    /// The rule which decided the result.
+    pub rule: ShebangRule,
+    /// The tokens after `#!` which were inspected, in order. The last one is
+    /// the token that decided the result, unless the end of input was reached.
+    pub peeked: Vec<Token>,
+}
+
+/// The rules [`strip_shebang`] applies, in order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShebangRule {
+    /// The input does not start with `#!`, so there is no shebang.
+    NoShebangPrefix,
+    /// `#!` is followed by `[` (possibly after whitespace and non-doc
+    /// comments), so it is the start of an inner attribute like `#![allow(..)]`.
+    InnerAttribute,
+    /// `#!` is followed by something other than `[`, so the first line is a
+    /// shebang.
+    Shebang,
+}
+
+fn shebang_decision(input: &str, mut peek: impl FnMut(Token)) -> (ShebangRule, Option<Shebang>) {
     // Shebang must start with `#!` literally, without any preceding whitespace.
     // For simplicity we consider any line starting with `#!` a shebang,
     // regardless of restrictions put on shebangs by specific platforms.
     if let Some(input_tail) = input.strip_prefix("#!") {
         // Ok, this is a shebang but if the next non-whitespace token is `[`,
         // then it may be valid Rust code, so consider it Rust code.
-        let next_non_whitespace_token = tokenize(input_tail).map(|tok| tok.kind).find(|tok| {
-            !matches!(
-                tok,
-                TokenKind::Whitespace
-                    | TokenKind::LineComment { doc_style: None }
-                    | TokenKind::BlockComment { doc_style: None, .. }
-            )
-        });
+        let next_non_whitespace_token = tokenize(input_tail)
+            .inspect(|tok| peek(Token::new(tok.kind, tok.len)))
+            .map(|tok| tok.kind)
+            .find(|tok| !tok.is_trivia());
         if next_non_whitespace_token != Some(TokenKind::OpenBracket) {
             // No other choice than to consider this a shebang.
-            return Some(2 + input_tail.lines().next().unwrap_or_default().len());
+            let line = input_tail.lines().next().unwrap_or_default();
+            let len = 2 + line.len();
+            let start = len - line.trim_start_matches(is_whitespace).len();
+            let end = 2 + line.trim_end_matches(is_whitespace).len();
+            let interpreter = start..end.max(start);
+            let shebang = Shebang { len, interpreter, ends_with_newline: len < input.len() };
+            return (ShebangRule::Shebang, Some(shebang));
         }
+        return (ShebangRule::InnerAttribute, None);
     }
-    None
+    (ShebangRule::NoShebangPrefix, None)
 }
 
 /// Parses the first token from the provided input string.
@@ -227,16 +703,254 @@ pub fn first_token(input: &str) -> Token {
 }
 
 /// Creates an iterator that produces tokens from the input string.
-pub fn tokenize(input: &str) -> impl Iterator<Item = Token> + '_ {
+pub fn tokenize(input: &str) -> Tokens<'_> {
+    tokenize_with_config(input, &LexerConfig::DEFAULT)
+}
+
+/// Slightly less than the average length of a token in Rust code, counting
+/// whitespace and comments as tokens, used to estimate the number of tokens
+/// of an input. Underestimating the length makes it unlikely that the vector
+/// of tokens has to grow.
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Lexes the whole `input` into a vector, together with a summary of the
+/// problems found in it.
+///
+/// Unlike collecting [`tokenize`], this preallocates the vector based on the
+/// length of the input and drives the [`Cursor`] directly, which is faster
+/// for consumers which need all tokens at once.
+pub fn tokenize_into_vec(input: &str) -> (Vec<Token>, ErrorSummary) {
+    let mut tokens = Vec::with_capacity(input.len() / BYTES_PER_TOKEN + 1);
+    let mut summary = ErrorSummary::default();
     let mut cursor = Cursor::new(input);
-    std::iter::from_fn(move || {
-        if cursor.is_eof() {
+    let mut pos = 0;
+    while !cursor.is_eof() {
+        let token = cursor.advance_token();
+        let range = pos..pos + token.len;
+        pos = range.end;
+        if let Some(kind) = error::error_kind(token.kind, &input[range.clone()]) {
+            summary.error_count += 1;
+            if summary.first_error.is_none() {
+                summary.first_error = Some(LexError { range, kind });
+            }
+        }
+        tokens.push(token);
+    }
+    (tokens, summary)
+}
+
+/// Lexes `input` up to the first token boundary at or past `max_bytes`.
+///
+/// Returns the tokens and, unless the whole input was lexed, the state from
+/// which to continue, e.g. with [`Tokens::restore`] or [`Cursor::resume`].
+/// This allows lexing the visible part of a huge file first and the rest in
+/// the background.
+pub fn tokenize_bounded(input: &str, max_bytes: usize) -> (Vec<Token>, Option<CursorState>) {
+    let mut tokens = tokenize(input);
+    let mut res = Vec::new();
+    while tokens.state().pos() < max_bytes {
+        match tokens.next() {
+            Some(token) => res.push(token),
+            None => break,
+        }
+    }
+    let state = if tokens.cursor.is_eof() { None } else { Some(tokens.state()) };
+    (res, state)
+}
+
+/// Like [`tokenize`], but also produces the [`Fingerprint`] of every token.
+pub fn tokenize_with_fingerprints(input: &str) -> impl Iterator<Item = (Token, Fingerprint)> + '_ {
+    tokenize(input).with_ranges().map(move |(range, token)| {
+        let fingerprint = Fingerprint::of(token.kind, &input[range]);
+        (token, fingerprint)
+    })
+}
+
+/// Like [`tokenize`], but produces the byte range of every token in `input`
+/// instead of its length, so the text can be sliced directly.
+pub fn tokenize_with_offsets(
+    input: &str,
+) -> impl Iterator<Item = (Range<usize>, TokenKind)> + Clone + '_ {
+    tokenize(input).with_ranges().map(|(range, token)| (range, token.kind))
+}
+
+/// Like [`tokenize`], but produces the text of every token along with it.
+/// The texts are slices of `src` itself.
+pub fn tokenize_with_text(src: &str) -> impl Iterator<Item = (Token, &str)> + Clone {
+    let mut rest = src;
+    tokenize(src).map(move |token| {
+        let (text, tail) = rest.split_at(token.len);
+        rest = tail;
+        (token, text)
+    })
+}
+
+/// Like [`tokenize`], but makes the lexical decisions which depend on the
+/// edition, like whether prefixes are reserved, according to `edition`.
+/// [`tokenize`] lexes Rust 2021.
+pub fn tokenize_with_edition(input: &str, edition: Edition) -> Tokens<'_> {
+    let config = match edition {
+        Edition::Edition2015 => &LexerConfig::EDITION_2015,
+        Edition::Edition2018 => &LexerConfig::EDITION_2018,
+        Edition::Edition2021 => &LexerConfig::DEFAULT,
+        Edition::Edition2024 => &LexerConfig::EDITION_2024,
+    };
+    tokenize_with_config(input, config)
+}
+
+/// Like [`tokenize`], but lexes according to `config`.
+pub fn tokenize_with_config<'a>(input: &'a str, config: &'a LexerConfig<'a>) -> Tokens<'a> {
+    Tokens { input, cursor: Cursor::with_config(input, config) }
+}
+
+/// Iterator over the tokens of a string, see [`tokenize`].
+///
+/// Cloning the iterator is cheap, so speculative consumers can fork the token
+/// stream instead of collecting it up front.
+#[derive(Clone)]
+pub struct Tokens<'a> {
+    input: &'a str,
+    cursor: Cursor<'a>,
+}
+
+impl<'a> Tokens<'a> {
+    /// Saves the current position, which is always at a token boundary.
+    pub fn state(&self) -> CursorState {
+        self.cursor.state()
+    }
+
+    /// Continues lexing from a position saved with [`Tokens::state`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `state` is not a char boundary of the input, i.e. if it was
+    /// saved from an iterator over a different input.
+    pub fn restore(&mut self, state: CursorState) {
+        self.cursor = Cursor::resume_with_config(self.input, self.cursor.config, state)
+            .expect("state of a different input");
+    }
+
+    /// Skips the trivia, see [`TokenKind::is_trivia`]. Every token is yielded
+    /// with the length of the trivia preceding it, so offsets can still be
+    /// tracked.
+    pub fn filter_trivia(self) -> FilterTrivia<Self> {
+        FilterTrivia { inner: self, trivia_len: 0 }
+    }
+
+    /// Produces the byte range of every token in the input along with it.
+    pub(crate) fn with_ranges(
+        mut self,
+    ) -> impl Iterator<Item = (Range<usize>, Token)> + Clone + 'a {
+        core::iter::from_fn(move || {
+            let start = self.cursor.pos();
+            self.next().map(|token| (start..start + token.len, token))
+        })
+    }
+}
+
+/// The state of the lexer inside of a string literal or block comment, from
+/// which lexing can continue with [`tokenize_continuing`] or
+/// [`Cursor::continue_token`].
+///
+/// Editors relex from the position of an edit. If that position is inside of
+/// a literal or comment which the user is typing, lexing it as the start of
+/// a token would turn the rest of the line, or the file, into garbage.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Continuation {
+    /// Inside the contents of a `"..."` string, not right after a backslash.
+    Str,
+    /// Inside the contents of a `b"..."` string, not right after a backslash.
+    ByteStr,
+    /// Inside the contents of a raw string opened with `n_hashes` hashes.
+    RawStr { n_hashes: u32 },
+    /// Inside the contents of a raw byte string opened with `n_hashes` hashes.
+    RawByteStr { n_hashes: u32 },
+    /// Inside the contents of a `c"..."` string, not right after a backslash.
+    CStr,
+    /// Inside the contents of a raw C string opened with `n_hashes` hashes.
+    RawCStr { n_hashes: u32 },
+    /// Inside a block comment, `depth` levels deep.
+    BlockComment { doc_style: Option<DocStyle>, depth: usize },
+}
+
+/// Like [`tokenize`], but `input` starts inside of a literal or comment as
+/// described by `continuation`. The first token is the rest of that literal
+/// or comment, see [`Cursor::continue_token`].
+pub fn tokenize_continuing(
+    input: &str,
+    continuation: Continuation,
+) -> impl Iterator<Item = Token> + '_ {
+    let mut tokens = tokenize(input);
+    let first = tokens.cursor.continue_token(continuation);
+    core::iter::once(first).filter(|token| token.len > 0).chain(tokens)
+}
+
+impl Iterator for Tokens<'_> {
+    type Item = Token;
+
+    fn next(&mut self) -> Option<Token> {
+        if self.cursor.is_eof() {
             None
         } else {
-            cursor.reset_len_consumed();
-            Some(cursor.advance_token())
+            Some(self.cursor.advance_token())
         }
-    })
+    }
+}
+
+/// Iterator over the tokens which are not trivia, see [`Tokens::filter_trivia`].
+#[derive(Clone)]
+pub struct FilterTrivia<I> {
+    inner: I,
+    trivia_len: usize,
+}
+
+impl<I: Iterator<Item = Token>> FilterTrivia<I> {
+    /// Length of the trivia consumed since the last yielded token. Once the
+    /// iterator is exhausted, this is the length of the trailing trivia.
+    pub fn pending_trivia_len(&self) -> usize {
+        self.trivia_len
+    }
+}
+
+impl<I: Iterator<Item = Token>> Iterator for FilterTrivia<I> {
+    /// The length of the trivia preceding the token and the token.
+    type Item = (usize, Token);
+
+    fn next(&mut self) -> Option<(usize, Token)> {
+        for token in &mut self.inner {
+            if token.kind.is_trivia() {
+                self.trivia_len += token.len;
+            } else {
+                return Some((core::mem::take(&mut self.trivia_len), token));
+            }
+        }
+        None
+    }
+}
+
+/// [`is_whitespace`] for ASCII characters, indexed by their code.
+pub const ASCII_WHITESPACE: [bool; 128] = ascii_table(b"\t\n\x0B\x0C\r ");
+
+/// [`is_id_start`] for ASCII characters, indexed by their code.
+pub const ASCII_ID_START: [bool; 128] =
+    ascii_table(b"_abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ");
+
+/// [`is_id_continue`] for ASCII characters, indexed by their code.
+pub const ASCII_ID_CONTINUE: [bool; 128] =
+    ascii_table(b"_abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789");
+
+/// ASCII characters which are lexed as single-character punctuation tokens
+/// such as [`TokenKind::Semi`], indexed by their code.
+pub const ASCII_PUNCTUATION: [bool; 128] = ascii_table(b";,.(){}[]@#~?:$=!<>-&|+*/^%");
+
+const fn ascii_table(chars: &[u8]) -> [bool; 128] {
+    let mut table = [false; 128];
+    let mut i = 0;
+    while i < chars.len() {
+        table[chars[i] as usize] = true;
+        i += 1;
+    }
+    table
 }
 
 /// True if `c` is considered a whitespace according to Rust language definition.
@@ -296,11 +1010,162 @@ pub fn is_ident(string: &str) -> bool {
     }
 }
 
-impl Cursor<'_> {
+/// Splits the text of a literal token into the literal and its suffix, which
+/// is `None` if the literal has no suffix.
+///
+/// `suffix_start` is the `suffix_start` of the token, e.g. of a
+/// [`TokenKind::Literal`]. Use [`is_valid_suffix`] to check the suffix.
+pub fn split_suffix(text: &str, suffix_start: usize) -> (&str, Option<&str>) {
+    let (literal, suffix) = text.split_at(suffix_start);
+    (literal, if suffix.is_empty() { None } else { Some(suffix) })
+}
+
+/// True if `suffix` is a well-formed literal suffix: an identifier other than
+/// `_`. The lexer accepts whatever its [`IdentPolicy`] allows in identifiers
+/// as a suffix, so a suffix may still be malformed for Rust.
+pub fn is_valid_suffix(suffix: &str) -> bool {
+    suffix != "_" && is_ident(suffix)
+}
+
+/// For the text of an unterminated char or byte literal, returns the offset
+/// at which the closing quote was most likely meant to be: right after the
+/// first (possibly escaped) character.
+///
+/// Also accepts lifetime-like text, since `'a;` lexes as a lifetime but may
+/// be an unterminated `'a'`. Returns `None` if `text` doesn't start with a
+/// single quote or has nothing after it.
+pub fn char_close_quote_offset(text: &str) -> Option<usize> {
+    let contents = text.strip_prefix("b'").or_else(|| text.strip_prefix('\''))?;
+    let mut chars = contents.chars();
+    if chars.next()? == '\\' {
+        let eat_while = |chars: &mut Chars<'_>, max: usize, pred: fn(char) -> bool| {
+            for _ in 0..max {
+                if !chars.as_str().starts_with(pred) {
+                    break;
+                }
+                chars.next();
+            }
+        };
+        match chars.next() {
+            Some('u') if chars.as_str().starts_with('{') => {
+                chars.next();
+                eat_while(&mut chars, usize::MAX, |c| c.is_ascii_hexdigit() || c == '_');
+                eat_while(&mut chars, 1, |c| c == '}');
+            }
+            Some('x') => eat_while(&mut chars, 2, |c| c.is_ascii_hexdigit()),
+            _ => {}
+        }
+    }
+    Some(text.len() - chars.as_str().len())
+}
+
+/// The structure of a raw string literal, see [`validate_raw_str`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RawStrParts {
+    /// Number of `#`s delimiting the string.
+    pub n_hashes: u32,
+    /// Byte range of the contents between the quotes.
+    pub content: Range<usize>,
+    /// Length of the literal including its closing delimiter, i.e. the offset
+    /// at which a suffix would start.
+    pub len: usize,
+}
+
+/// A place where an unterminated raw string may have been meant to end, see
+/// [`raw_str_terminator_candidates`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TerminatorCandidate {
+    /// Offset of the `#`s after the `"`, like
+    /// `possible_terminator_offset` of [`RawStrError::NoTerminator`].
+    pub offset: usize,
+    /// Number of `#`s after the `"`, fewer than the string was opened with.
+    /// Only a `"` ending the input is a candidate without any `#`s.
+    pub found: usize,
+}
+
+/// Returns the places where the unterminated raw string, raw byte string or
+/// raw C string literal `text` may have been meant to end, best first. The first one is
+/// the `possible_terminator_offset` of the [`RawStrError::NoTerminator`]
+/// error of `text`.
+///
+/// Candidates are quotes followed by some, but not enough, `#`s, and a quote
+/// ending the input. They are ranked by the number of `#`s, then by whether
+/// the following char could follow a literal, then by their position.
+/// Returns nothing if `text` is not an unterminated raw string, or if it has
+/// no candidates.
+pub fn raw_str_terminator_candidates(text: &str) -> Vec<TerminatorCandidate> {
+    if !matches!(validate_raw_str(text), Err(RawStrError::NoTerminator { .. })) {
+        return Vec::new();
+    }
+    // `r`, `br` or `cr`, as validated above.
+    let prefix_len = if text.starts_with('r') { 1 } else { 2 };
+    let n_hashes = text[prefix_len..].bytes().take_while(|&b| b == b'#').count();
+    let content_start = prefix_len + n_hashes + 1;
+    let mut candidates: Vec<_> = text[content_start..]
+        .match_indices('"')
+        .filter_map(|(idx, _)| {
+            let offset = content_start + idx + 1;
+            let found = text[offset..].bytes().take_while(|&b| b == b'#').count();
+            if found == 0 && offset < text.len() {
+                return None;
+            }
+            let next = text[offset + found..].chars().next().unwrap_or(EOF_CHAR);
+            Some((TerminatorCandidate { offset, found }, plausible_terminator_end(next)))
+        })
+        .collect();
+    candidates.sort_by_key(|&(candidate, plausible)| (Reverse(candidate.found), !plausible));
+    candidates.into_iter().map(|(candidate, _)| candidate).collect()
+}
+
+/// Whether `c`, following a candidate terminator of a raw string, could
+/// follow the end of a literal. In `"#a` or `"#"`, the `"#` is unlikely to
+/// be meant as a terminator.
+fn plausible_terminator_end(c: char) -> bool {
+    c != '"' && !is_id_continue(c)
+}
+
+/// Validates the text of a raw string, raw byte string or raw C string
+/// literal, like `r#"abc"#`, `br"abc"` or `cr"abc"`, and returns its parts.
+///
+/// Text following the closing delimiter, such as a suffix, is not inspected.
+/// Offsets in the error are relative to the start of `text`, like for tokens
+/// produced by the lexer. Text not starting with `r`, `br` or `cr` followed by
+/// `"` or `#` is reported as [`RawStrError::InvalidStarter`] with the first
+/// unexpected char.
+pub fn validate_raw_str(text: &str) -> Result<RawStrParts, RawStrError> {
+    let mut cursor = Cursor::new(text);
+    if matches!(cursor.first(), 'b' | 'c') {
+        cursor.bump();
+    }
+    if cursor.first() != 'r' {
+        return Err(RawStrError::InvalidStarter { bad_char: cursor.first() });
+    }
+    cursor.bump();
+    let prefix_len = cursor.len_consumed();
+    let (n_hashes, err, _) = cursor.raw_string_with_newline(prefix_len);
+    if let Some(err) = err {
+        return Err(err);
+    }
+    let len = cursor.len_consumed();
+    let content = prefix_len + n_hashes as usize + 1..len - n_hashes as usize - 1;
+    Ok(RawStrParts { n_hashes, content, len })
+}
+
+impl<S: Source> Cursor<'_, S> {
     /// Parses a token from the input string.
-    fn advance_token(&mut self) -> Token {
+    pub fn advance_token(&mut self) -> Token {
+        if let Some(continuation) = self.continuation.take() {
+            return self.continue_token(continuation);
+        }
+        self.reset_len_consumed();
         let first_char = self.bump().unwrap();
         let token_kind = match first_char {
+            // Comment started by a configured introducer.
+            c if self.at_custom_line_comment(c) => {
+                self.eat_while(|c| c != '\n');
+                LineComment { doc_style: None }
+            }
+
             // Slash, comment or block comment.
             '/' => match self.first() {
                 '/' => self.line_comment(),
@@ -313,14 +1178,14 @@ fn advance_token(&mut self) -> Token {
 
             // Raw identifier, raw string literal or identifier.
             'r' => match (self.first(), self.second()) {
-                ('#', c1) if is_id_start(c1) => self.raw_ident(),
+                ('#', c1) if self.is_id_start(c1) => self.raw_ident(),
                 ('#', _) | ('"', _) => {
-                    let (n_hashes, err) = self.raw_double_quoted_string(1);
+                    let (n_hashes, err, first_newline) = self.raw_string_with_newline(1);
                     let suffix_start = self.len_consumed();
                     if err.is_none() {
                         self.eat_literal_suffix();
                     }
-                    let kind = RawStr { n_hashes, err };
+                    let kind = RawStr { n_hashes, err, first_newline };
                     Literal { kind, suffix_start }
                 }
                 _ => self.ident_or_unknown_prefix(),
@@ -340,22 +1205,48 @@ fn advance_token(&mut self) -> Token {
                 }
                 ('"', _) => {
                     self.bump();
-                    let terminated = self.double_quoted_string();
+                    let (terminated, first_newline) = self.double_quoted_string_with_newline();
                     let suffix_start = self.len_consumed();
                     if terminated {
                         self.eat_literal_suffix();
                     }
-                    let kind = ByteStr { terminated };
+                    let kind = ByteStr { terminated, first_newline };
                     Literal { kind, suffix_start }
                 }
                 ('r', '"') | ('r', '#') => {
                     self.bump();
-                    let (n_hashes, err) = self.raw_double_quoted_string(2);
+                    let (n_hashes, err, first_newline) = self.raw_string_with_newline(2);
                     let suffix_start = self.len_consumed();
                     if err.is_none() {
                         self.eat_literal_suffix();
                     }
-                    let kind = RawByteStr { n_hashes, err };
+                    let kind = RawByteStr { n_hashes, err, first_newline };
+                    Literal { kind, suffix_start }
+                }
+                _ => self.ident_or_unknown_prefix(),
+            },
+
+            // C string literal, raw C string literal or identifier. Before Rust
+            // 2021, `c"` is an identifier followed by a string.
+            'c' if self.reserved_prefixes() => match (self.first(), self.second()) {
+                ('"', _) => {
+                    self.bump();
+                    let (terminated, first_newline) = self.double_quoted_string_with_newline();
+                    let suffix_start = self.len_consumed();
+                    if terminated {
+                        self.eat_literal_suffix();
+                    }
+                    let kind = CStr { terminated, first_newline };
+                    Literal { kind, suffix_start }
+                }
+                ('r', '"') | ('r', '#') => {
+                    self.bump();
+                    let (n_hashes, err, first_newline) = self.raw_string_with_newline(2);
+                    let suffix_start = self.len_consumed();
+                    if err.is_none() {
+                        self.eat_literal_suffix();
+                    }
+                    let kind = RawCStr { n_hashes, err, first_newline };
                     Literal { kind, suffix_start }
                 }
                 _ => self.ident_or_unknown_prefix(),
@@ -363,7 +1254,7 @@ fn advance_token(&mut self) -> Token {
 
             // Identifier (this should be checked after other variant that can
             // start as identifier).
-            c if is_id_start(c) => self.ident_or_unknown_prefix(),
+            c if self.is_id_start(c) => self.ident_or_unknown_prefix(),
 
             // Numeric literal.
             c @ '0'..='9' => {
@@ -384,6 +1275,9 @@ fn advance_token(&mut self) -> Token {
             '[' => OpenBracket,
             ']' => CloseBracket,
             '@' => At,
+            '#' if self.guarded_strings() && matches!(self.first(), '#' | '"') => {
+                self.guarded_str_or_pound()
+            }
             '#' => Pound,
             '~' => Tilde,
             '?' => Question,
@@ -406,12 +1300,12 @@ fn advance_token(&mut self) -> Token {
 
             // String literal.
             '"' => {
-                let terminated = self.double_quoted_string();
+                let (terminated, first_newline) = self.double_quoted_string_with_newline();
                 let suffix_start = self.len_consumed();
                 if terminated {
                     self.eat_literal_suffix();
                 }
-                let kind = Str { terminated };
+                let kind = Str { terminated, first_newline };
                 Literal { kind, suffix_start }
             }
             // Identifier starting with an emoji. Only lexed for graceful error recovery.
@@ -423,6 +1317,74 @@ fn advance_token(&mut self) -> Token {
         Token::new(token_kind, self.len_consumed())
     }
 
+    /// Returns the token `n` tokens ahead without consuming anything, so
+    /// `peek_token(0)` is the token [`Cursor::advance_token`] returns next.
+    /// Returns `None` if the input ends before that token.
+    ///
+    /// Every call lexes all tokens up to the peeked one, which is cheap for
+    /// the few tokens of lookahead hand-written parsers need. Like
+    /// [`Cursor::state`], this should only be called between tokens.
+    pub fn peek_token(&self, n: usize) -> Option<Token> {
+        let mut cursor = self.clone();
+        for _ in 0..n {
+            if cursor.is_eof() {
+                return None;
+            }
+            cursor.advance_token();
+        }
+        if cursor.is_eof() {
+            None
+        } else {
+            Some(cursor.advance_token())
+        }
+    }
+
+    /// Lexes the rest of a token which started before the input of the
+    /// cursor, see [`Continuation`].
+    ///
+    /// The returned token covers the rest of the literal or comment and has
+    /// the kind the whole token would have; offsets in it are relative to the
+    /// position the cursor was at.
+    pub fn continue_token(&mut self, continuation: Continuation) -> Token {
+        self.reset_len_consumed();
+        let token_kind = match continuation {
+            Continuation::Str | Continuation::ByteStr | Continuation::CStr => {
+                let (terminated, first_newline) = self.double_quoted_string_with_newline();
+                let suffix_start = self.len_consumed();
+                if terminated {
+                    self.eat_literal_suffix();
+                }
+                let kind = match continuation {
+                    Continuation::Str => Str { terminated, first_newline },
+                    Continuation::ByteStr => ByteStr { terminated, first_newline },
+                    _ => CStr { terminated, first_newline },
+                };
+                Literal { kind, suffix_start }
+            }
+            Continuation::RawStr { n_hashes }
+            | Continuation::RawByteStr { n_hashes }
+            | Continuation::RawCStr { n_hashes } => {
+                let mut first_newline = None;
+                let err = self.raw_string_contents(n_hashes as usize, 0, &mut first_newline);
+                let suffix_start = self.len_consumed();
+                if err.is_none() {
+                    self.eat_literal_suffix();
+                }
+                let kind = match continuation {
+                    Continuation::RawStr { .. } => RawStr { n_hashes, err, first_newline },
+                    Continuation::RawByteStr { .. } => RawByteStr { n_hashes, err, first_newline },
+                    _ => RawCStr { n_hashes, err, first_newline },
+                };
+                Literal { kind, suffix_start }
+            }
+            Continuation::BlockComment { doc_style, depth } => {
+                let depth = self.block_comment_contents(depth);
+                BlockComment { doc_style, terminated: depth == 0 }
+            }
+        };
+        Token::new(token_kind, self.len_consumed())
+    }
+
     fn line_comment(&mut self) -> TokenKind {
         debug_assert!(self.prev() == '/' && self.first() == '/');
         self.bump();
@@ -435,10 +1397,21 @@ fn line_comment(&mut self) -> TokenKind {
             _ => None,
         };
 
+        #[cfg(feature = "simd")]
+        self.skip_prefix(|bytes| swar::find_bytes(bytes, b"\n"));
         self.eat_while(|c| c != '\n');
         LineComment { doc_style }
     }
 
+    /// Checks if `first_char` and the input following it start one of the
+    /// [`LexerConfig::line_comment_starts`].
+    fn at_custom_line_comment(&self, first_char: char) -> bool {
+        self.config.line_comment_starts.iter().any(|start| {
+            let mut chars = start.chars();
+            chars.next() == Some(first_char) && self.rest_starts_with(chars.as_str())
+        })
+    }
+
     fn block_comment(&mut self) -> TokenKind {
         debug_assert!(self.prev() == '/' && self.first() == '*');
         self.bump();
@@ -452,10 +1425,17 @@ fn block_comment(&mut self) -> TokenKind {
             _ => None,
         };
 
-        let mut depth = 1usize;
+        let depth = self.block_comment_contents(1);
+        BlockComment { doc_style, terminated: depth == 0 }
+    }
+
+    /// Eats the rest of a block comment nested `depth` levels deep, and
+    /// returns the depth at which it stopped, which is 0 unless the end of
+    /// input was reached.
+    fn block_comment_contents(&mut self, mut depth: usize) -> usize {
         while let Some(c) = self.bump() {
             match c {
-                '/' if self.first() == '*' => {
+                '/' if self.first() == '*' && self.config.nested_block_comments => {
                     self.bump();
                     depth += 1;
                 }
@@ -472,18 +1452,57 @@ fn block_comment(&mut self) -> TokenKind {
                 _ => (),
             }
         }
-
-        BlockComment { doc_style, terminated: depth == 0 }
+        depth
     }
 
     fn whitespace(&mut self) -> TokenKind {
         debug_assert!(is_whitespace(self.prev()));
+        if self.config.classify_line_endings || self.config.count_whitespace {
+            let (line_endings, counts) = self.whitespace_details();
+            return Whitespace {
+                line_endings: Some(line_endings).filter(|_| self.config.classify_line_endings),
+                counts: Some(counts).filter(|_| self.config.count_whitespace),
+            };
+        }
+        #[cfg(feature = "simd")]
+        self.skip_prefix(swar::whitespace_len);
         self.eat_while(is_whitespace);
-        Whitespace
+        Whitespace { line_endings: None, counts: None }
+    }
+
+    fn whitespace_details(&mut self) -> (LineEndingKinds, WhitespaceCounts) {
+        let mut line_endings = LineEndingKinds::default();
+        let mut counts = WhitespaceCounts::default();
+        let mut c = self.prev();
+        loop {
+            match c {
+                ' ' => counts.spaces += 1,
+                '\t' => counts.tabs += 1,
+                '\n' => {
+                    line_endings.lf = true;
+                    counts.newlines += 1;
+                }
+                '\r' if self.first() == '\n' => {
+                    self.bump();
+                    line_endings.crlf = true;
+                    counts.newlines += 1;
+                }
+                '\r' => {
+                    line_endings.cr = true;
+                    counts.newlines += 1;
+                }
+                _ => {}
+            }
+            if !is_whitespace(self.first()) {
+                return (line_endings, counts);
+            }
+            c = self.first();
+            self.bump();
+        }
     }
 
     fn raw_ident(&mut self) -> TokenKind {
-        debug_assert!(self.prev() == 'r' && self.first() == '#' && is_id_start(self.second()));
+        debug_assert!(self.prev() == 'r' && self.first() == '#' && self.is_id_start(self.second()));
         // Eat "#" symbol.
         self.bump();
         // Eat the identifier part of RawIdent.
@@ -492,13 +1511,19 @@ fn raw_ident(&mut self) -> TokenKind {
     }
 
     fn ident_or_unknown_prefix(&mut self) -> TokenKind {
-        debug_assert!(is_id_start(self.prev()));
+        debug_assert!(self.is_id_start(self.prev()));
         // Start is already eaten, eat the rest of identifier.
-        self.eat_while(is_id_continue);
+        self.eat_id_continue();
         // Known prefixes must have been handled earlier. So if
-        // we see a prefix here, it is definitely an unknown prefix.
+        // we see a prefix here, it is definitely an unknown prefix,
+        // unless it was registered as a custom one.
         match self.first() {
-            '#' | '"' | '\'' => UnknownPrefix,
+            '"' | '\'' => match self.custom_literal_prefix() {
+                Some(prefix) => self.prefixed_literal(prefix),
+                None if self.reserved_prefixes() => self.unknown_prefix(),
+                None => Ident,
+            },
+            '#' if self.reserved_prefixes() => self.unknown_prefix(),
             c if !c.is_ascii() && unic_emoji_char::is_emoji(c) => {
                 self.fake_ident_or_unknown_prefix()
             }
@@ -506,6 +1531,30 @@ fn ident_or_unknown_prefix(&mut self) -> TokenKind {
         }
     }
 
+    /// Returns the index of the eaten identifier in the custom literal prefixes.
+    fn custom_literal_prefix(&self) -> Option<usize> {
+        self.config.literal_prefixes.iter().position(|prefix| self.consumed_eq(prefix))
+    }
+
+    fn prefixed_literal(&mut self, prefix: usize) -> TokenKind {
+        let kind = match self.bump() {
+            Some('"') => {
+                let (terminated, first_newline) = self.double_quoted_string_with_newline();
+                Str { terminated, first_newline }
+            }
+            Some('\'') => {
+                let terminated = self.single_quoted_string();
+                Char { terminated }
+            }
+            _ => unreachable!("custom literal prefix not followed by a quote"),
+        };
+        let suffix_start = self.len_consumed();
+        if !matches!(kind, Str { terminated: false, .. } | Char { terminated: false }) {
+            self.eat_literal_suffix();
+        }
+        PrefixedLiteral { prefix, kind, suffix_start }
+    }
+
     fn fake_ident_or_unknown_prefix(&mut self) -> TokenKind {
         // Start is already eaten, eat the rest of identifier.
         self.eat_while(|c| {
@@ -516,30 +1565,87 @@ fn fake_ident_or_unknown_prefix(&mut self) -> TokenKind {
         // Known prefixes must have been handled earlier. So if
         // we see a prefix here, it is definitely an unknown prefix.
         match self.first() {
-            '#' | '"' | '\'' => UnknownPrefix,
+            '#' | '"' | '\'' if self.reserved_prefixes() => self.unknown_prefix(),
             _ => InvalidIdent,
         }
     }
 
+    /// Returns the kind of an unknown prefix which is followed by the next
+    /// char, which must be `"`, `'` or `#`.
+    fn unknown_prefix(&self) -> TokenKind {
+        let followed_by = match self.first() {
+            '"' => PrefixFollower::Str,
+            '\'' => PrefixFollower::Char,
+            _ => PrefixFollower::Pound,
+        };
+        UnknownPrefix { followed_by }
+    }
+
+    /// Whether identifiers directly followed by `#`, `"` or `'` are reserved
+    /// as prefixes, which they are since Rust 2021.
+    fn reserved_prefixes(&self) -> bool {
+        self.config.edition >= Edition::Edition2021
+    }
+
+    /// Whether `#` directly followed by a string is reserved for guarded
+    /// strings, which it is since Rust 2024.
+    fn guarded_strings(&self) -> bool {
+        self.config.edition >= Edition::Edition2024
+    }
+
+    /// Lexes a guarded string like `##"foo"##`, or a single `#` if the `#`s
+    /// aren't followed by a string.
+    ///
+    /// The first `#` must be the last bumped char.
+    fn guarded_str_or_pound(&mut self) -> TokenKind {
+        debug_assert!(self.prev() == '#');
+        let checkpoint = self.checkpoint();
+        let mut n_hashes: u32 = 1;
+        while self.first() == '#' {
+            self.bump();
+            n_hashes = n_hashes.saturating_add(1);
+        }
+        if self.first() != '"' {
+            self.rewind(checkpoint);
+            return Pound;
+        }
+        self.bump();
+        let terminated = self.double_quoted_string();
+        if terminated {
+            // Extra `#` after the closing ones are lexed as their own tokens.
+            let mut n_closing = 0;
+            while self.first() == '#' && n_closing < n_hashes {
+                self.bump();
+                n_closing += 1;
+            }
+            self.eat_literal_suffix();
+        }
+        GuardedStr { n_hashes, terminated }
+    }
+
     fn number(&mut self, first_digit: char) -> LiteralKind {
         debug_assert!('0' <= self.prev() && self.prev() <= '9');
         let mut base = Base::Decimal;
+        let mut underscore_after_prefix = false;
         if first_digit == '0' {
             // Attempt to parse encoding base.
             let has_digits = match self.first() {
                 'b' => {
                     base = Base::Binary;
                     self.bump();
+                    underscore_after_prefix = self.first() == '_';
                     self.eat_decimal_digits()
                 }
                 'o' => {
                     base = Base::Octal;
                     self.bump();
+                    underscore_after_prefix = self.first() == '_';
                     self.eat_decimal_digits()
                 }
                 'x' => {
                     base = Base::Hexadecimal;
                     self.bump();
+                    underscore_after_prefix = self.first() == '_';
                     self.eat_hexadecimal_digits()
                 }
                 // Not a base prefix.
@@ -548,51 +1654,106 @@ fn number(&mut self, first_digit: char) -> LiteralKind {
                     true
                 }
                 // Just a 0.
-                _ => return Int { base, empty_int: false },
+                _ => return Int { base, empty_int: None, underscore_after_prefix },
             };
             // Base prefix was provided, but there were no digits
             // after it, e.g. "0x".
             if !has_digits {
-                return Int { base, empty_int: true };
+                let empty_int = MissingDigits { start: 0, expected_at: self.len_consumed() as u32 };
+                return Int { base, empty_int: Some(empty_int), underscore_after_prefix };
             }
         } else {
             // No base prefix, parse number in the usual way.
             self.eat_decimal_digits();
         };
 
+        if base == Base::Hexadecimal {
+            if let Some(hex_float) = self.hex_float() {
+                return hex_float;
+            }
+        }
+
         match self.first() {
             // Don't be greedy if this is actually an
             // integer literal followed by field/method access or a range pattern
             // (`0..2` and `12.foo()`)
-            '.' if self.second() != '.' && !is_id_start(self.second()) => {
+            '.' if self.second() != '.' && !self.is_id_start(self.second()) => {
                 // might have stuff after the ., and if it does, it needs to start
                 // with a number
                 self.bump();
-                let mut empty_exponent = false;
+                let mut empty_exponent = None;
                 if self.first().is_digit(10) {
                     self.eat_decimal_digits();
                     match self.first() {
-                        'e' | 'E' => {
-                            self.bump();
-                            empty_exponent = !self.eat_float_exponent();
-                        }
+                        'e' | 'E' => empty_exponent = self.exponent(),
                         _ => (),
                     }
                 }
-                Float { base, empty_exponent }
+                Float { base, empty_exponent, hex_exponent: None }
             }
-            'e' | 'E' => {
+            'e' | 'E' => Float { base, empty_exponent: self.exponent(), hex_exponent: None },
+            _ => Int { base, empty_int: None, underscore_after_prefix },
+        }
+    }
+
+    /// Eats the rest of a hexadecimal float like `0x1.8p3` after its integer
+    /// digits. Without this, it would lex as an integer followed by a field
+    /// access like `0x1.` `ap3`. Eats nothing and returns `None` unless a
+    /// `.`, hexadecimal digits and a binary exponent with digits follow, so
+    /// `0x1p3` stays an integer with a suffix.
+    fn hex_float(&mut self) -> Option<LiteralKind> {
+        if self.first() != '.' || self.second() == '.' {
+            return None;
+        }
+        let checkpoint = self.checkpoint();
+        self.bump();
+        if self.eat_hexadecimal_digits() && matches!(self.first(), 'p' | 'P') {
+            let hex_exponent = self.len_consumed() as u32;
+            self.bump();
+            if matches!(self.first(), '+' | '-') {
                 self.bump();
-                let empty_exponent = !self.eat_float_exponent();
-                Float { base, empty_exponent }
             }
-            _ => Int { base, empty_int: false },
+            if self.first().is_ascii_digit() {
+                self.eat_decimal_digits();
+                return Some(Float {
+                    base: Base::Hexadecimal,
+                    empty_exponent: None,
+                    hex_exponent: Some(hex_exponent),
+                });
+            }
+        }
+        self.rewind(checkpoint);
+        None
+    }
+
+    /// Eats the exponent of a float starting with `e` or `E`, returning where
+    /// its digits are missing, if they are.
+    fn exponent(&mut self) -> Option<MissingDigits> {
+        let start = self.len_consumed() as u32;
+        self.bump();
+        if self.eat_float_exponent() {
+            None
+        } else {
+            Some(MissingDigits { start, expected_at: self.len_consumed() as u32 })
         }
     }
 
     fn lifetime_or_char(&mut self) -> TokenKind {
         debug_assert!(self.prev() == '\'');
 
+        if self.first() == 'r'
+            && self.second() == '#'
+            && self.is_id_start(self.third())
+            && self.reserved_prefixes()
+        {
+            // Eat `r`, `#` and the start of the name.
+            self.bump();
+            self.bump();
+            self.bump();
+            self.eat_id_continue();
+            return RawLifetime;
+        }
+
         let can_be_a_lifetime = if self.second() == '\'' {
             // It's surely not a lifetime.
             false
@@ -600,7 +1761,7 @@ fn lifetime_or_char(&mut self) -> TokenKind {
             // If the first symbol is valid for identifier, it can be a lifetime.
             // Also check if it's a number for a better error reporting (so '0 will
             // be reported as invalid lifetime and not as unterminated char literal).
-            is_id_start(self.first()) || self.first().is_digit(10)
+            self.is_id_start(self.first()) || self.first().is_digit(10)
         };
 
         if !can_be_a_lifetime {
@@ -622,7 +1783,7 @@ fn lifetime_or_char(&mut self) -> TokenKind {
         // First symbol can be a number (which isn't a valid identifier start),
         // so skip it without any checks.
         self.bump();
-        self.eat_while(is_id_continue);
+        self.eat_id_continue();
 
         // Check if after skipping literal contents we've met a closing
         // single quote (which means that user attempted to create a
@@ -636,7 +1797,12 @@ fn lifetime_or_char(&mut self) -> TokenKind {
         }
     }
 
-    fn single_quoted_string(&mut self) -> bool {
+    /// Eats a single-quoted char literal after its opening `'`, which must be
+    /// the last bumped char. Returns true if the literal is terminated.
+    ///
+    /// On unterminated literals, stops before a `/` or a newline to not
+    /// swallow a following comment or line.
+    pub fn single_quoted_string(&mut self) -> bool {
         debug_assert!(self.prev() == '\'');
         // Check if it's a one-symbol literal.
         if self.second() == '\'' && self.first() != '\\' {
@@ -679,12 +1845,28 @@ fn single_quoted_string(&mut self) -> bool {
 
     /// Eats double-quoted string and returns true
     /// if string is terminated.
-    fn double_quoted_string(&mut self) -> bool {
+    ///
+    /// The opening `"` must be the last bumped char.
+    pub fn double_quoted_string(&mut self) -> bool {
         debug_assert!(self.prev() == '"');
-        while let Some(c) = self.bump() {
+        self.double_quoted_string_with_newline().0
+    }
+
+    /// Like [`Cursor::double_quoted_string`], but also returns the offset of
+    /// the first newline in the literal from the start of the token.
+    fn double_quoted_string_with_newline(&mut self) -> (bool, Option<u32>) {
+        let mut first_newline = None;
+        while let Some(c) = {
+            #[cfg(feature = "simd")]
+            self.skip_prefix(|bytes| swar::find_bytes(bytes, b"\"\n\\"));
+            self.bump()
+        } {
             match c {
                 '"' => {
-                    return true;
+                    return (true, first_newline);
+                }
+                '\n' if first_newline.is_none() => {
+                    first_newline = Some(self.len_consumed() as u32 - 1);
                 }
                 '\\' if self.first() == '\\' || self.first() == '"' => {
                     // Bump again to skip escaped character.
@@ -694,27 +1876,48 @@ fn double_quoted_string(&mut self) -> bool {
             }
         }
         // End of file reached.
-        false
+        (false, first_newline)
     }
 
     /// Eats the double-quoted string and returns `n_hashes` and an error if encountered.
-    fn raw_double_quoted_string(&mut self, prefix_len: usize) -> (u16, Option<RawStrError>) {
+    ///
+    /// The `r` must be the last bumped char. `prefix_len` is the length of
+    /// the literal prefix before the `r`, e.g. 1 for `br`, which is added to
+    /// `possible_terminator_offset` of [`RawStrError::NoTerminator`].
+    pub fn raw_double_quoted_string(&mut self, prefix_len: usize) -> (u32, Option<RawStrError>) {
+        let (n_hashes, err, _) = self.raw_string_with_newline(prefix_len);
+        (n_hashes, err)
+    }
+
+    /// Like [`Cursor::raw_double_quoted_string`], but also returns the offset
+    /// of the first newline in the literal from the start of the token.
+    fn raw_string_with_newline(
+        &mut self,
+        prefix_len: usize,
+    ) -> (u32, Option<RawStrError>, Option<u32>) {
         // Wrap the actual function to handle the error with too many hashes.
         // This way, it eats the whole raw string.
-        let (n_hashes, err) = self.raw_string_unvalidated(prefix_len);
-        // Only up to 65535 `#`s are allowed in raw strings
-        match u16::try_from(n_hashes) {
-            Ok(num) => (num, err),
+        let mut first_newline = None;
+        let (n_hashes, err) = self.raw_string_unvalidated(prefix_len, &mut first_newline);
+        // Only up to `max_raw_str_hashes` `#`s are allowed in raw strings
+        let max = self.config.max_raw_str_hashes;
+        // More than `u32::MAX` `#`s are reported as `u32::MAX`.
+        let n_hashes = u32::try_from(n_hashes).unwrap_or(u32::MAX);
+        if n_hashes <= max {
+            (n_hashes, err, first_newline)
+        } else {
             // We lie about the number of hashes here :P
-            Err(_) => (0, Some(RawStrError::TooManyDelimiters { found: n_hashes })),
+            (0, Some(RawStrError::TooManyDelimiters { found: n_hashes, max }), first_newline)
         }
     }
 
-    fn raw_string_unvalidated(&mut self, prefix_len: usize) -> (usize, Option<RawStrError>) {
+    fn raw_string_unvalidated(
+        &mut self,
+        prefix_len: usize,
+        first_newline: &mut Option<u32>,
+    ) -> (usize, Option<RawStrError>) {
         debug_assert!(self.prev() == 'r');
         let start_pos = self.len_consumed();
-        let mut possible_terminator_offset = None;
-        let mut max_hashes = 0;
 
         // Count opening '#' symbols.
         let mut eaten = 0;
@@ -733,20 +1936,45 @@ fn raw_string_unvalidated(&mut self, prefix_len: usize) -> (usize, Option<RawStr
             }
         }
 
+        let err = self.raw_string_contents(n_start_hashes, start_pos - prefix_len, first_newline);
+        (n_start_hashes, err)
+    }
+
+    /// Eats the contents and the terminator of a raw string after its opening
+    /// quote. Possible terminator offsets are reported relative to
+    /// `offset_base`.
+    fn raw_string_contents(
+        &mut self,
+        n_start_hashes: usize,
+        offset_base: usize,
+        first_newline: &mut Option<u32>,
+    ) -> Option<RawStrError> {
+        let mut possible_terminator_offset = None;
+        let mut max_hashes = 0;
+        let mut plausible = false;
+
         // Skip the string contents and on each '#' character met, check if this is
         // a raw string termination.
         loop {
-            self.eat_while(|c| c != '"');
+            #[cfg(feature = "simd")]
+            self.skip_prefix(|bytes| swar::find_bytes(bytes, b"\"\n"));
+            self.eat_while(|c| c != '"' && c != '\n');
+
+            if self.first() == '\n' {
+                if first_newline.is_none() {
+                    *first_newline = Some(self.len_consumed() as u32);
+                }
+                self.bump();
+                continue;
+            }
 
             if self.is_eof() {
-                return (
-                    n_start_hashes,
-                    Some(RawStrError::NoTerminator {
-                        expected: n_start_hashes,
-                        found: max_hashes,
-                        possible_terminator_offset,
-                    }),
-                );
+                return Some(RawStrError::NoTerminator {
+                    expected: n_start_hashes as u32,
+                    found: max_hashes as u32,
+                    possible_terminator_offset: possible_terminator_offset
+                        .map(|offset| offset as u32),
+                });
             }
 
             // Eat closing double quote.
@@ -764,18 +1992,25 @@ fn raw_string_unvalidated(&mut self, prefix_len: usize) -> (usize, Option<RawStr
             }
 
             if n_end_hashes == n_start_hashes {
-                return (n_start_hashes, None);
-            } else if n_end_hashes > max_hashes {
-                // Keep track of possible terminators to give a hint about
-                // where there might be a missing terminator
-                possible_terminator_offset =
-                    Some(self.len_consumed() - start_pos - n_end_hashes + prefix_len);
+                return None;
+            }
+            // Keep track of the best possible terminator to give a hint
+            // about where there might be a missing terminator, ranked like
+            // in `raw_str_terminator_candidates`.
+            let end_plausible = plausible_terminator_end(self.first());
+            if (n_end_hashes > 0 || self.is_eof())
+                && (n_end_hashes, end_plausible) > (max_hashes, plausible)
+            {
+                possible_terminator_offset = Some(self.len_consumed() - n_end_hashes - offset_base);
                 max_hashes = n_end_hashes;
+                plausible = end_plausible;
             }
         }
     }
 
-    fn eat_decimal_digits(&mut self) -> bool {
+    /// Eats decimal digits and underscores. Returns true if at least one
+    /// digit was met.
+    pub fn eat_decimal_digits(&mut self) -> bool {
         let mut has_digits = false;
         loop {
             match self.first() {
@@ -792,7 +2027,9 @@ fn eat_decimal_digits(&mut self) -> bool {
         has_digits
     }
 
-    fn eat_hexadecimal_digits(&mut self) -> bool {
+    /// Eats hexadecimal digits and underscores. Returns true if at least one
+    /// digit was met.
+    pub fn eat_hexadecimal_digits(&mut self) -> bool {
         let mut has_digits = false;
         loop {
             match self.first() {
@@ -811,7 +2048,9 @@ fn eat_hexadecimal_digits(&mut self) -> bool {
 
     /// Eats the float exponent. Returns true if at least one digit was met,
     /// and returns false otherwise.
-    fn eat_float_exponent(&mut self) -> bool {
+    ///
+    /// The `e` or `E` must be the last bumped char.
+    pub fn eat_float_exponent(&mut self) -> bool {
         debug_assert!(self.prev() == 'e' || self.prev() == 'E');
         if self.first() == '-' || self.first() == '+' {
             self.bump();
@@ -819,18 +2058,40 @@ fn eat_float_exponent(&mut self) -> bool {
         self.eat_decimal_digits()
     }
 
-    // Eats the suffix of the literal, e.g. "_u8".
-    fn eat_literal_suffix(&mut self) {
+    /// Eats the suffix of the literal, e.g. "_u8".
+    pub fn eat_literal_suffix(&mut self) {
         self.eat_identifier();
     }
 
-    // Eats the identifier.
-    fn eat_identifier(&mut self) {
-        if !is_id_start(self.first()) {
+    /// True if `c` can start an identifier according to the
+    /// [`IdentPolicy`] of the configuration.
+    fn is_id_start(&self, c: char) -> bool {
+        (self.config.ident_policy.is_start)(c)
+    }
+
+    /// Eats the chars which can continue an identifier.
+    fn eat_id_continue(&mut self) {
+        let is_continue = self.config.ident_policy.is_continue;
+        // The fast path only applies to policies known to accept all ASCII
+        // letters, digits and `_`. Comparing function addresses may give false
+        // negatives, which only cost the fast path.
+        #[cfg(feature = "simd")]
+        if [IdentPolicy::RUST, IdentPolicy::ASCII]
+            .iter()
+            .any(|policy| policy.is_continue as usize == is_continue as usize)
+        {
+            self.skip_prefix(swar::ascii_id_continue_len);
+        }
+        self.eat_while(is_continue);
+    }
+
+    /// Eats the identifier, if the next char can start one.
+    pub fn eat_identifier(&mut self) {
+        if !self.is_id_start(self.first()) {
             return;
         }
         self.bump();
 
-        self.eat_while(is_id_continue);
+        self.eat_id_continue();
     }
 }
@@ -0,0 +1,78 @@
+//! Extraction of license information from the head of a source file.
+//!
+//! Only the comments before the first significant token are inspected, so
+//! license-like text in string literals or later comments is never picked
+//! up.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{strip_prelude, tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+const SPDX_TAG: &str = "SPDX-License-Identifier:";
+
+/// License information found in the leading comments, see [`license_header`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LicenseHeader {
+    /// Byte ranges of the license expressions following each
+    /// `SPDX-License-Identifier:` tag, e.g. `MIT OR Apache-2.0`.
+    pub spdx_expressions: Vec<Range<usize>>,
+    /// Byte range of the first block of comments mentioning a license or a
+    /// copyright, from the start of its first comment to the end of its last.
+    /// Comments separated by a blank line belong to different blocks.
+    pub text: Option<Range<usize>>,
+}
+
+/// Inspects the comments before the first significant token of `src` for
+/// SPDX tags and a license header.
+pub fn license_header(src: &str) -> LicenseHeader {
+    let mut res = LicenseHeader::default();
+    let start = strip_prelude(src).start;
+    let mut blocks: Vec<Range<usize>> = Vec::new();
+    let mut block_ended = true;
+    for (range, kind) in tokenize_with_offsets(&src[start..]) {
+        let range = start + range.start..start + range.end;
+        match kind {
+            TokenKind::Whitespace { .. } => {
+                if src[range].matches('\n').count() > 1 {
+                    block_ended = true;
+                }
+            }
+            TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } => {
+                spdx_expressions(src, range.clone(), &mut res.spdx_expressions);
+                match blocks.last_mut() {
+                    Some(block) if !block_ended => block.end = range.end,
+                    _ => blocks.push(range),
+                }
+                block_ended = false;
+            }
+            _ => break,
+        }
+    }
+    res.text = blocks.into_iter().find(|block| mentions_license(&src[block.clone()]));
+    res
+}
+
+/// Collects the expressions of the SPDX tags in the comment at `comment`.
+fn spdx_expressions(src: &str, comment: Range<usize>, out: &mut Vec<Range<usize>>) {
+    let text = &src[comment.clone()];
+    for (tag_start, _) in text.match_indices(SPDX_TAG) {
+        let start = tag_start + SPDX_TAG.len();
+        let line = text[start..].lines().next().unwrap_or_default();
+        let line = line.trim_end();
+        let line = line.strip_suffix("*/").unwrap_or(line);
+        let expression = line.trim();
+        if !expression.is_empty() {
+            let offset = comment.start + start + (line.len() - line.trim_start().len());
+            out.push(offset..offset + expression.len());
+        }
+    }
+}
+
+fn mentions_license(text: &str) -> bool {
+    let text = text.to_ascii_lowercase();
+    ["license", "licence", "copyright", "spdx-"].iter().any(|word| text.contains(word))
+}
@@ -0,0 +1,44 @@
+use super::*;
+
+fn check(src: &str, expressions: &[&str], text: Option<&str>) {
+    let header = license_header(src);
+    let actual: Vec<_> = header.spdx_expressions.iter().map(|range| &src[range.clone()]).collect();
+    assert_eq!(actual, expressions);
+    assert_eq!(header.text.map(|range| &src[range]), text);
+}
+
+#[test]
+fn test_spdx() {
+    check(
+        "// SPDX-License-Identifier: MIT OR Apache-2.0\nfn main() {}",
+        &["MIT OR Apache-2.0"],
+        Some("// SPDX-License-Identifier: MIT OR Apache-2.0"),
+    );
+    check(
+        "/* SPDX-License-Identifier: MIT */",
+        &["MIT"],
+        Some("/* SPDX-License-Identifier: MIT */"),
+    );
+    check(
+        "#!/usr/bin/env run\n// SPDX-License-Identifier: MIT\n",
+        &["MIT"],
+        Some("// SPDX-License-Identifier: MIT"),
+    );
+    check("// SPDX-License-Identifier:\n", &[], Some("// SPDX-License-Identifier:"));
+}
+
+#[test]
+fn test_license_text() {
+    check(
+        "// Some tool config\n\n// Copyright 2020 Foo\n// Licensed under MIT.\n\nuse foo;",
+        &[],
+        Some("// Copyright 2020 Foo\n// Licensed under MIT."),
+    );
+    check("//! A crate.\nfn main() {}", &[], None);
+}
+
+#[test]
+fn test_only_leading_trivia() {
+    check("fn main() {}\n// SPDX-License-Identifier: MIT\n", &[], None);
+    check("const L: &str = \"SPDX-License-Identifier: MIT\";", &[], None);
+}
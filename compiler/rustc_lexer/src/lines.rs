@@ -0,0 +1,334 @@
+//! Line-oriented analyses over the token stream.
+//!
+//! Text-level tools (formatters, pre-commit hooks, normalizers) usually work
+//! line by line, but plain text scanning cannot tell whether a piece of text is
+//! inside a string literal. The helpers in this module use the lexer to skip
+//! literal contents, so their results are safe to act upon.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{
+    tokenize, tokenize_with_offsets, LineEndingKinds, LiteralKind, RawStrError, Token, TokenKind,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// Returns the byte ranges of whitespace immediately preceding a newline
+/// (or the end of input), excluding whitespace inside string literals.
+///
+/// The `\r` of a `\r\n` line ending is not considered trailing whitespace.
+/// Removing the returned ranges never changes the value of a literal.
+pub fn trailing_whitespace(src: &str) -> Vec<Range<usize>> {
+    let mut res = Vec::new();
+    for segment in code_segments(src) {
+        let text = &src[segment.clone()];
+        let mut line_start = 0;
+        for line_end in text.match_indices('\n').map(|(i, _)| i).chain(Some(text.len())) {
+            let line = &text[line_start..line_end];
+            let line = line.strip_suffix('\r').unwrap_or(line);
+            let trimmed = line.trim_end_matches(is_horizontal_whitespace);
+            if trimmed.len() < line.len() {
+                let start = segment.start + line_start + trimmed.len();
+                res.push(start..start + line.len() - trimmed.len());
+            }
+            line_start = line_end + 1;
+        }
+    }
+    // Whitespace before the end of a segment is only trailing if the segment
+    // is followed by a newline or the end of input, not by a literal.
+    res.retain(|range| match src[range.end..].chars().next() {
+        None | Some('\n') | Some('\r') => true,
+        Some(_) => false,
+    });
+    res
+}
+
+/// Describes how the input ends, see [`final_newline`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FinalNewline {
+    /// The input is empty.
+    Empty,
+    /// The input ends with a newline.
+    Present,
+    /// The input does not end with a newline, which should be inserted at
+    /// `insert_at` (the length of the input).
+    Missing { insert_at: usize },
+    /// The input ends inside an unterminated literal or block comment that
+    /// starts at `token_start`. Appending a newline would change the contents
+    /// of that token rather than end the line, so the file should be reported
+    /// instead of fixed.
+    Unterminated { token_start: usize },
+}
+
+/// Checks whether the input ends with a newline.
+pub fn final_newline(src: &str) -> FinalNewline {
+    if src.is_empty() {
+        return FinalNewline::Empty;
+    }
+    match tokenize_with_offsets(src).last().map(|(range, kind)| (range.start, kind)) {
+        Some((token_start, kind)) if !is_terminated(kind) => {
+            FinalNewline::Unterminated { token_start }
+        }
+        _ if src.ends_with('\n') => FinalNewline::Present,
+        _ => FinalNewline::Missing { insert_at: src.len() },
+    }
+}
+
+/// Returns false for tokens that were cut off by the end of input.
+pub(crate) fn is_terminated(kind: TokenKind) -> bool {
+    match kind {
+        TokenKind::BlockComment { terminated, .. } | TokenKind::GuardedStr { terminated, .. } => {
+            terminated
+        }
+        TokenKind::Literal { kind, .. } => match kind {
+            LiteralKind::Char { terminated }
+            | LiteralKind::Byte { terminated }
+            | LiteralKind::Str { terminated, .. }
+            | LiteralKind::ByteStr { terminated, .. }
+            | LiteralKind::CStr { terminated, .. } => terminated,
+            LiteralKind::RawStr { err, .. }
+            | LiteralKind::RawByteStr { err, .. }
+            | LiteralKind::RawCStr { err, .. } => {
+                !matches!(err, Some(RawStrError::NoTerminator { .. }))
+            }
+            LiteralKind::Int { .. } | LiteralKind::Float { .. } => true,
+        },
+        _ => true,
+    }
+}
+
+/// Line endings found in the input, see [`line_endings`].
+///
+/// Each vector holds the byte offsets at which the line endings of the
+/// respective style start.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineEndings {
+    /// `\n` line endings.
+    pub lf: Vec<usize>,
+    /// `\r\n` line endings.
+    pub crlf: Vec<usize>,
+    /// `\r` characters which are not followed by `\n`.
+    pub cr: Vec<usize>,
+}
+
+impl LineEndings {
+    /// Returns true if more than one line-ending style is used.
+    pub fn is_mixed(&self) -> bool {
+        self.kinds().is_mixed()
+    }
+
+    /// Returns which line-ending styles are used.
+    pub fn kinds(&self) -> LineEndingKinds {
+        LineEndingKinds {
+            lf: !self.lf.is_empty(),
+            crlf: !self.crlf.is_empty(),
+            cr: !self.cr.is_empty(),
+        }
+    }
+}
+
+/// Collects the line endings of the input, excluding the ones inside
+/// literals.
+///
+/// Line endings inside (raw) string literals are part of the literal value,
+/// so normalizing them would change the program.
+pub fn line_endings(src: &str) -> LineEndings {
+    let mut res = LineEndings::default();
+    for segment in code_segments(src) {
+        let bytes = &src.as_bytes()[segment.clone()];
+        for (i, &byte) in bytes.iter().enumerate() {
+            let pos = segment.start + i;
+            match byte {
+                b'\n' if i > 0 && bytes[i - 1] == b'\r' => {}
+                b'\n' => res.lf.push(pos),
+                b'\r' if bytes.get(i + 1) == Some(&b'\n') => res.crlf.push(pos),
+                b'\r' => res.cr.push(pos),
+                _ => {}
+            }
+        }
+    }
+    res
+}
+
+/// Whitespace that does not end a line.
+fn is_horizontal_whitespace(c: char) -> bool {
+    crate::is_whitespace(c) && c != '\n' && c != '\r'
+}
+
+/// Splits the input into the byte ranges that lie outside of literal tokens.
+fn code_segments(src: &str) -> Vec<Range<usize>> {
+    let mut segments = Vec::new();
+    let mut segment_start = 0;
+    for (range, kind) in tokenize_with_offsets(src) {
+        if let TokenKind::Literal { .. } = kind {
+            if segment_start < range.start {
+                segments.push(segment_start..range.start);
+            }
+            segment_start = range.end;
+        }
+    }
+    if segment_start < src.len() {
+        segments.push(segment_start..src.len());
+    }
+    segments
+}
+
+/// A byte order mark found after the start of the input, see [`stray_boms`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct StrayBom {
+    /// Byte offset of the U+FEFF character.
+    pub offset: usize,
+    pub kind: StrayBomKind,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum StrayBomKind {
+    /// The BOM starts a line, which is what concatenating files that each
+    /// start with a BOM produces. It can be removed.
+    ProbableConcatenation,
+    /// The BOM is in the middle of a line.
+    Other,
+}
+
+/// Finds byte order marks that are not at the start of the input.
+///
+/// A leading BOM is stripped before lexing, but one anywhere else lexes as
+/// an unknown token. BOMs inside literals and comments are not reported, as
+/// they are part of the token's contents.
+pub fn stray_boms(src: &str) -> Vec<StrayBom> {
+    let mut res = Vec::new();
+    for (range, kind) in tokenize_with_offsets(src) {
+        let pos = range.start;
+        if kind == TokenKind::Unknown && pos > 0 && src[pos..].starts_with('\u{FEFF}') {
+            let kind = if src[..pos].ends_with('\n') {
+                StrayBomKind::ProbableConcatenation
+            } else {
+                StrayBomKind::Other
+            };
+            res.push(StrayBom { offset: pos, kind });
+        }
+    }
+    res
+}
+
+/// Returns the byte ranges of lines longer than `max_len` bytes, not
+/// counting the line ending.
+///
+/// Minified or generated sources can have lines megabytes long, which are
+/// impractical to render in diagnostics. Unlike the other helpers in this
+/// module this counts every line, including lines inside literals.
+pub fn long_lines(src: &str, max_len: usize) -> Vec<Range<usize>> {
+    let mut res = Vec::new();
+    let mut line_start = 0;
+    for line in src.split_inclusive('\n') {
+        let content = line.strip_suffix('\n').unwrap_or(line);
+        let content = content.strip_suffix('\r').unwrap_or(content);
+        if content.len() > max_len {
+            res.push(line_start..line_start + content.len());
+        }
+        line_start += line.len();
+    }
+    res
+}
+
+/// A position in the input as a zero-based line and the byte offset in that
+/// line, see [`LineIndex`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct LineCol {
+    pub line: usize,
+    pub col: usize,
+}
+
+/// The offsets at which the lines of the input start, for converting between
+/// byte offsets and [`LineCol`]s.
+///
+/// Lines end at `\n`, including the ones inside literals and comments, so a
+/// `\r` before it is part of the line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LineIndex {
+    /// Offsets of the first byte of every line, starting with 0.
+    line_starts: Vec<usize>,
+    len: usize,
+}
+
+impl LineIndex {
+    pub fn new(src: &str) -> LineIndex {
+        let mut line_starts = vec![0];
+        line_starts.extend(src.match_indices('\n').map(|(i, _)| i + 1));
+        LineIndex { line_starts, len: src.len() }
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    /// Converts a byte offset, which may be the length of the input, into a
+    /// line and column. Returns `None` if the offset is out of bounds.
+    pub fn line_col(&self, offset: usize) -> Option<LineCol> {
+        if offset > self.len {
+            return None;
+        }
+        let line = self.line_starts.partition_point(|&start| start <= offset) - 1;
+        Some(LineCol { line, col: offset - self.line_starts[line] })
+    }
+
+    /// Converts a line and column back into a byte offset. Returns `None` if
+    /// the line doesn't exist or the column is past its end.
+    pub fn offset(&self, line_col: LineCol) -> Option<usize> {
+        let range = self.line_range(line_col.line)?;
+        let offset = range.start + line_col.col;
+        if offset > range.end {
+            None
+        } else {
+            Some(offset)
+        }
+    }
+
+    /// Returns the byte range of `line`, including its line ending.
+    pub fn line_range(&self, line: usize) -> Option<Range<usize>> {
+        let start = *self.line_starts.get(line)?;
+        let end = self.line_starts.get(line + 1).copied().unwrap_or(self.len);
+        Some(start..end)
+    }
+}
+
+/// Lexes `src` and builds its [`LineIndex`] in the same pass. Only tokens
+/// which can contain newlines are searched for them.
+pub fn tokenize_with_line_index(src: &str) -> (Vec<Token>, LineIndex) {
+    let mut tokens = Vec::new();
+    let mut line_starts = vec![0];
+    for (range, token) in tokenize(src).with_ranges() {
+        let first_newline = match token.kind {
+            TokenKind::Whitespace { .. } | TokenKind::BlockComment { .. } => Some(0),
+            TokenKind::Literal { kind, .. } => match kind {
+                LiteralKind::Str { first_newline, .. }
+                | LiteralKind::ByteStr { first_newline, .. }
+                | LiteralKind::CStr { first_newline, .. }
+                | LiteralKind::RawStr { err: None, first_newline, .. }
+                | LiteralKind::RawByteStr { err: None, first_newline, .. }
+                | LiteralKind::RawCStr { err: None, first_newline, .. } => first_newline,
+                // Malformed raw strings don't track newlines, and `'\n'` is
+                // lexed as a char literal.
+                LiteralKind::RawStr { .. }
+                | LiteralKind::RawByteStr { .. }
+                | LiteralKind::RawCStr { .. }
+                | LiteralKind::Char { .. }
+                | LiteralKind::Byte { .. } => Some(0),
+                LiteralKind::Int { .. } | LiteralKind::Float { .. } => None,
+            },
+            _ => None,
+        };
+        if let Some(first_newline) = first_newline {
+            let first_newline = first_newline as usize;
+            let start = range.start + first_newline;
+            let text = &src[start..range.end];
+            line_starts.extend(text.match_indices('\n').map(|(i, _)| start + i + 1));
+        }
+        tokens.push(token);
+    }
+    (tokens, LineIndex { line_starts, len: src.len() })
+}
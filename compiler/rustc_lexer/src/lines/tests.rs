@@ -0,0 +1,112 @@
+use super::*;
+
+fn check_ranges(actual: Vec<Range<usize>>, expected: &[(usize, usize)]) {
+    let actual: Vec<_> = actual.into_iter().map(|range| (range.start, range.end)).collect();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn test_trailing_whitespace() {
+    let check = |src, expected| check_ranges(trailing_whitespace(src), expected);
+
+    check("", &[]);
+    check("fn main() {}\n", &[]);
+    check("fn main() {}  \n", &[(12, 14)]);
+    check("fn main() {}\t \r\n", &[(12, 14)]);
+    check("a \nb\t", &[(1, 2), (4, 5)]);
+    check("// comment  \nx", &[(10, 12)]);
+    check("/* a  \n b */", &[(4, 6)]);
+}
+
+#[test]
+fn test_trailing_whitespace_in_literals() {
+    let check = |src, expected| check_ranges(trailing_whitespace(src), expected);
+
+    check("\"a  \nb\"", &[]);
+    check("r#\"a  \n\"#  \n", &[(9, 11)]);
+    check("let x =  \"a\"", &[]);
+    check("let x =  \n\"a\"", &[(7, 9)]);
+}
+
+#[test]
+fn test_final_newline() {
+    assert_eq!(final_newline(""), FinalNewline::Empty);
+    assert_eq!(final_newline("fn main() {}\n"), FinalNewline::Present);
+    assert_eq!(final_newline("fn main() {}\r\n"), FinalNewline::Present);
+    assert_eq!(final_newline("fn main() {}"), FinalNewline::Missing { insert_at: 12 });
+    assert_eq!(final_newline("// comment"), FinalNewline::Missing { insert_at: 10 });
+    assert_eq!(final_newline("x = \"abc\n"), FinalNewline::Unterminated { token_start: 4 });
+    assert_eq!(final_newline("x /* a /* */\n"), FinalNewline::Unterminated { token_start: 2 });
+    assert_eq!(final_newline("r#\"a\"\n"), FinalNewline::Unterminated { token_start: 0 });
+    assert_eq!(final_newline("r#\"a\"#"), FinalNewline::Missing { insert_at: 6 });
+}
+
+#[test]
+fn test_line_endings() {
+    let endings = line_endings("a\nb\r\nc\rd\n");
+    assert_eq!(endings.lf, [1, 8]);
+    assert_eq!(endings.crlf, [3]);
+    assert_eq!(endings.cr, [6]);
+    assert!(endings.is_mixed());
+    assert_eq!(endings.kinds(), LineEndingKinds { lf: true, crlf: true, cr: true });
+
+    let endings = line_endings("a\r\n// comment\r\n");
+    assert_eq!(endings, LineEndings { lf: vec![], crlf: vec![1, 13], cr: vec![] });
+    assert!(!endings.is_mixed());
+
+    // Line endings inside literals are part of their value.
+    let endings = line_endings("x = r#\"a\r\nb\n\"#;\n\"\r\n\"");
+    assert_eq!(endings, LineEndings { lf: vec![15], crlf: vec![], cr: vec![] });
+}
+
+#[test]
+fn test_stray_boms() {
+    assert_eq!(stray_boms("\u{FEFF}fn main() {}\n"), vec![]);
+    assert_eq!(
+        stray_boms("fn a() {}\n\u{FEFF}fn b() {}\n"),
+        vec![StrayBom { offset: 10, kind: StrayBomKind::ProbableConcatenation }]
+    );
+    assert_eq!(
+        stray_boms("fn a() {\u{FEFF}}"),
+        vec![StrayBom { offset: 8, kind: StrayBomKind::Other }]
+    );
+    assert_eq!(stray_boms("// \u{FEFF}\n\"\u{FEFF}\""), vec![]);
+}
+
+#[test]
+fn test_long_lines() {
+    let check = |src, max_len, expected| check_ranges(long_lines(src, max_len), expected);
+    check("", 0, &[]);
+    check("abc\nabcd\r\nab", 3, &[(4, 8)]);
+    check("abcd", 3, &[(0, 4)]);
+    check("\"abcd\nab\"", 4, &[(0, 5)]);
+}
+
+#[test]
+fn test_line_index() {
+    let src = "fn f() {\r\n    \"a\nb\" /* c\n */\n}";
+    let (tokens, index) = tokenize_with_line_index(src);
+    assert_eq!(tokens, tokenize(src).collect::<Vec<_>>());
+    assert_eq!(index, LineIndex::new(src));
+    assert_eq!(index.line_count(), 5);
+
+    assert_eq!(index.line_col(0), Some(LineCol { line: 0, col: 0 }));
+    assert_eq!(index.line_col(9), Some(LineCol { line: 0, col: 9 }));
+    assert_eq!(index.line_col(14), Some(LineCol { line: 1, col: 4 }));
+    assert_eq!(index.line_col(src.len()), Some(LineCol { line: 4, col: 1 }));
+    assert_eq!(index.line_col(src.len() + 1), None);
+
+    assert_eq!(index.offset(LineCol { line: 1, col: 4 }), Some(14));
+    assert_eq!(index.offset(LineCol { line: 4, col: 1 }), Some(src.len()));
+    assert_eq!(index.offset(LineCol { line: 4, col: 2 }), None);
+    assert_eq!(index.offset(LineCol { line: 5, col: 0 }), None);
+    assert_eq!(index.line_range(0), Some(0..10));
+}
+
+#[test]
+fn test_line_index_in_tokens() {
+    for src in ["'\n'", "r#\"a\nb\"", "r#~\"a\nb\"#", "b\"\n\"", "a\n\nb"] {
+        let (_, index) = tokenize_with_line_index(src);
+        assert_eq!(index, LineIndex::new(src), "{:?}", src);
+    }
+}
@@ -0,0 +1,155 @@
+//! Extraction and lexing of Rust code blocks in Markdown documents.
+//!
+//! Only fenced code blocks are recognized (```` ``` ```` or `~~~`), following
+//! the CommonMark rules for fences and their indentation. Code blocks nested in
+//! block quotes or list items are not.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize, Token};
+
+#[cfg(test)]
+mod tests;
+
+/// A fenced code block, see [`code_blocks`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CodeBlock {
+    /// The info string following the opening fence, e.g. `rust,ignore`.
+    pub info: String,
+    /// The contents of the block, with the indentation of the fence removed.
+    pub code: String,
+    /// Sorted `(code offset, markdown offset)` pairs for the start of every line.
+    line_starts: Vec<(usize, usize)>,
+    /// Markdown offset of the end of the contents.
+    end: usize,
+}
+
+impl CodeBlock {
+    /// Returns true if the block contains Rust code, using the same rules as
+    /// rustdoc: blocks without an info string, blocks marked `rust`, and
+    /// blocks with only rustdoc attributes like `ignore` or `edition2018`.
+    pub fn is_rust(&self) -> bool {
+        let mut seen_other = false;
+        for token in self.info.split(|c: char| c == ',' || c.is_whitespace()) {
+            match token.trim() {
+                "" => {}
+                "rust" => return true,
+                "ignore" | "should_panic" | "no_run" | "compile_fail" | "test_harness"
+                | "allow_fail" => {}
+                token if token.starts_with("edition") || token.starts_with("ignore-") => {}
+                token
+                    if token.len() > 1
+                        && token.starts_with('E')
+                        && token[1..].bytes().all(|b| b.is_ascii_digit()) => {}
+                _ => seen_other = true,
+            }
+        }
+        !seen_other
+    }
+
+    /// Maps an offset into [`CodeBlock::code`] to the offset into the
+    /// Markdown document.
+    pub fn markdown_offset(&self, offset: usize) -> usize {
+        if offset == self.code.len() {
+            return self.end;
+        }
+        let idx = self.line_starts.partition_point(|&(start, _)| start <= offset) - 1;
+        let (code_start, markdown_start) = self.line_starts[idx];
+        markdown_start + offset - code_start
+    }
+
+    /// Lexes the code of the block. Along with each token, its range in the
+    /// Markdown document is returned.
+    pub fn tokenize(&self) -> impl Iterator<Item = (Token, Range<usize>)> + '_ {
+        tokenize(&self.code).with_ranges().map(move |(range, token)| {
+            (token, self.markdown_offset(range.start)..self.markdown_offset(range.end))
+        })
+    }
+}
+
+/// Returns the fenced code blocks of a Markdown document.
+pub fn code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut res = Vec::new();
+    let mut lines = lines_with_offsets(markdown);
+    while let Some((_, line)) = lines.next() {
+        let fence = match Fence::parse(line) {
+            Some(fence) => fence,
+            None => continue,
+        };
+        let info = line[fence.indent + fence.len..].trim();
+        // Backtick fences can't have backticks in their info string.
+        if fence.char == '`' && info.contains('`') {
+            continue;
+        }
+        let mut block = CodeBlock {
+            info: info.to_string(),
+            code: String::new(),
+            line_starts: Vec::new(),
+            end: markdown.len(),
+        };
+        for (offset, line) in lines.by_ref() {
+            if fence.is_closed_by(line) {
+                block.end = offset;
+                break;
+            }
+            // Remove up to as much indentation as the opening fence has.
+            let indent = line.bytes().take(fence.indent).take_while(|&b| b == b' ').count();
+            block.line_starts.push((block.code.len(), offset + indent));
+            block.code.push_str(&line[indent..]);
+        }
+        res.push(block);
+    }
+    res
+}
+
+/// Returns the fenced code blocks of a Markdown document which contain Rust
+/// code, see [`CodeBlock::is_rust`].
+pub fn rust_code_blocks(markdown: &str) -> Vec<CodeBlock> {
+    let mut blocks = code_blocks(markdown);
+    blocks.retain(CodeBlock::is_rust);
+    blocks
+}
+
+struct Fence {
+    char: char,
+    indent: usize,
+    len: usize,
+}
+
+impl Fence {
+    fn parse(line: &str) -> Option<Fence> {
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        if indent > 3 {
+            return None;
+        }
+        let char = line[indent..].chars().next().filter(|&c| c == '`' || c == '~')?;
+        let len = line[indent..].len() - line[indent..].trim_start_matches(char).len();
+        if len < 3 {
+            return None;
+        }
+        Some(Fence { char, indent, len })
+    }
+
+    fn is_closed_by(&self, line: &str) -> bool {
+        match Fence::parse(line) {
+            Some(fence) => {
+                fence.char == self.char
+                    && fence.len >= self.len
+                    && line[fence.indent + fence.len..].trim().is_empty()
+            }
+            None => false,
+        }
+    }
+}
+
+/// Splits `text` into lines including their line endings, along with their
+/// offsets.
+fn lines_with_offsets(text: &str) -> impl Iterator<Item = (usize, &str)> {
+    text.split_inclusive('\n').scan(0, |offset, line| {
+        let start = *offset;
+        *offset += line.len();
+        Some((start, line))
+    })
+}
@@ -0,0 +1,78 @@
+use super::*;
+use crate::TokenKind;
+
+const DOC: &str = "# Title
+
+```rust,ignore
+let x = 1;
+```
+
+  ~~~~
+  // indented
+   fn f() {}
+  ~~~~
+
+```text
+not rust
+```
+
+````
+unclosed
+";
+
+#[test]
+fn test_code_blocks() {
+    let blocks = code_blocks(DOC);
+    let summary: Vec<_> = blocks
+        .iter()
+        .map(|block| (block.info.as_str(), block.code.as_str(), block.is_rust()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("rust,ignore", "let x = 1;\n", true),
+            ("", "// indented\n fn f() {}\n", true),
+            ("text", "not rust\n", false),
+            ("", "unclosed\n", true),
+        ]
+    );
+    assert_eq!(rust_code_blocks(DOC).len(), 3);
+}
+
+#[test]
+fn test_markdown_offsets() {
+    let blocks = code_blocks(DOC);
+    let tokens: Vec<_> = blocks[1]
+        .tokenize()
+        .filter(|(token, _)| !matches!(token.kind, TokenKind::Whitespace { .. }))
+        .map(|(token, range)| (token.kind, &DOC[range]))
+        .collect();
+    assert_eq!(
+        tokens,
+        [
+            (TokenKind::LineComment { doc_style: None }, "// indented"),
+            (TokenKind::Ident, "fn"),
+            (TokenKind::Ident, "f"),
+            (TokenKind::OpenParen, "("),
+            (TokenKind::CloseParen, ")"),
+            (TokenKind::OpenBrace, "{"),
+            (TokenKind::CloseBrace, "}"),
+        ]
+    );
+    assert_eq!(blocks[3].markdown_offset(blocks[3].code.len()), DOC.len());
+}
+
+#[test]
+fn test_info_strings() {
+    let is_rust = |info: &str| {
+        CodeBlock { info: info.to_string(), code: String::new(), line_starts: vec![], end: 0 }
+            .is_rust()
+    };
+    assert!(is_rust(""));
+    assert!(is_rust("rust"));
+    assert!(is_rust("should_panic, edition2021"));
+    assert!(is_rust("compile_fail,E0308"));
+    assert!(is_rust("sh rust"));
+    assert!(!is_rust("sh"));
+    assert!(!is_rust("ignore text"));
+}
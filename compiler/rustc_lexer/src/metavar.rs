@@ -0,0 +1,102 @@
+//! Recognition of `macro_rules!` metavariables in the token stream.
+//!
+//! The lexer produces a `$` as a separate [`Dollar`](crate::TokenKind::Dollar)
+//! token, so `$name:ty` is five tokens. This module pairs them back up into
+//! the units macro-aware tools care about.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Fragment specifiers accepted in `macro_rules!` matchers.
+const FRAGMENT_SPECIFIERS: &[&str] = &[
+    "block",
+    "expr",
+    "ident",
+    "item",
+    "lifetime",
+    "literal",
+    "meta",
+    "pat",
+    "pat_param",
+    "path",
+    "stmt",
+    "tt",
+    "ty",
+    "vis",
+];
+
+/// A metavariable occurrence, see [`metavariables`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MetaVar {
+    /// Byte range of the whole unit, starting at the `$`.
+    pub range: Range<usize>,
+    pub kind: MetaVarKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MetaVarKind {
+    /// `$crate`
+    Crate,
+    /// `$name`, with the byte range of `name`.
+    Var { name: Range<usize> },
+    /// `$name:fragment`, with the byte ranges of `name` and `fragment`.
+    Decl { name: Range<usize>, fragment: Range<usize> },
+}
+
+/// Returns true if `s` is a fragment specifier, like the `ty` in `$t:ty`.
+pub fn is_fragment_specifier(s: &str) -> bool {
+    FRAGMENT_SPECIFIERS.contains(&s)
+}
+
+/// Finds the metavariables in `src`, which is typically the body of a
+/// `macro_rules!` definition.
+///
+/// Whitespace and comments are allowed between the parts of a metavariable,
+/// like rustc does. Since transcribers may contain code such as `$x: u32`,
+/// `$name:fragment` is only recognized if `fragment` is a known fragment
+/// specifier.
+pub fn metavariables(src: &str) -> Vec<MetaVar> {
+    let tokens: Vec<_> = tokenize_with_offsets(src)
+        .filter(|(_, kind)| !kind.is_trivia())
+        .map(|(range, kind)| (kind, range))
+        .collect();
+
+    let mut res = Vec::new();
+    let mut i = 0;
+    while i < tokens.len() {
+        let (start, name) = match (&tokens[i], tokens.get(i + 1)) {
+            ((TokenKind::Dollar, dollar), Some((TokenKind::Ident | TokenKind::RawIdent, name))) => {
+                (dollar.start, name.clone())
+            }
+            _ => {
+                i += 1;
+                continue;
+            }
+        };
+        if &src[name.clone()] == "crate" {
+            res.push(MetaVar { range: start..name.end, kind: MetaVarKind::Crate });
+            i += 2;
+            continue;
+        }
+        match (tokens.get(i + 2), tokens.get(i + 3)) {
+            (Some((TokenKind::Colon, _)), Some((TokenKind::Ident, fragment)))
+                if is_fragment_specifier(&src[fragment.clone()]) =>
+            {
+                let range = start..fragment.end;
+                let kind = MetaVarKind::Decl { name, fragment: fragment.clone() };
+                res.push(MetaVar { range, kind });
+                i += 4;
+            }
+            _ => {
+                res.push(MetaVar { range: start..name.end, kind: MetaVarKind::Var { name } });
+                i += 2;
+            }
+        }
+    }
+    res
+}
@@ -0,0 +1,37 @@
+use super::*;
+
+#[test]
+fn test_metavariables() {
+    let src = "($name:ident, $ t : ty) => { $crate::f::<$t>($name: u32) }";
+    let actual: Vec<_> = metavariables(src)
+        .into_iter()
+        .map(|var| {
+            let kind = match var.kind {
+                MetaVarKind::Crate => "crate".to_string(),
+                MetaVarKind::Var { name } => format!("var {}", &src[name]),
+                MetaVarKind::Decl { name, fragment } => {
+                    format!("decl {} {}", &src[name], &src[fragment])
+                }
+            };
+            (&src[var.range], kind)
+        })
+        .collect();
+    assert_eq!(
+        actual,
+        [
+            ("$name:ident", "decl name ident".to_string()),
+            ("$ t : ty", "decl t ty".to_string()),
+            ("$crate", "crate".to_string()),
+            ("$t", "var t".to_string()),
+            ("$name", "var name".to_string()),
+        ]
+    );
+}
+
+#[test]
+fn test_repetitions_are_not_metavariables() {
+    assert_eq!(
+        metavariables("$($x:expr),* $"),
+        [MetaVar { range: 2..9, kind: MetaVarKind::Decl { name: 3..4, fragment: 5..9 } }]
+    );
+}
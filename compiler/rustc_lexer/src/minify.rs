@@ -0,0 +1,82 @@
+//! Removal of comments and redundant whitespace.
+//!
+//! [`minify`] keeps every token except trivia and inserts a single space only
+//! where two tokens would otherwise lex differently, e.g. between two
+//! identifiers or between `a` and `"b"`, which would form a reserved prefix.
+//! Punctuation which a parser would glue into an operator, like `<` and `=`,
+//! stays separate as well.
+
+use alloc::format;
+use alloc::string::String;
+
+use crate::pipeline::GLUED_PUNCT;
+use crate::{strip_shebang, tokenize, tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct MinifyOptions {
+    /// Whether doc comments are kept. They are removed by default, which
+    /// changes the meaning of the code if it inspects its own attributes.
+    pub keep_doc_comments: bool,
+}
+
+/// Returns `src` without comments and with as little whitespace as possible,
+/// while lexing to the same tokens.
+pub fn minify(src: &str) -> String {
+    minify_with_options(src, &MinifyOptions::default())
+}
+
+/// Like [`minify`], but configured by `options`.
+pub fn minify_with_options(src: &str, options: &MinifyOptions) -> String {
+    let mut out = String::with_capacity(src.len());
+    let shebang_len = strip_shebang(src).map_or(0, |shebang| shebang.len);
+    if shebang_len > 0 {
+        out.push_str(&src[..shebang_len]);
+        out.push('\n');
+    }
+    let mut prev: Option<(TokenKind, &str)> = None;
+    let mut separated = false;
+    let rest = &src[shebang_len..];
+    for (range, kind) in tokenize_with_offsets(rest) {
+        let text = &rest[range];
+        let keep = match kind {
+            TokenKind::Whitespace { .. } => false,
+            TokenKind::LineComment { doc_style } | TokenKind::BlockComment { doc_style, .. } => {
+                doc_style.is_some() && options.keep_doc_comments
+            }
+            _ => true,
+        };
+        if !keep {
+            separated = true;
+            continue;
+        }
+        if let Some((prev_kind, prev_text)) = prev {
+            if matches!(prev_kind, TokenKind::LineComment { .. }) {
+                out.push('\n');
+            } else if separated && needs_space(prev_text, text) {
+                out.push(' ');
+            }
+        }
+        out.push_str(text);
+        prev = Some((kind, text));
+        separated = false;
+    }
+    out
+}
+
+/// Whether the tokens `left` and `right`, which are separated by trivia in
+/// the source, need to stay separated.
+fn needs_space(left: &str, right: &str) -> bool {
+    let joined = format!("{}{}", left, right);
+    let mut tokens = tokenize(&joined);
+    if tokens.next().map(|token| token.len) != Some(left.len())
+        || tokens.next().map(|token| token.len) != Some(right.len())
+    {
+        return true;
+    }
+    // Punctuation which the parser would glue into an operator, like the
+    // `< <` of `a < <T>::C`, which would be read as a shift.
+    left.len() == 1 && right.len() == 1 && GLUED_PUNCT.iter().any(|op| op.contains(joined.as_str()))
+}
@@ -0,0 +1,65 @@
+use super::*;
+use expect_test::{expect, Expect};
+
+/// The texts of the tokens of `src` which are not trivia.
+fn significant_tokens(src: &str, keep_doc_comments: bool) -> Vec<&str> {
+    let mut pos = 0;
+    let mut res = Vec::new();
+    for token in tokenize(src) {
+        let text = &src[pos..pos + token.len];
+        pos += token.len;
+        match token.kind {
+            TokenKind::Whitespace { .. } => {}
+            TokenKind::LineComment { doc_style } | TokenKind::BlockComment { doc_style, .. }
+                if doc_style.is_none() || !keep_doc_comments => {}
+            _ => res.push(text),
+        }
+    }
+    res
+}
+
+fn check(src: &str, keep_doc_comments: bool, expect: Expect) {
+    let minified = minify_with_options(src, &MinifyOptions { keep_doc_comments });
+    assert_eq!(
+        significant_tokens(&minified, keep_doc_comments),
+        significant_tokens(src, keep_doc_comments)
+    );
+    expect.assert_eq(&minified);
+}
+
+#[test]
+fn test_minify() {
+    check(
+        "fn main() {\n    // comment\n    let x = a /* c */ - -1;\n    println!(\"{}\", x);\n}\n",
+        false,
+        expect![[r#"fn main(){let x=a--1;println!("{}",x);}"#]],
+    );
+}
+
+#[test]
+fn test_separation() {
+    check(
+        r##"a "b" r#"c"# a /**/ 'b' 1 . 0 1 .foo a < <T>::C / /**/* x . . = y"##,
+        false,
+        expect![[r##"a"b" r#"c"# a'b'1 .0 1 .foo a< <T>::C/ *x. . =y"##]],
+    );
+}
+
+#[test]
+fn test_doc_comments() {
+    check(
+        "#!/bin/rust\n/// doc\nfn f() {} /** doc */ struct S;",
+        false,
+        expect![[r#"
+        #!/bin/rust
+        fn f(){}struct S;"#]],
+    );
+    check(
+        "#!/bin/rust\n/// doc\nfn f() {} /** doc */ struct S;",
+        true,
+        expect![[r#"
+        #!/bin/rust
+        /// doc
+        fn f(){}/** doc */struct S;"#]],
+    );
+}
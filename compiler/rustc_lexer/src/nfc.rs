@@ -0,0 +1,89 @@
+//! Detection of identifiers which are not in Unicode Normalization Form C.
+//!
+//! rustc normalizes identifiers to NFC, so `é` written as `e` followed by a
+//! combining acute accent is the same identifier as the precomposed `é`.
+//! [`non_nfc_idents`] finds the identifiers and lifetimes this applies to,
+//! so tools can normalize them the same way, or warn about them.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use unicode_normalization::char::canonical_combining_class;
+use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+
+use crate::{tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// An identifier which is not in NFC, see [`non_nfc_idents`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NonNfcIdent {
+    /// Byte range of the token in the source.
+    pub range: Range<usize>,
+    /// The NFC of the text of the token.
+    pub normalized: String,
+    /// Byte ranges in the source of the parts of the token which change when
+    /// normalized.
+    pub differing: Vec<Range<usize>>,
+}
+
+/// Returns the identifiers, raw identifiers and lifetimes in `src` which are
+/// not in NFC.
+pub fn non_nfc_idents(src: &str) -> Vec<NonNfcIdent> {
+    let mut res = Vec::new();
+    for (range, kind) in tokenize_with_offsets(src) {
+        if !matches!(
+            kind,
+            TokenKind::Ident
+                | TokenKind::RawIdent
+                | TokenKind::Lifetime { .. }
+                | TokenKind::RawLifetime
+        ) {
+            continue;
+        }
+        let text = &src[range.clone()];
+        if text.is_ascii() || unicode_normalization::is_nfc(text) {
+            continue;
+        }
+        let differing = differing_parts(text)
+            .into_iter()
+            .map(|part| range.start + part.start..range.start + part.end)
+            .collect();
+        res.push(NonNfcIdent { range, normalized: text.nfc().collect(), differing });
+    }
+    res
+}
+
+/// Returns the ranges of the parts of `text` which change when normalized.
+///
+/// `text` is split before each starter which is unchanged by normalization
+/// and never combines with the chars before it, so each part normalizes on
+/// its own. Adjacent changing parts are merged.
+fn differing_parts(text: &str) -> Vec<Range<usize>> {
+    let mut res: Vec<Range<usize>> = Vec::new();
+    let mut boundaries: Vec<usize> = text
+        .char_indices()
+        .filter(|&(i, c)| i == 0 || is_boundary_before(c))
+        .map(|(i, _)| i)
+        .collect();
+    boundaries.push(text.len());
+    for part in boundaries.windows(2) {
+        let part = part[0]..part[1];
+        if text[part.clone()].nfc().eq(text[part.clone()].chars()) {
+            continue;
+        }
+        match res.last_mut() {
+            Some(last) if last.end == part.start => last.end = part.end,
+            _ => res.push(part),
+        }
+    }
+    res
+}
+
+/// Whether `c` is a starter which is unchanged by normalization to NFC and
+/// never combines with the chars before it.
+fn is_boundary_before(c: char) -> bool {
+    canonical_combining_class(c) == 0 && is_nfc_quick(core::iter::once(c)) == IsNormalized::Yes
+}
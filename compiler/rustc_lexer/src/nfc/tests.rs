@@ -0,0 +1,38 @@
+use super::*;
+use expect_test::expect;
+
+#[test]
+fn test_non_nfc_idents() {
+    // `e` followed by U+0301 COMBINING ACUTE ACCENT, and U+212B ANGSTROM SIGN,
+    // whose NFC is U+00C5 LATIN CAPITAL LETTER A WITH RING ABOVE. The `x`
+    // before the latter is reported as well, since the parts are split only
+    // before chars which can't change when normalized.
+    let src = "let caf\u{65}\u{301}_x\u{212B} = '\u{65}\u{301}; let caf\u{e9} = r#n\u{303}o;";
+    expect![[r#"
+        [
+            NonNfcIdent {
+                range: 4..15,
+                normalized: "café_xÅ",
+                differing: [
+                    7..10,
+                    11..15,
+                ],
+            },
+            NonNfcIdent {
+                range: 18..22,
+                normalized: "'é",
+                differing: [
+                    19..22,
+                ],
+            },
+            NonNfcIdent {
+                range: 36..42,
+                normalized: "r#ño",
+                differing: [
+                    38..41,
+                ],
+            },
+        ]
+    "#]]
+    .assert_debug_eq(&non_nfc_idents(src));
+}
@@ -0,0 +1,144 @@
+//! Normalized token sequences for code-similarity analysis.
+//!
+//! Clone and plagiarism detectors compare token sequences in which renaming
+//! an identifier or changing a constant makes no difference. [`normalize`]
+//! drops trivia and doc comments, glues operators and replaces identifiers
+//! and literals by buckets, configured by [`NormalizeOptions`]. Every token
+//! keeps its source range, so matches can be reported in the source.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::pipeline::Stage;
+use crate::{tokenize, LiteralKind, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// How [`normalize`] buckets identifiers, including lifetimes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IdentBuckets {
+    /// Identifiers are kept as [`Normalized::Text`].
+    Verbatim,
+    /// All identifiers are [`Normalized::Ident`]`(0)`.
+    Single,
+    /// Identifiers are numbered by their first occurrence, so a sequence
+    /// matches a consistently renamed copy of itself but not a copy in which
+    /// two names were merged.
+    ByFirstOccurrence,
+}
+
+/// How [`normalize`] buckets literals.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiteralBuckets {
+    /// Literals are kept as [`Normalized::Text`].
+    Verbatim,
+    /// Literals are bucketed by their [`LiteralClass`].
+    ByClass,
+    /// All literals are [`Normalized::Literal`]`(None)`.
+    Single,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NormalizeOptions {
+    pub identifiers: IdentBuckets,
+    pub literals: LiteralBuckets,
+}
+
+impl Default for NormalizeOptions {
+    fn default() -> NormalizeOptions {
+        NormalizeOptions {
+            identifiers: IdentBuckets::ByFirstOccurrence,
+            literals: LiteralBuckets::ByClass,
+        }
+    }
+}
+
+/// The class of a literal, ignoring prefixes like `r#`, suffixes and the
+/// base of numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum LiteralClass {
+    Int,
+    Float,
+    Char,
+    Byte,
+    Str,
+    ByteStr,
+    CStr,
+}
+
+impl From<LiteralKind> for LiteralClass {
+    fn from(kind: LiteralKind) -> LiteralClass {
+        match kind {
+            LiteralKind::Int { .. } => LiteralClass::Int,
+            LiteralKind::Float { .. } => LiteralClass::Float,
+            LiteralKind::Char { .. } => LiteralClass::Char,
+            LiteralKind::Byte { .. } => LiteralClass::Byte,
+            LiteralKind::Str { .. } | LiteralKind::RawStr { .. } => LiteralClass::Str,
+            LiteralKind::ByteStr { .. } | LiteralKind::RawByteStr { .. } => LiteralClass::ByteStr,
+            LiteralKind::CStr { .. } | LiteralKind::RawCStr { .. } => LiteralClass::CStr,
+        }
+    }
+}
+
+/// A token of a normalized sequence. Equal values are considered the same
+/// token by similarity analyses.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Normalized<'a> {
+    /// A keyword, operator, delimiter or other token which is kept as is.
+    Text(&'a str),
+    /// A bucketed identifier or lifetime, see [`IdentBuckets`].
+    Ident(usize),
+    /// A bucketed literal, see [`LiteralBuckets`]. The class is `None` with
+    /// [`LiteralBuckets::Single`].
+    Literal(Option<LiteralClass>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizedToken<'a> {
+    pub kind: Normalized<'a>,
+    /// Byte range of the token in the source.
+    pub range: Range<usize>,
+}
+
+/// Returns the normalized token sequence of `src`.
+pub fn normalize<'a>(src: &'a str, options: &NormalizeOptions) -> Vec<NormalizedToken<'a>> {
+    let mut idents: BTreeMap<&str, usize> = BTreeMap::new();
+    let mut res = Vec::new();
+    for lexeme in tokenize(src).lexemes().attach_trivia().glued().classified() {
+        let text = &src[lexeme.range.clone()];
+        let kind = match lexeme.kind {
+            TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } => continue,
+            TokenKind::Ident
+            | TokenKind::RawIdent
+            | TokenKind::InvalidIdent
+            | TokenKind::UnknownPrefix { .. }
+            | TokenKind::Lifetime { .. }
+            | TokenKind::RawLifetime
+                if !lexeme.keyword =>
+            {
+                match options.identifiers {
+                    IdentBuckets::Verbatim => Normalized::Text(text),
+                    IdentBuckets::Single => Normalized::Ident(0),
+                    IdentBuckets::ByFirstOccurrence => {
+                        // `r#foo` is the same identifier as `foo`.
+                        let name = text.strip_prefix("r#").unwrap_or(text);
+                        let next = idents.len();
+                        Normalized::Ident(*idents.entry(name).or_insert(next))
+                    }
+                }
+            }
+            TokenKind::Literal { kind, .. } | TokenKind::PrefixedLiteral { kind, .. } => {
+                match options.literals {
+                    LiteralBuckets::Verbatim => Normalized::Text(text),
+                    LiteralBuckets::ByClass => Normalized::Literal(Some(kind.into())),
+                    LiteralBuckets::Single => Normalized::Literal(None),
+                }
+            }
+            _ => Normalized::Text(text),
+        };
+        res.push(NormalizedToken { kind, range: lexeme.range });
+    }
+    res
+}
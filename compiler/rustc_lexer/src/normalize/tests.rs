@@ -0,0 +1,89 @@
+use super::*;
+use expect_test::{expect, Expect};
+
+fn check(src: &str, options: NormalizeOptions, expect: Expect) {
+    let actual: Vec<String> = normalize(src, &options)
+        .into_iter()
+        .map(|token| format!("{:?}@{:?}", token.kind, token.range))
+        .collect();
+    expect.assert_eq(&actual.join("\n"));
+}
+
+#[test]
+fn test_default() {
+    check(
+        "fn f(x: u8) -> u8 { /* c */ x + r#x * 2 + 'c'.len() as u8 }",
+        NormalizeOptions::default(),
+        expect![[r#"
+            Text("fn")@0..2
+            Ident(0)@3..4
+            Text("(")@4..5
+            Ident(1)@5..6
+            Text(":")@6..7
+            Ident(2)@8..10
+            Text(")")@10..11
+            Text("->")@12..14
+            Ident(2)@15..17
+            Text("{")@18..19
+            Ident(1)@28..29
+            Text("+")@30..31
+            Ident(1)@32..35
+            Text("*")@36..37
+            Literal(Some(Int))@38..39
+            Text("+")@40..41
+            Literal(Some(Char))@42..45
+            Text(".")@45..46
+            Ident(3)@46..49
+            Text("(")@49..50
+            Text(")")@50..51
+            Text("as")@52..54
+            Ident(2)@55..57
+            Text("}")@58..59"#]],
+    );
+}
+
+#[test]
+fn test_renaming() {
+    let a = normalize("let a = b + a; // one", &NormalizeOptions::default());
+    let b = normalize("let x = y\n    + x;", &NormalizeOptions::default());
+    let c = normalize("let x = x + x;", &NormalizeOptions::default());
+    let kinds = |tokens: Vec<NormalizedToken<'static>>| {
+        tokens.into_iter().map(|token| token.kind).collect::<Vec<_>>()
+    };
+    let a = kinds(a);
+    assert_eq!(a, kinds(b));
+    assert_ne!(a, kinds(c));
+}
+
+#[test]
+fn test_options() {
+    check(
+        "a(b, 1, 2.0, \"s\")",
+        NormalizeOptions { identifiers: IdentBuckets::Single, literals: LiteralBuckets::Single },
+        expect![[r#"
+            Ident(0)@0..1
+            Text("(")@1..2
+            Ident(0)@2..3
+            Text(",")@3..4
+            Literal(None)@5..6
+            Text(",")@6..7
+            Literal(None)@8..11
+            Text(",")@11..12
+            Literal(None)@13..16
+            Text(")")@16..17"#]],
+    );
+    check(
+        "a(b, 1)",
+        NormalizeOptions {
+            identifiers: IdentBuckets::Verbatim,
+            literals: LiteralBuckets::Verbatim,
+        },
+        expect![[r#"
+            Text("a")@0..1
+            Text("(")@1..2
+            Text("b")@2..3
+            Text(",")@3..4
+            Text("1")@5..6
+            Text(")")@6..7"#]],
+    );
+}
@@ -0,0 +1,282 @@
+//! Composable post-processing of the token stream.
+//!
+//! Consumers usually need a few layers on top of the raw tokens: multi-char
+//! operators glued together, trivia out of the way, keywords told apart from
+//! identifiers, or delimited groups. Each of these is a [`Stage`], an
+//! iterator adapter over [`Lexeme`]s, so consumers pick the layers they need
+//! and nothing is collected in between:
+//!
+//! ```ignore (illustrative)
+//! let trees = tokenize(src).lexemes().attach_trivia().glued().classified().trees();
+//! ```
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::keywords::is_keyword;
+use crate::tree::{Delimiter, Node, TreeBuilder, DEFAULT_MAX_DEPTH};
+use crate::{Edition, TokenKind, Tokens};
+
+#[cfg(test)]
+mod tests;
+
+/// Multi-char operators, which [`Stage::glued`] combines from the tokens of
+/// their chars.
+pub(crate) const GLUED_PUNCT: &[&str] = &[
+    "<<=", ">>=", "...", "..=", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+=", "-=",
+    "*=", "/=", "%=", "^=", "&=", "|=", "<<", ">>", "..",
+];
+
+/// What all stages of a pipeline share.
+#[derive(Clone, Copy, Debug)]
+pub struct Context<'a> {
+    /// The input being lexed.
+    pub src: &'a str,
+    /// The edition the input is lexed in, see
+    /// [`tokenize_with_edition`](crate::tokenize_with_edition).
+    pub edition: Edition,
+}
+
+impl<'a> Context<'a> {
+    /// Returns the text of `lexeme`.
+    pub fn text(&self, lexeme: &Lexeme) -> &'a str {
+        &self.src[lexeme.range.clone()]
+    }
+}
+
+/// A token flowing through the pipeline, annotated by the stages it passed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lexeme {
+    /// Kind of the token. For glued operators, this is the kind of their
+    /// first char, the operator itself is given by the text.
+    pub kind: TokenKind,
+    /// Byte range in [`Context::src`].
+    pub range: Range<usize>,
+    /// Byte range of the whitespace and comments preceding the lexeme, set by
+    /// [`Stage::attach_trivia`]. Empty otherwise.
+    pub leading_trivia: Range<usize>,
+    /// Whether the lexeme is a keyword, set by [`Stage::classified`].
+    pub keyword: bool,
+}
+
+/// A token iterator which can be extended by further stages.
+pub trait Stage<'a>: Iterator<Item = Lexeme> + Sized {
+    fn context(&self) -> Context<'a>;
+
+    /// Combines adjacent punctuation tokens into multi-char operators like
+    /// `::` or `>>=`, taking the longest match. Tokens separated by trivia are
+    /// never glued. Consumers which need e.g. the `>>` closing nested generics
+    /// as two tokens have to split it again.
+    fn glued(self) -> Glued<Self> {
+        Glued { inner: self, buf: Vec::new() }
+    }
+
+    /// Marks identifiers which are strict or reserved keywords in the edition
+    /// of the [`Context`].
+    fn classified(self) -> Classified<Self> {
+        Classified { inner: self }
+    }
+
+    /// Removes whitespace and non-doc comments, recording their range as the
+    /// [`Lexeme::leading_trivia`] of the following lexeme. Trivia at the end
+    /// of the input follows the last lexeme and isn't attached to any.
+    fn attach_trivia(self) -> AttachTrivia<Self> {
+        AttachTrivia { inner: self }
+    }
+
+    /// Builds the trees of matching delimiters, nesting at most
+    /// [`DEFAULT_MAX_DEPTH`] groups.
+    fn trees(self) -> Vec<LexemeTree> {
+        self.trees_with_max_depth(DEFAULT_MAX_DEPTH)
+    }
+
+    /// Like [`Stage::trees`], but groups nested deeper than `max_depth` are
+    /// replaced by [`LexemeTree::TooDeep`] nodes.
+    fn trees_with_max_depth(self, max_depth: usize) -> Vec<LexemeTree> {
+        let len = self.context().src.len();
+        let mut builder = TreeBuilder::new(max_depth);
+        for lexeme in self {
+            builder.push(lexeme);
+        }
+        builder.finish(len)
+    }
+}
+
+impl<'a> Tokens<'a> {
+    /// Starts a pipeline over the remaining tokens.
+    pub fn lexemes(self) -> Lexemes<'a> {
+        Lexemes { tokens: self }
+    }
+}
+
+/// The first stage of every pipeline, see [`Tokens::lexemes`].
+#[derive(Clone)]
+pub struct Lexemes<'a> {
+    tokens: Tokens<'a>,
+}
+
+impl Iterator for Lexemes<'_> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let start = self.tokens.state().pos();
+        let token = self.tokens.next()?;
+        let range = start..start + token.len;
+        Some(Lexeme { kind: token.kind, range, leading_trivia: start..start, keyword: false })
+    }
+}
+
+impl<'a> Stage<'a> for Lexemes<'a> {
+    fn context(&self) -> Context<'a> {
+        Context { src: self.tokens.input, edition: self.tokens.cursor.config.edition }
+    }
+}
+
+/// See [`Stage::glued`].
+pub struct Glued<I> {
+    inner: I,
+    /// Lexemes read ahead, at most as many as the longest operator has chars.
+    buf: Vec<Lexeme>,
+}
+
+impl<'a, I: Stage<'a>> Iterator for Glued<I> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Lexeme> {
+        while self.buf.len() < 3 {
+            match self.inner.next() {
+                Some(lexeme) => self.buf.push(lexeme),
+                None => break,
+            }
+        }
+        if self.buf.is_empty() {
+            return None;
+        }
+        let src = self.inner.context().src;
+        let glued_len = (2..=self.buf.len()).rev().find(|&n| {
+            let lexemes = &self.buf[..n];
+            let adjacent = lexemes.windows(2).all(|pair| pair[0].range.end == pair[1].range.start);
+            adjacent
+                && lexemes.iter().all(|lexeme| lexeme.range.len() == 1)
+                && GLUED_PUNCT.contains(&&src[lexemes[0].range.start..lexemes[n - 1].range.end])
+        });
+        let mut lexeme = self.buf.remove(0);
+        if let Some(n) = glued_len {
+            lexeme.range.end = self.buf.drain(..n - 1).next_back().unwrap().range.end;
+        }
+        Some(lexeme)
+    }
+}
+
+impl<'a, I: Stage<'a>> Stage<'a> for Glued<I> {
+    fn context(&self) -> Context<'a> {
+        self.inner.context()
+    }
+}
+
+/// See [`Stage::classified`].
+pub struct Classified<I> {
+    inner: I,
+}
+
+impl<'a, I: Stage<'a>> Iterator for Classified<I> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let mut lexeme = self.inner.next()?;
+        if lexeme.kind == TokenKind::Ident {
+            let context = self.inner.context();
+            lexeme.keyword = is_keyword(context.text(&lexeme), context.edition);
+        }
+        Some(lexeme)
+    }
+}
+
+impl<'a, I: Stage<'a>> Stage<'a> for Classified<I> {
+    fn context(&self) -> Context<'a> {
+        self.inner.context()
+    }
+}
+
+/// See [`Stage::attach_trivia`].
+pub struct AttachTrivia<I> {
+    inner: I,
+}
+
+impl<'a, I: Stage<'a>> Iterator for AttachTrivia<I> {
+    type Item = Lexeme;
+
+    fn next(&mut self) -> Option<Lexeme> {
+        let mut trivia_start = None;
+        for mut lexeme in &mut self.inner {
+            if lexeme.kind.is_trivia() {
+                trivia_start.get_or_insert(lexeme.range.start);
+            } else {
+                let start = trivia_start.unwrap_or(lexeme.range.start);
+                lexeme.leading_trivia = start..lexeme.range.start;
+                return Some(lexeme);
+            }
+        }
+        None
+    }
+}
+
+impl<'a, I: Stage<'a>> Stage<'a> for AttachTrivia<I> {
+    fn context(&self) -> Context<'a> {
+        self.inner.context()
+    }
+}
+
+/// A lexeme or a delimited group of lexeme trees, see [`Stage::trees`] and
+/// [`TokenTree`](crate::tree::TokenTree).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LexemeTree {
+    /// A lexeme which is not a delimiter.
+    Lexeme(Lexeme),
+    /// A group of lexeme trees enclosed in delimiters.
+    Delimited {
+        delim: Delimiter,
+        open: Lexeme,
+        /// The closing delimiter, or `None` if the group is unclosed.
+        close: Option<Lexeme>,
+        children: Vec<LexemeTree>,
+    },
+    /// A closing delimiter without a matching opening one.
+    UnmatchedClose { delim: Delimiter, lexeme: Lexeme },
+    /// A group nested deeper than the maximum depth, see
+    /// [`TokenTree::TooDeep`](crate::tree::TokenTree::TooDeep).
+    TooDeep { range: Range<usize> },
+}
+
+impl Node for LexemeTree {
+    type Leaf = Lexeme;
+
+    fn kind(leaf: &Lexeme) -> TokenKind {
+        leaf.kind
+    }
+
+    fn range(leaf: &Lexeme) -> Range<usize> {
+        leaf.range.clone()
+    }
+
+    fn leaf(leaf: Lexeme) -> Self {
+        LexemeTree::Lexeme(leaf)
+    }
+
+    fn delimited(
+        delim: Delimiter,
+        open: Lexeme,
+        close: Option<Lexeme>,
+        children: Vec<Self>,
+    ) -> Self {
+        LexemeTree::Delimited { delim, open, close, children }
+    }
+
+    fn unmatched_close(delim: Delimiter, close: Lexeme) -> Self {
+        LexemeTree::UnmatchedClose { delim, lexeme: close }
+    }
+
+    fn too_deep(range: Range<usize>) -> Self {
+        LexemeTree::TooDeep { range }
+    }
+}
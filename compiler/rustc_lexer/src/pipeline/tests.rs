@@ -0,0 +1,133 @@
+use super::*;
+use crate::{tokenize, tokenize_with_edition};
+use expect_test::{expect, Expect};
+
+fn check<'a>(stage: impl Stage<'a>, expect: Expect) {
+    let ctx = stage.context();
+    let actual: String = stage
+        .map(|lexeme| {
+            format!(
+                "{:?} {:?} trivia={:?} keyword={}\n",
+                lexeme.kind,
+                ctx.text(&lexeme),
+                &ctx.src[lexeme.leading_trivia.clone()],
+                lexeme.keyword
+            )
+        })
+        .collect();
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn test_glued() {
+    check(
+        tokenize("a::b >>= c.. =>! = =").lexemes().glued(),
+        expect![[r#"
+            Ident "a" trivia="" keyword=false
+            Colon "::" trivia="" keyword=false
+            Ident "b" trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Gt ">>=" trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Ident "c" trivia="" keyword=false
+            Dot ".." trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Eq "=>" trivia="" keyword=false
+            Bang "!" trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Eq "=" trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Eq "=" trivia="" keyword=false
+        "#]],
+    );
+}
+
+#[test]
+fn test_attach_trivia_classified() {
+    check(
+        tokenize("fn /* c */ foo() // x\n/// doc\nr#fn ").lexemes().attach_trivia().classified(),
+        expect![[r#"
+            Ident "fn" trivia="" keyword=true
+            Ident "foo" trivia=" /* c */ " keyword=false
+            OpenParen "(" trivia="" keyword=false
+            CloseParen ")" trivia="" keyword=false
+            LineComment { doc_style: Some(Outer) } "/// doc" trivia=" // x\n" keyword=false
+            RawIdent "r#fn" trivia="\n" keyword=false
+        "#]],
+    );
+}
+
+#[test]
+fn test_classified_edition() {
+    check(
+        tokenize_with_edition("async dyn try", Edition::Edition2015).lexemes().classified(),
+        expect![[r#"
+            Ident "async" trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Ident "dyn" trivia="" keyword=false
+            Whitespace { line_endings: None, counts: None } " " trivia="" keyword=false
+            Ident "try" trivia="" keyword=false
+        "#]],
+    );
+}
+
+#[test]
+fn test_trees() {
+    let trees = tokenize("f(a ::b)").lexemes().attach_trivia().glued().trees();
+    expect![[r#"
+        [
+            Lexeme(
+                Lexeme {
+                    kind: Ident,
+                    range: 0..1,
+                    leading_trivia: 0..0,
+                    keyword: false,
+                },
+            ),
+            Delimited {
+                delim: Paren,
+                open: Lexeme {
+                    kind: OpenParen,
+                    range: 1..2,
+                    leading_trivia: 1..1,
+                    keyword: false,
+                },
+                close: Some(
+                    Lexeme {
+                        kind: CloseParen,
+                        range: 7..8,
+                        leading_trivia: 7..7,
+                        keyword: false,
+                    },
+                ),
+                children: [
+                    Lexeme(
+                        Lexeme {
+                            kind: Ident,
+                            range: 2..3,
+                            leading_trivia: 2..2,
+                            keyword: false,
+                        },
+                    ),
+                    Lexeme(
+                        Lexeme {
+                            kind: Colon,
+                            range: 4..6,
+                            leading_trivia: 3..4,
+                            keyword: false,
+                        },
+                    ),
+                    Lexeme(
+                        Lexeme {
+                            kind: Ident,
+                            range: 6..7,
+                            leading_trivia: 6..6,
+                            keyword: false,
+                        },
+                    ),
+                ],
+            },
+        ]
+    "#]]
+    .assert_debug_eq(&trees);
+}
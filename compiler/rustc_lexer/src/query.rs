@@ -0,0 +1,157 @@
+//! Lexing as a memoized query of an incremental computation framework, like
+//! the salsa framework used by rust-analyzer.
+//!
+//! The crate doesn't depend on any framework. Instead, the types here have
+//! what such frameworks require of query inputs and outputs: they are cheap
+//! to clone, and they compare equal whenever the lexing result is the same,
+//! so dependent queries are not re-executed after edits which don't change
+//! any token ("early cutoff"). A framework query is then a thin wrapper:
+//!
+//! ```ignore (illustrative)
+//! fn tokens(db: &dyn Db, file: FileId) -> TokenBuffer {
+//!     let source = db.source_text(file);
+//!     db.intern_tokens(lex(&source))
+//! }
+//! ```
+
+use alloc::string::String;
+use alloc::sync::Arc;
+use core::ops::Range;
+use std::collections::HashSet;
+
+use crate::incremental::{map_token_identities, TextEdit};
+use crate::{tokenize, Token};
+
+#[cfg(test)]
+mod tests;
+
+/// How rarely an input changes, which lets frameworks skip validating
+/// queries depending only on inputs which haven't changed. The levels
+/// correspond to salsa's durabilities of the same names.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Durability {
+    /// Files being edited, e.g. of the current workspace.
+    Low,
+    /// Files which change occasionally, e.g. of path dependencies.
+    Medium,
+    /// Files which practically never change, e.g. of the standard library
+    /// or of dependencies from a registry.
+    High,
+}
+
+/// The text of a file, the input of [`lex`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SourceText {
+    text: Arc<str>,
+    durability: Durability,
+}
+
+impl SourceText {
+    pub fn new(text: impl Into<Arc<str>>, durability: Durability) -> SourceText {
+        SourceText { text: text.into(), durability }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The durability to set for the input in the framework.
+    pub fn durability(&self) -> Durability {
+        self.durability
+    }
+
+    /// Applies an edit, replacing the bytes in `edit.range` by `replacement`.
+    pub fn edit(&self, edit: &TextEdit, replacement: &str) -> SourceText {
+        assert_eq!(edit.new_len, replacement.len(), "length of the replacement");
+        let mut text = String::with_capacity(self.text.len() - edit.range.len() + edit.new_len);
+        text.push_str(&self.text[..edit.range.start]);
+        text.push_str(replacement);
+        text.push_str(&self.text[edit.range.end..]);
+        SourceText { text: text.into(), durability: self.durability }
+    }
+}
+
+/// The tokens of a file, the output of [`lex`].
+///
+/// Clones share the tokens. Buffers compare equal if their tokens are equal,
+/// which is quick for buffers interned with a [`TokenInterner`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TokenBuffer {
+    tokens: Arc<[Token]>,
+}
+
+impl TokenBuffer {
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+}
+
+/// Lexes `source`. This is the function to memoize.
+pub fn lex(source: &SourceText) -> TokenBuffer {
+    TokenBuffer { tokens: tokenize(source.text()).collect() }
+}
+
+/// Deduplicates token buffers, so that equal buffers share their tokens and
+/// comparing them doesn't need to look at the tokens.
+#[derive(Default)]
+pub struct TokenInterner {
+    buffers: HashSet<Arc<[Token]>>,
+}
+
+impl TokenInterner {
+    pub fn new() -> TokenInterner {
+        TokenInterner::default()
+    }
+
+    /// Returns a buffer sharing the tokens of an equal, previously interned
+    /// buffer, if any.
+    pub fn intern(&mut self, buffer: TokenBuffer) -> TokenBuffer {
+        if let Some(tokens) = self.buffers.get(&buffer.tokens) {
+            return TokenBuffer { tokens: tokens.clone() };
+        }
+        self.buffers.insert(buffer.tokens.clone());
+        buffer
+    }
+}
+
+/// The tokens changed by an edit, see [`damage`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Damage {
+    /// Indices of the tokens in the new buffer which are not the same as a
+    /// token of the old buffer.
+    pub tokens: Range<usize>,
+    /// Byte range of these tokens in the new text.
+    pub bytes: Range<usize>,
+}
+
+impl Damage {
+    /// Whether the edit left all tokens intact, apart from their offsets.
+    /// Dependent queries which don't look at the text of the tokens don't
+    /// need to be invalidated.
+    pub fn is_empty(&self) -> bool {
+        self.tokens.is_empty()
+    }
+}
+
+/// Computes the range of tokens which changed when `old` was relexed to `new`
+/// after `edit`, e.g. to invalidate only the dependent queries whose inputs
+/// overlap the damage. Tokens are matched as by
+/// [`map_token_identities`].
+pub fn damage(old: &TokenBuffer, new: &TokenBuffer, edit: &TextEdit) -> Damage {
+    let identities = map_token_identities(old.tokens(), new.tokens(), edit);
+    // Tokens in the new buffer before the first and after the last affected
+    // old token are the same as in the old buffer.
+    let (mut start, mut end) = (0, new.tokens().len());
+    for (old_idx, new_idx) in identities.iter().enumerate() {
+        match new_idx {
+            Some(new_idx) if *new_idx == old_idx && start == old_idx => start += 1,
+            _ => break,
+        }
+    }
+    if let Some(Some(first_suffix)) = identities[start..].iter().find(|id| id.is_some()) {
+        end = *first_suffix;
+    }
+    let offset = |idx: usize| new.tokens()[..idx].iter().map(|token| token.len).sum::<usize>();
+    let bytes = offset(start)..offset(end.max(start));
+    Damage { tokens: start..end.max(start), bytes }
+}
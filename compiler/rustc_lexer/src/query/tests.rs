@@ -0,0 +1,38 @@
+use super::*;
+
+fn relex(old: &SourceText, edit: TextEdit, replacement: &str) -> Damage {
+    let new = old.edit(&edit, replacement);
+    damage(&lex(old), &lex(&new), &edit)
+}
+
+#[test]
+fn test_lex_is_stable() {
+    let source = SourceText::new("fn main() {}", Durability::Low);
+    assert_eq!(lex(&source), lex(&source.clone()));
+    assert_eq!(source.durability(), Durability::Low);
+
+    let mut interner = TokenInterner::new();
+    let a = interner.intern(lex(&source));
+    let b = interner.intern(lex(&source));
+    assert!(Arc::ptr_eq(&a.tokens, &b.tokens));
+}
+
+#[test]
+fn test_damage() {
+    let source = SourceText::new("let a = b + c;", Durability::Low);
+    // `b` -> `bcd`
+    assert_eq!(
+        relex(&source, TextEdit { range: 8..9, new_len: 3 }, "bcd"),
+        Damage { tokens: 6..7, bytes: 8..11 }
+    );
+    // `+` -> `+=`, which are two tokens.
+    assert_eq!(
+        relex(&source, TextEdit { range: 10..11, new_len: 2 }, "+="),
+        Damage { tokens: 8..10, bytes: 10..12 }
+    );
+    // Appending a token at the end.
+    assert_eq!(
+        relex(&source, TextEdit { range: 14..14, new_len: 1 }, "x"),
+        Damage { tokens: 12..13, bytes: 14..15 }
+    );
+}
@@ -0,0 +1,77 @@
+//! Support for interactive consoles.
+//!
+//! A console reading Rust code line by line has to decide whether to evaluate
+//! the input or to show a continuation prompt. [`input_state`] makes that
+//! decision with the lexer's rules instead of guessing.
+
+use alloc::vec::Vec;
+
+use crate::tree::Delimiter;
+use crate::{lines, tokenize_with_offsets, LiteralKind, RawStrError, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Whether an input is lexically complete, see [`input_state`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum InputState {
+    /// The input can be handed to the parser. It may still contain errors.
+    Complete,
+    /// The input is incomplete and the user should be asked for more.
+    NeedsMore(Incomplete),
+}
+
+/// Why an input is incomplete.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Incomplete {
+    /// The delimiter opened at byte offset `open` is not closed.
+    UnclosedDelimiter { delim: Delimiter, open: usize },
+    /// The literal or block comment starting at `token_start` is not
+    /// terminated.
+    UnterminatedToken { kind: TokenKind, token_start: usize },
+    /// The input ends with the start of a raw string like `r##`, which is
+    /// missing its opening quote.
+    RawStrPrefix { token_start: usize },
+}
+
+/// Checks whether `src` is lexically complete: all delimiters are closed and
+/// it doesn't end inside of a literal or block comment.
+///
+/// Inputs with mismatched or unexpected closing delimiters are considered
+/// complete, as more input cannot fix them.
+pub fn input_state(src: &str) -> InputState {
+    let mut open_delims = Vec::new();
+    let mut last = None;
+    for (range, kind) in tokenize_with_offsets(src) {
+        last = Some((kind, range.start));
+        if let Some(delim) = Delimiter::from_open(kind) {
+            open_delims.push((delim, range.start));
+        } else if let Some(delim) = Delimiter::from_close(kind) {
+            match open_delims.pop() {
+                Some((open, _)) if open == delim => {}
+                _ => return InputState::Complete,
+            }
+        }
+    }
+    if let Some((kind, token_start)) = last {
+        if let TokenKind::Literal {
+            kind:
+                LiteralKind::RawStr { err: Some(RawStrError::InvalidStarter { .. }), .. }
+                | LiteralKind::RawByteStr { err: Some(RawStrError::InvalidStarter { .. }), .. }
+                | LiteralKind::RawCStr { err: Some(RawStrError::InvalidStarter { .. }), .. },
+            ..
+        } = kind
+        {
+            if src[token_start..].trim_end_matches('#').ends_with('r') {
+                return InputState::NeedsMore(Incomplete::RawStrPrefix { token_start });
+            }
+        }
+        if !lines::is_terminated(kind) {
+            return InputState::NeedsMore(Incomplete::UnterminatedToken { kind, token_start });
+        }
+    }
+    match open_delims.pop() {
+        Some((delim, open)) => InputState::NeedsMore(Incomplete::UnclosedDelimiter { delim, open }),
+        None => InputState::Complete,
+    }
+}
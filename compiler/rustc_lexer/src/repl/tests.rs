@@ -0,0 +1,57 @@
+use super::*;
+
+fn needs_more(src: &str) -> Incomplete {
+    match input_state(src) {
+        InputState::NeedsMore(reason) => reason,
+        InputState::Complete => panic!("{:?} is complete", src),
+    }
+}
+
+#[test]
+fn test_complete() {
+    for src in ["", "let x = 1;", "fn main() { println!(\"{}\", [1][0]); }", "r#\"a\"#", "x)", "(]"]
+    {
+        assert_eq!(input_state(src), InputState::Complete, "{:?}", src);
+    }
+}
+
+#[test]
+fn test_unclosed_delimiter() {
+    assert_eq!(
+        needs_more("fn main() {\n    let v = vec![1,"),
+        Incomplete::UnclosedDelimiter { delim: Delimiter::Bracket, open: 28 }
+    );
+    assert_eq!(
+        needs_more("fn main() {"),
+        Incomplete::UnclosedDelimiter { delim: Delimiter::Brace, open: 10 }
+    );
+}
+
+#[test]
+fn test_unterminated_token() {
+    assert_eq!(
+        needs_more("let s = \"abc\n"),
+        Incomplete::UnterminatedToken {
+            kind: TokenKind::Literal {
+                kind: LiteralKind::Str { terminated: false, first_newline: Some(4) },
+                suffix_start: 5
+            },
+            token_start: 8
+        }
+    );
+    assert!(matches!(
+        needs_more("{ /* a /* b */"),
+        Incomplete::UnterminatedToken { kind: TokenKind::BlockComment { .. }, token_start: 2 }
+    ));
+    assert!(matches!(
+        needs_more("let s = r#\"a\"\"\n"),
+        Incomplete::UnterminatedToken { token_start: 8, .. }
+    ));
+}
+
+#[test]
+fn test_raw_str_prefix() {
+    assert_eq!(needs_more("let s = r#"), Incomplete::RawStrPrefix { token_start: 8 });
+    assert_eq!(needs_more("let s = br##"), Incomplete::RawStrPrefix { token_start: 8 });
+    assert_eq!(input_state("let s = r#~"), InputState::Complete);
+}
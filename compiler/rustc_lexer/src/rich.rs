@@ -0,0 +1,174 @@
+//! Rich tokens, the equivalent of what `rustc_parse`'s `StringReader` hands
+//! to the parser, for parsers which don't depend on the rest of the compiler.
+//!
+//! Compared to [`Token`](crate::Token), a [`RichToken`] carries its interned
+//! text, multi-char operators are glued, trivia is gone, doc comments are
+//! stripped of their markers and literals are split into their contents and
+//! suffix. All problems are collected as [`LexDiagnostic`]s on the side.
+
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::pipeline::Stage;
+use crate::{
+    lex_diagnostics, split_suffix, tokenize, DocStyle, LexDiagnostic, LiteralKind, TokenKind,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// The chars of all single-char operators, see [`RichTokenKind::Op`].
+const OP_CHARS: &str = ";,.(){}[]@#~?:$=!<>-&|+*/^%";
+
+/// An interned string, see [`Interner`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+/// Maps strings to [`Symbol`]s, which are cheap to copy and compare.
+#[derive(Default)]
+pub struct Interner {
+    symbols: BTreeMap<Box<str>, Symbol>,
+    strings: Vec<Box<str>>,
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        Interner::default()
+    }
+
+    pub fn intern(&mut self, string: &str) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(string) {
+            return symbol;
+        }
+        let symbol = Symbol(self.strings.len() as u32);
+        self.strings.push(string.into());
+        self.symbols.insert(string.into(), symbol);
+        symbol
+    }
+
+    /// Returns the string of `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `symbol` was interned by a different interner.
+    pub fn get(&self, symbol: Symbol) -> &str {
+        &self.strings[symbol.0 as usize]
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum RichTokenKind {
+    /// An identifier or keyword. Identifiers with invalid chars and unknown
+    /// prefixes are reported as errors and lexed as identifiers, like rustc
+    /// does.
+    Ident { symbol: Symbol, is_raw: bool },
+    /// A lifetime, the symbol includes the `'`.
+    Lifetime(Symbol),
+    /// A literal. `symbol` is the text without the quotes or hashes, but with
+    /// escapes not yet unescaped; for numbers it is the whole number
+    /// including its base prefix.
+    Literal { kind: LiteralKind, symbol: Symbol, suffix: Option<Symbol> },
+    /// An operator or delimiter, with multi-char operators like `::` glued.
+    Op(&'static str),
+    /// A doc comment, the symbol is its text without the comment markers.
+    DocComment { style: DocStyle, is_block: bool, symbol: Symbol },
+    /// A char which doesn't start any token.
+    Unknown(char),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RichToken {
+    pub kind: RichTokenKind,
+    /// Byte offset of the token in the input.
+    pub start: usize,
+    pub len: usize,
+    /// Whether the token is an operator directly followed by another
+    /// operator, like rustc's `Spacing::Joint`.
+    pub joint: bool,
+}
+
+impl RichToken {
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.start + self.len
+    }
+}
+
+/// The result of [`rich_tokens`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RichTokens {
+    pub tokens: Vec<RichToken>,
+    /// All problems found in the input, see [`lex_diagnostics`].
+    pub errors: Vec<LexDiagnostic>,
+}
+
+/// Lexes `input` into rich tokens, interning their text into `interner`.
+pub fn rich_tokens(input: &str, interner: &mut Interner) -> RichTokens {
+    let mut tokens: Vec<RichToken> = Vec::new();
+    for lexeme in tokenize(input).lexemes().attach_trivia().glued() {
+        let text = &input[lexeme.range.clone()];
+        let kind = match lexeme.kind {
+            kind if kind.is_trivia() => unreachable!("trivia is removed"),
+            TokenKind::LineComment { doc_style: Some(style) } => RichTokenKind::DocComment {
+                style,
+                is_block: false,
+                symbol: interner.intern(&text[3..]),
+            },
+            TokenKind::BlockComment { doc_style: Some(style), terminated } => {
+                let end = if terminated { text.len() - 2 } else { text.len() };
+                RichTokenKind::DocComment {
+                    style,
+                    is_block: true,
+                    symbol: interner.intern(&text[3..end.max(3)]),
+                }
+            }
+            TokenKind::Ident | TokenKind::InvalidIdent | TokenKind::UnknownPrefix { .. } => {
+                RichTokenKind::Ident { symbol: interner.intern(text), is_raw: false }
+            }
+            TokenKind::RawIdent => {
+                RichTokenKind::Ident { symbol: interner.intern(&text[2..]), is_raw: true }
+            }
+            TokenKind::Lifetime { .. } | TokenKind::RawLifetime => {
+                RichTokenKind::Lifetime(interner.intern(text))
+            }
+            TokenKind::Literal { kind, suffix_start } => {
+                let contents = match kind {
+                    LiteralKind::Int { .. } | LiteralKind::Float { .. } => 0..suffix_start,
+                    _ => kind.content_range(text, suffix_start),
+                };
+                let (_, suffix) = split_suffix(text, suffix_start);
+                RichTokenKind::Literal {
+                    kind,
+                    symbol: interner.intern(&text[contents]),
+                    suffix: suffix.map(|suffix| interner.intern(suffix)),
+                }
+            }
+            TokenKind::PrefixedLiteral { .. } => unreachable!("never produced when lexing Rust"),
+            TokenKind::GuardedStr { .. } => unreachable!("only produced for Rust 2024"),
+            TokenKind::Unknown => RichTokenKind::Unknown(text.chars().next().unwrap()),
+            _ => RichTokenKind::Op(op_str(text)),
+        };
+        if let Some(prev) = tokens.last_mut() {
+            prev.joint = matches!(prev.kind, RichTokenKind::Op(_))
+                && matches!(kind, RichTokenKind::Op(_))
+                && prev.start + prev.len == lexeme.range.start;
+        }
+        tokens.push(RichToken {
+            kind,
+            start: lexeme.range.start,
+            len: lexeme.range.len(),
+            joint: false,
+        });
+    }
+    RichTokens { tokens, errors: lex_diagnostics(input) }
+}
+
+/// Returns the static string of the operator `text`.
+fn op_str(text: &str) -> &'static str {
+    if let Some(&op) = crate::pipeline::GLUED_PUNCT.iter().find(|&&op| op == text) {
+        return op;
+    }
+    let idx = OP_CHARS.find(text).expect("operator");
+    &OP_CHARS[idx..idx + 1]
+}
@@ -0,0 +1,102 @@
+use super::*;
+use expect_test::{expect, Expect};
+
+fn check(input: &str, expect: Expect) {
+    let mut interner = Interner::new();
+    let RichTokens { tokens, errors } = rich_tokens(input, &mut interner);
+    let mut actual = String::new();
+    for token in tokens {
+        let kind = match token.kind {
+            RichTokenKind::Ident { symbol, is_raw } => {
+                format!("Ident {:?} is_raw={}", interner.get(symbol), is_raw)
+            }
+            RichTokenKind::Lifetime(symbol) => format!("Lifetime {:?}", interner.get(symbol)),
+            RichTokenKind::Literal { kind, symbol, suffix } => format!(
+                "Literal {:?} {:?} suffix={:?}",
+                kind,
+                interner.get(symbol),
+                suffix.map(|suffix| interner.get(suffix))
+            ),
+            RichTokenKind::Op(op) => format!("Op {:?}", op),
+            RichTokenKind::DocComment { style, is_block, symbol } => {
+                format!("DocComment {:?} is_block={} {:?}", style, is_block, interner.get(symbol))
+            }
+            RichTokenKind::Unknown(c) => format!("Unknown {:?}", c),
+        };
+        actual += &format!("{} {:?} joint={}\n", kind, token.range(), token.joint);
+    }
+    for error in errors {
+        actual += &format!("error {:?} {:?}\n", error.message_key, error.range);
+    }
+    expect.assert_eq(&actual);
+}
+
+#[test]
+fn test_rich_tokens() {
+    check(
+        "/// doc\nfn r#f<'a>(x: &'a u8) -> u8 { x.0 +=1_u8; }",
+        expect![[r#"
+            DocComment Outer is_block=false " doc" 0..7 joint=false
+            Ident "fn" is_raw=false 8..10 joint=false
+            Ident "f" is_raw=true 11..14 joint=false
+            Op "<" 14..15 joint=false
+            Lifetime "'a" 15..17 joint=false
+            Op ">" 17..18 joint=true
+            Op "(" 18..19 joint=false
+            Ident "x" is_raw=false 19..20 joint=false
+            Op ":" 20..21 joint=false
+            Op "&" 22..23 joint=false
+            Lifetime "'a" 23..25 joint=false
+            Ident "u8" is_raw=false 26..28 joint=false
+            Op ")" 28..29 joint=false
+            Op "->" 30..32 joint=false
+            Ident "u8" is_raw=false 33..35 joint=false
+            Op "{" 36..37 joint=false
+            Ident "x" is_raw=false 38..39 joint=false
+            Op "." 39..40 joint=false
+            Literal Int { base: Decimal, empty_int: None, underscore_after_prefix: false } "0" suffix=None 40..41 joint=false
+            Op "+=" 42..44 joint=false
+            Literal Int { base: Decimal, empty_int: None, underscore_after_prefix: false } "1_" suffix=Some("u8") 44..48 joint=false
+            Op ";" 48..49 joint=false
+            Op "}" 50..51 joint=false
+        "#]],
+    );
+}
+
+#[test]
+fn test_literals() {
+    check(
+        r###""a\n"suf b"b" r#"raw"# 0x1F_u32 1.5e3f64 'c' /** block */"###,
+        expect![[r#"
+            Literal Str { terminated: true, first_newline: None } "a\\n" suffix=Some("suf") 0..8 joint=false
+            Literal ByteStr { terminated: true, first_newline: None } "b" suffix=None 9..13 joint=false
+            Literal RawStr { n_hashes: 1, err: None, first_newline: None } "raw" suffix=None 14..22 joint=false
+            Literal Int { base: Hexadecimal, empty_int: None, underscore_after_prefix: false } "0x1F_" suffix=Some("u32") 23..31 joint=false
+            Literal Float { base: Decimal, empty_exponent: None, hex_exponent: None } "1.5e3" suffix=Some("f64") 32..40 joint=false
+            Literal Char { terminated: true } "c" suffix=None 41..44 joint=false
+            DocComment Outer is_block=true " block " 45..57 joint=false
+        "#]],
+    );
+}
+
+#[test]
+fn test_errors() {
+    check(
+        "'abc \"x",
+        expect![[r#"
+        Lifetime "'abc" 0..4 joint=false
+        Literal Str { terminated: false, first_newline: None } "x" suffix=None 5..7 joint=false
+        error "lex-unterminated-str" 5..7
+    "#]],
+    );
+}
+
+#[test]
+fn test_interner() {
+    let mut interner = Interner::new();
+    let a = interner.intern("a");
+    let b = interner.intern("b");
+    assert_ne!(a, b);
+    assert_eq!(interner.intern("a"), a);
+    assert_eq!(interner.get(b), "b");
+}
@@ -0,0 +1,588 @@
+//! A machine-readable description of the token grammar.
+//!
+//! [`state_machine`] describes the lexer as a deterministic state machine, for
+//! lexers of other platforms to be generated from, or checked against. A
+//! token is lexed by starting in state 0 and taking transitions while one
+//! applies: the transitions of a state are tried in order, and the first one
+//! whose class contains the next char, and none of whose `not_followed_by`
+//! classes contains the char after that, is taken. The token is the longest
+//! prefix which ends in a state with an `accept` kind.
+//!
+//! Raw strings and block comments are not regular. Entering a state with a
+//! `scanner` ends the description of the token: its length is determined by
+//! running the named scanner from the start of the token.
+//!
+//! - `raw_string`: after `r`, `br` or `cr`, counts the `#`s before the opening
+//!   quote and ends after a quote followed by the same number of `#`s.
+//! - `block_comment`: ends after the `*/` matching the opening `/*`, where
+//!   each nested `/*` needs its own `*/`.
+//!
+//! The description doesn't cover reserved prefixes like `foo#`, which are
+//! lexed as [`UnknownPrefix`](crate::TokenKind::UnknownPrefix), and
+//! identifiers containing emoji, which are lexed as
+//! [`InvalidIdent`](crate::TokenKind::InvalidIdent).
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::{is_id_continue, is_id_start, is_whitespace, Cursor};
+
+pub mod textmate;
+
+#[cfg(test)]
+mod tests;
+
+/// A set of chars a transition applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CharClass {
+    Char(char),
+    /// An inclusive range of chars.
+    Range(char, char),
+    /// See [`is_whitespace`].
+    Whitespace,
+    /// See [`is_id_start`].
+    IdStart,
+    /// See [`is_id_continue`].
+    IdContinue,
+    /// Any char but the given ones.
+    AnyExcept(&'static [char]),
+}
+
+impl CharClass {
+    pub fn contains(&self, c: char) -> bool {
+        match *self {
+            CharClass::Char(expected) => c == expected,
+            CharClass::Range(start, end) => (start..=end).contains(&c),
+            CharClass::Whitespace => is_whitespace(c),
+            CharClass::IdStart => is_id_start(c),
+            CharClass::IdContinue => is_id_continue(c),
+            CharClass::AnyExcept(chars) => !chars.contains(&c),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct State {
+    pub name: &'static str,
+    /// The kind of token lexed when the token ends in this state, e.g.
+    /// `"Ident"` or `"Literal.Str"`, named after the
+    /// [`TokenKind`](crate::TokenKind) and [`LiteralKind`](crate::LiteralKind)
+    /// variants.
+    pub accept: Option<&'static str>,
+    /// The scanner determining the rest of the token, see the
+    /// [module docs](self).
+    pub scanner: Option<&'static str>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Transition {
+    pub from: usize,
+    pub to: usize,
+    pub class: CharClass,
+    /// Classes the char after the one matched by `class` must not be in.
+    pub not_followed_by: &'static [CharClass],
+}
+
+/// See [`state_machine`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StateMachine {
+    pub states: Vec<State>,
+    /// The transitions, ordered by priority.
+    pub transitions: Vec<Transition>,
+}
+
+const DIGIT: CharClass = CharClass::Range('0', '9');
+const UNDERSCORE: CharClass = CharClass::Char('_');
+const HEX_DIGIT: [CharClass; 3] = [DIGIT, CharClass::Range('a', 'f'), CharClass::Range('A', 'F')];
+const ANY: CharClass = CharClass::AnyExcept(&[]);
+
+/// One-char tokens.
+const PUNCT: &[(char, &str)] = &[
+    (';', "Semi"),
+    (',', "Comma"),
+    ('.', "Dot"),
+    ('(', "OpenParen"),
+    (')', "CloseParen"),
+    ('{', "OpenBrace"),
+    ('}', "CloseBrace"),
+    ('[', "OpenBracket"),
+    (']', "CloseBracket"),
+    ('@', "At"),
+    ('#', "Pound"),
+    ('~', "Tilde"),
+    ('?', "Question"),
+    (':', "Colon"),
+    ('$', "Dollar"),
+    ('=', "Eq"),
+    ('!', "Bang"),
+    ('<', "Lt"),
+    ('>', "Gt"),
+    ('-', "Minus"),
+    ('&', "And"),
+    ('|', "Or"),
+    ('+', "Plus"),
+    ('*', "Star"),
+    ('^', "Caret"),
+    ('%', "Percent"),
+];
+
+struct Builder {
+    states: Vec<State>,
+    transitions: Vec<Transition>,
+}
+
+impl Builder {
+    fn state(&mut self, name: &'static str, accept: Option<&'static str>) -> usize {
+        self.states.push(State { name, accept, scanner: None });
+        self.states.len() - 1
+    }
+
+    fn scanner(
+        &mut self,
+        name: &'static str,
+        accept: &'static str,
+        scanner: &'static str,
+    ) -> usize {
+        self.states.push(State { name, accept: Some(accept), scanner: Some(scanner) });
+        self.states.len() - 1
+    }
+
+    fn add(&mut self, from: usize, class: CharClass, to: usize) {
+        self.add_unless(from, class, &[], to);
+    }
+
+    fn add_unless(
+        &mut self,
+        from: usize,
+        class: CharClass,
+        not_followed_by: &'static [CharClass],
+        to: usize,
+    ) {
+        self.transitions.push(Transition { from, to, class, not_followed_by });
+    }
+
+    /// Adds a state for the suffix of literals of the given kind.
+    fn suffix(&mut self, name: &'static str, kind: &'static str) -> usize {
+        let suffix = self.state(name, Some(kind));
+        self.add(suffix, CharClass::IdContinue, suffix);
+        suffix
+    }
+
+    /// Adds the states of a double quoted string after its opening quote.
+    fn string(&mut self, open: usize, names: [&'static str; 4], kind: &'static str) {
+        let [body_name, escape_name, close_name, suffix_name] = names;
+        // Unterminated strings extend to the end of input.
+        let body = self.state(body_name, Some(kind));
+        let escape = self.state(escape_name, Some(kind));
+        let close = self.state(close_name, Some(kind));
+        let suffix = self.suffix(suffix_name, kind);
+        for from in [open, body] {
+            self.add(from, CharClass::Char('"'), close);
+            self.add(from, CharClass::Char('\\'), escape);
+            self.add(from, ANY, body);
+        }
+        self.add(escape, ANY, body);
+        self.add(close, CharClass::IdStart, suffix);
+    }
+
+    /// Adds the states of a single quoted literal after its opening quote,
+    /// returning the state after the closing quote.
+    fn quoted(&mut self, open: usize, names: [&'static str; 4], kind: &'static str) -> usize {
+        let [body_name, escape_name, close_name, suffix_name] = names;
+        let body = self.state(body_name, Some(kind));
+        let escape = self.state(escape_name, Some(kind));
+        let close = self.state(close_name, Some(kind));
+        let suffix = self.suffix(suffix_name, kind);
+        self.add(open, CharClass::Char('\''), close);
+        self.add(open, CharClass::Char('\\'), escape);
+        self.add(open, ANY, body);
+        self.add(body, CharClass::Char('\''), close);
+        self.add(body, CharClass::Char('\\'), escape);
+        // An unterminated literal ends at the end of the line or at a `/`,
+        // which likely starts a comment.
+        self.add(body, CharClass::AnyExcept(&['\n', '/']), body);
+        self.add(escape, ANY, body);
+        self.add(close, CharClass::IdStart, suffix);
+        close
+    }
+}
+
+/// Describes the lexer as a state machine, see the [module docs](self).
+pub fn state_machine() -> StateMachine {
+    let mut b = Builder { states: Vec::new(), transitions: Vec::new() };
+    let start = b.state("start", None);
+
+    // Comments and `/`.
+    let slash = b.state("slash", Some("Slash"));
+    let line_comment = b.state("line_comment", Some("LineComment"));
+    let block_comment = b.scanner("block_comment", "BlockComment", "block_comment");
+    b.add(start, CharClass::Char('/'), slash);
+    b.add(slash, CharClass::Char('/'), line_comment);
+    b.add(slash, CharClass::Char('*'), block_comment);
+    b.add(line_comment, CharClass::AnyExcept(&['\n']), line_comment);
+
+    let whitespace = b.state("whitespace", Some("Whitespace"));
+    b.add(start, CharClass::Whitespace, whitespace);
+    b.add(whitespace, CharClass::Whitespace, whitespace);
+
+    // Identifiers and prefixed literals.
+    let ident = b.state("ident", Some("Ident"));
+    let r = b.state("r", Some("Ident"));
+    let r_hash = b.state("raw_ident_hash", None);
+    let raw_ident = b.state("raw_ident", Some("RawIdent"));
+    let raw_str = b.scanner("raw_str", "Literal.RawStr", "raw_string");
+    let prefix_b = b.state("b", Some("Ident"));
+    let br = b.state("br", Some("Ident"));
+    let raw_byte_str = b.scanner("raw_byte_str", "Literal.RawByteStr", "raw_string");
+    let byte_open = b.state("byte_open", Some("Literal.Byte"));
+    let byte_str_open = b.state("byte_str_open", Some("Literal.ByteStr"));
+    let prefix_c = b.state("c", Some("Ident"));
+    let cr = b.state("cr", Some("Ident"));
+    let raw_c_str = b.scanner("raw_c_str", "Literal.RawCStr", "raw_string");
+    let c_str_open = b.state("c_str_open", Some("Literal.CStr"));
+
+    b.add(start, CharClass::Char('r'), r);
+    b.add_unless(r, CharClass::Char('#'), &[CharClass::IdStart], raw_str);
+    b.add(r, CharClass::Char('#'), r_hash);
+    b.add(r, CharClass::Char('"'), raw_str);
+    b.add(r_hash, CharClass::IdStart, raw_ident);
+    b.add(raw_ident, CharClass::IdContinue, raw_ident);
+
+    b.add(start, CharClass::Char('b'), prefix_b);
+    b.add(prefix_b, CharClass::Char('\''), byte_open);
+    b.add(prefix_b, CharClass::Char('"'), byte_str_open);
+    b.add(prefix_b, CharClass::Char('r'), br);
+    b.add(br, CharClass::Char('#'), raw_byte_str);
+    b.add(br, CharClass::Char('"'), raw_byte_str);
+
+    b.add(start, CharClass::Char('c'), prefix_c);
+    b.add(prefix_c, CharClass::Char('"'), c_str_open);
+    b.add(prefix_c, CharClass::Char('r'), cr);
+    b.add(cr, CharClass::Char('#'), raw_c_str);
+    b.add(cr, CharClass::Char('"'), raw_c_str);
+
+    b.add(start, CharClass::IdStart, ident);
+    for from in [ident, r, prefix_b, br, prefix_c, cr] {
+        b.add(from, CharClass::IdContinue, ident);
+    }
+
+    // Strings, chars and lifetimes.
+    let str_open = b.state("str_open", Some("Literal.Str"));
+    b.add(start, CharClass::Char('"'), str_open);
+    b.string(str_open, ["str_body", "str_escape", "str_close", "str_suffix"], "Literal.Str");
+    b.string(
+        byte_str_open,
+        ["byte_str_body", "byte_str_escape", "byte_str_close", "byte_str_suffix"],
+        "Literal.ByteStr",
+    );
+    b.string(
+        c_str_open,
+        ["c_str_body", "c_str_escape", "c_str_close", "c_str_suffix"],
+        "Literal.CStr",
+    );
+    b.quoted(byte_open, ["byte_body", "byte_escape", "byte_close", "byte_suffix"], "Literal.Byte");
+
+    let quote = b.state("quote", Some("Literal.Char"));
+    let lifetime = b.state("lifetime", Some("Lifetime"));
+    let lifetime_r = b.state("lifetime_r", Some("Lifetime"));
+    let raw_lifetime_hash = b.state("raw_lifetime_hash", None);
+    let raw_lifetime = b.state("raw_lifetime", Some("RawLifetime"));
+    b.add(start, CharClass::Char('\''), quote);
+    b.add(quote, CharClass::Char('r'), lifetime_r);
+    b.add(quote, CharClass::IdStart, lifetime);
+    b.add(quote, DIGIT, lifetime);
+    let char_close =
+        b.quoted(quote, ["char_body", "char_escape", "char_close", "char_suffix"], "Literal.Char");
+    // `'ab'` is a char literal with several chars.
+    b.add(lifetime, CharClass::Char('\''), char_close);
+    b.add(lifetime, CharClass::IdContinue, lifetime);
+    b.add(lifetime_r, CharClass::Char('\''), char_close);
+    b.add(lifetime_r, CharClass::Char('#'), raw_lifetime_hash);
+    b.add(lifetime_r, CharClass::IdContinue, lifetime);
+    b.add(raw_lifetime_hash, CharClass::IdStart, raw_lifetime);
+    b.add(raw_lifetime, CharClass::IdContinue, raw_lifetime);
+
+    // Numbers. The digits of binary and octal numbers are not checked.
+    let int = Some("Literal.Int");
+    let zero = b.state("zero", int);
+    let decimal = b.state("decimal", int);
+    let binary = b.state("binary", int);
+    let octal = b.state("octal", int);
+    let hex_prefix = b.state("hexadecimal_prefix", int);
+    let hexadecimal = b.state("hexadecimal", int);
+    let int_suffix = b.suffix("int_suffix", "Literal.Int");
+    let float = Some("Literal.Float");
+    let dot = b.state("float_dot", float);
+    let fraction = b.state("float_fraction", float);
+    let exponent = b.state("float_exponent", float);
+    let exponent_sign = b.state("float_exponent_sign", float);
+    let exponent_digits = b.state("float_exponent_digits", float);
+    let float_suffix = b.suffix("float_suffix", "Literal.Float");
+
+    b.add(start, CharClass::Char('0'), zero);
+    b.add(start, CharClass::Range('1', '9'), decimal);
+    b.add(zero, CharClass::Char('b'), binary);
+    b.add(zero, CharClass::Char('o'), octal);
+    b.add(zero, CharClass::Char('x'), hex_prefix);
+    for (from, to) in [(zero, decimal), (decimal, decimal), (binary, binary), (octal, octal)] {
+        b.add(from, DIGIT, to);
+        b.add(from, UNDERSCORE, to);
+    }
+    for class in HEX_DIGIT {
+        b.add(hex_prefix, class, hexadecimal);
+        b.add(hexadecimal, class, hexadecimal);
+    }
+    b.add(hex_prefix, UNDERSCORE, hex_prefix);
+    b.add(hexadecimal, UNDERSCORE, hexadecimal);
+    for from in [zero, decimal, binary, octal] {
+        // `1.foo()` and `1..2` are not floats.
+        b.add_unless(from, CharClass::Char('.'), &[CharClass::Char('.'), CharClass::IdStart], dot);
+    }
+    for from in [zero, decimal, binary, octal, dot, fraction] {
+        b.add(from, CharClass::Char('e'), exponent);
+        b.add(from, CharClass::Char('E'), exponent);
+    }
+    b.add(dot, DIGIT, fraction);
+    b.add(fraction, DIGIT, fraction);
+    b.add(fraction, UNDERSCORE, fraction);
+    b.add(exponent, CharClass::Char('+'), exponent_sign);
+    b.add(exponent, CharClass::Char('-'), exponent_sign);
+    for from in [exponent, exponent_sign, exponent_digits] {
+        b.add(from, DIGIT, exponent_digits);
+        b.add(from, UNDERSCORE, exponent_digits);
+    }
+    hex_float(&mut b, hexadecimal, [exponent_sign, float_suffix]);
+    for from in [zero, decimal, binary, octal, hex_prefix, hexadecimal] {
+        b.add(from, CharClass::IdStart, int_suffix);
+    }
+    for from in [fraction, exponent, exponent_digits] {
+        b.add(from, CharClass::IdStart, float_suffix);
+    }
+
+    for &(c, kind) in PUNCT {
+        let state = b.state(kind, Some(kind));
+        b.add(start, CharClass::Char(c), state);
+    }
+
+    let unknown = b.state("unknown", Some("Unknown"));
+    b.add(start, ANY, unknown);
+
+    StateMachine { states: b.states, transitions: b.transitions }
+}
+
+/// Adds the states of hexadecimal floats with a binary exponent like
+/// `0x1.8p3`, which start after the integer digits.
+///
+/// Until the exponent has a digit, the token is what it would be without the
+/// exponent: an integer followed by a field access like `0x1.ap`, or a float
+/// with a suffix like `0x1.5ap`. Without a `.`, `0x1p3` is an integer with a
+/// suffix.
+fn hex_float(b: &mut Builder, hexadecimal: usize, states: [usize; 2]) {
+    let [exponent_sign, float_suffix] = states;
+    let float = Some("Literal.Float");
+    let hex_exponent_sign = b.state("hex_exponent_sign", None);
+    let hex_exponent_digits = b.state("hex_exponent_digits", float);
+    let dot = b.state("hex_float_dot", float);
+    let fraction = b.state("hex_float_fraction", float);
+    let fraction_e = b.state("hex_float_fraction_e", float);
+    let fraction_hex = b.state("hex_float_fraction_hex", float);
+    let float_exponent = b.state("hex_float_exponent", float);
+    let point = b.state("hex_point", None);
+    let point_digits = b.state("hex_point_digits", None);
+    let point_exponent = b.state("hex_point_exponent", None);
+    let exponent_start = [CharClass::Char('p'), CharClass::Char('P')];
+    let signs = [CharClass::Char('+'), CharClass::Char('-')];
+
+    // `0x1.5p3` and `0x1.` are floats even without the exponent, while
+    // `0x1.ap3` is an integer followed by a field access without it.
+    b.add_unless(
+        hexadecimal,
+        CharClass::Char('.'),
+        &[CharClass::Char('.'), CharClass::IdStart],
+        dot,
+    );
+    b.add_unless(hexadecimal, CharClass::Char('.'), &[CharClass::Char('.')], point);
+    b.add(dot, DIGIT, fraction);
+    for from in [fraction, fraction_e, fraction_hex] {
+        for class in exponent_start {
+            b.add(from, class, float_exponent);
+        }
+    }
+    b.add(fraction, DIGIT, fraction);
+    b.add(fraction, UNDERSCORE, fraction);
+    b.add(fraction, CharClass::Char('e'), fraction_e);
+    b.add(fraction, CharClass::Char('E'), fraction_e);
+    for class in signs {
+        // `0x1.5e+3` is a float with a decimal exponent.
+        b.add(fraction_e, class, exponent_sign);
+    }
+    for from in [fraction, fraction_e, fraction_hex] {
+        for class in HEX_DIGIT {
+            b.add(from, class, fraction_hex);
+        }
+    }
+    for from in [fraction_e, fraction_hex] {
+        b.add(from, UNDERSCORE, fraction_hex);
+    }
+    for class in HEX_DIGIT {
+        b.add(point, class, point_digits);
+        b.add(point_digits, class, point_digits);
+    }
+    b.add(point, UNDERSCORE, point);
+    b.add(point_digits, UNDERSCORE, point_digits);
+    for class in exponent_start {
+        b.add(point_digits, class, point_exponent);
+    }
+
+    for from in [float_exponent, point_exponent] {
+        b.add(from, DIGIT, hex_exponent_digits);
+        for class in signs {
+            b.add(from, class, hex_exponent_sign);
+        }
+    }
+    b.add(hex_exponent_sign, DIGIT, hex_exponent_digits);
+    b.add(hex_exponent_digits, DIGIT, hex_exponent_digits);
+    b.add(hex_exponent_digits, UNDERSCORE, hex_exponent_digits);
+    b.add(hex_exponent_digits, CharClass::IdStart, float_suffix);
+    for from in [fraction, fraction_e, fraction_hex, float_exponent] {
+        b.add(from, CharClass::IdContinue, float_suffix);
+    }
+}
+
+impl StateMachine {
+    /// Lexes `input` according to the description, returning the kind and
+    /// length of each token. For inputs covered by the description, this
+    /// produces the same tokens as [`tokenize`](crate::tokenize).
+    pub fn tokenize(&self, input: &str) -> Vec<(&'static str, usize)> {
+        let mut res = Vec::new();
+        let mut pos = 0;
+        while pos < input.len() {
+            let rest = &input[pos..];
+            let (mut state, mut len) = (0, 0);
+            let mut accepted = None;
+            loop {
+                let current = &self.states[state];
+                if current.scanner.is_some() {
+                    // The scanners are those of the lexer itself.
+                    let len = Cursor::new(rest).advance_token().len;
+                    accepted = Some((current.accept.unwrap(), len));
+                    break;
+                }
+                if let Some(kind) = current.accept {
+                    accepted = Some((kind, len));
+                }
+                let mut chars = rest[len..].chars();
+                let c = match chars.next() {
+                    Some(c) => c,
+                    None => break,
+                };
+                let next = chars.next();
+                let transition = self.transitions.iter().find(|t| {
+                    t.from == state
+                        && t.class.contains(c)
+                        && !matches!(next, Some(next)
+                            if t.not_followed_by.iter().any(|class| class.contains(next)))
+                });
+                match transition {
+                    Some(transition) => {
+                        state = transition.to;
+                        len += c.len_utf8();
+                    }
+                    None => break,
+                }
+            }
+            let (kind, len) = accepted.expect("the start state only leads to accepting states");
+            res.push((kind, len));
+            pos += len;
+        }
+        res
+    }
+
+    /// Serializes the description as JSON, of the form
+    ///
+    /// ```text
+    /// {
+    ///   "states": [{"name": "start", "accept": null, "scanner": null}, ...],
+    ///   "transitions": [{"from": 0, "to": 1, "class": {"char": "/"}, "not_followed_by": []}, ...]
+    /// }
+    /// ```
+    ///
+    /// Classes are written as `{"char": "a"}`, `{"range": ["0", "9"]}`,
+    /// `{"any_except": ["\n"]}`, or `{"named": n}` where `n` is `"whitespace"`,
+    /// `"id_start"` or `"id_continue"`.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("{\n  \"states\": [\n");
+        for (i, state) in self.states.iter().enumerate() {
+            out.push_str("    {\"name\": ");
+            push_json_str(&mut out, state.name);
+            out.push_str(", \"accept\": ");
+            push_json_opt_str(&mut out, state.accept);
+            out.push_str(", \"scanner\": ");
+            push_json_opt_str(&mut out, state.scanner);
+            out.push('}');
+            out.push_str(if i + 1 < self.states.len() { ",\n" } else { "\n" });
+        }
+        out.push_str("  ],\n  \"transitions\": [\n");
+        for (i, transition) in self.transitions.iter().enumerate() {
+            out.push_str(&format!(
+                "    {{\"from\": {}, \"to\": {}, \"class\": ",
+                transition.from, transition.to
+            ));
+            push_json_class(&mut out, &transition.class);
+            out.push_str(", \"not_followed_by\": [");
+            for (j, class) in transition.not_followed_by.iter().enumerate() {
+                if j > 0 {
+                    out.push_str(", ");
+                }
+                push_json_class(&mut out, class);
+            }
+            out.push_str("]}");
+            out.push_str(if i + 1 < self.transitions.len() { ",\n" } else { "\n" });
+        }
+        out.push_str("  ]\n}\n");
+        out
+    }
+}
+
+fn push_json_class(out: &mut String, class: &CharClass) {
+    match *class {
+        CharClass::Char(c) => {
+            out.push_str("{\"char\": ");
+            push_json_str(out, c.encode_utf8(&mut [0; 4]));
+        }
+        CharClass::Range(start, end) => {
+            out.push_str("{\"range\": [");
+            push_json_str(out, start.encode_utf8(&mut [0; 4]));
+            out.push_str(", ");
+            push_json_str(out, end.encode_utf8(&mut [0; 4]));
+            out.push(']');
+        }
+        CharClass::Whitespace => out.push_str("{\"named\": \"whitespace\""),
+        CharClass::IdStart => out.push_str("{\"named\": \"id_start\""),
+        CharClass::IdContinue => out.push_str("{\"named\": \"id_continue\""),
+        CharClass::AnyExcept(chars) => {
+            out.push_str("{\"any_except\": [");
+            for (i, c) in chars.iter().enumerate() {
+                if i > 0 {
+                    out.push_str(", ");
+                }
+                push_json_str(out, c.encode_utf8(&mut [0; 4]));
+            }
+            out.push(']');
+        }
+    }
+    out.push('}');
+}
+
+fn push_json_opt_str(out: &mut String, s: Option<&str>) {
+    match s {
+        Some(s) => push_json_str(out, s),
+        None => out.push_str("null"),
+    }
+}
+
+fn push_json_str(out: &mut String, s: &str) {
+    // Writing to a `String` can't fail.
+    crate::json::write_json_str(s, out).unwrap();
+}
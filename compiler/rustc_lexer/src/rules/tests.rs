@@ -0,0 +1,57 @@
+use super::*;
+use crate::{tokenize, TokenKind};
+
+/// Names a token kind the way the state machine does.
+fn kind_name(kind: TokenKind) -> String {
+    let name = |debug: String| debug.split([' ', '{']).next().unwrap().to_string();
+    match kind {
+        TokenKind::Literal { kind, .. } => format!("Literal.{}", name(format!("{:?}", kind))),
+        kind => name(format!("{:?}", kind)),
+    }
+}
+
+#[test]
+fn test_matches_lexer() {
+    let machine = state_machine();
+    let inputs = [
+        "fn main() { println!(\"Hello, {}!\", r#\"world\"#); }",
+        "let x: u8 = 0b1010_u8 + 0o17 + 0xfF_u8 + 1_000 - 2.5e-3f32 * 1e10 / 3.;",
+        "a.0.1 + 1..2 + 1.foo() + 0x1.0 + 0b1e3 + 1e_ + 1ex",
+        "0x1p3 0x1.8p-3f32 0x1.p3 0x1.ap+_1 0x1.5a 0x1.5e+3 0x1.5ap+ 0x1.a 0x1p+ 0xp3 0x.5 0x_1P3",
+        "0x1.8e 0x1.8e3p1_x 0x1.8ap 0x1.8_e_p1 0x1.p 0x1.ab.c 0x1.5e3 0x1._p3 0x1._ap3",
+        "'a 'static 'a' '\\n' '\\u{1F980}' b'x' b'\\'' 'ab' '1",
+        "'r#fn 'r#a'b 'r 'r# 'r#1 'r' 'rb",
+        "\"a\\\"b\" b\"\\x00\" br##\"x\"#\"## r\"\"suffix \"s\"_x",
+        "// line\n/// doc\n//! inner\n/* block /* nested */ */ /** doc */",
+        "r#ident r#\"raw\"# br\"bytes\" b rb bra r ident_1 _ é",
+        "c\"a\\x80\\0\" cr#\"b\"# cr\"c\"_x c cr cra",
+        "#[attr] $x @ ~ ? : ; , = ! < > - & | + * / ^ % № \u{a0}",
+        "/* unterminated",
+        "\"unterminated",
+        "r##\"unterminated\"#",
+    ];
+    for input in inputs {
+        let expected: Vec<_> =
+            tokenize(input).map(|token| (kind_name(token.kind), token.len)).collect();
+        let actual: Vec<_> = machine
+            .tokenize(input)
+            .into_iter()
+            .map(|(kind, len)| (kind.to_string(), len))
+            .collect();
+        assert_eq!(actual, expected, "{:?}", input);
+    }
+}
+
+#[test]
+fn test_to_json() {
+    let json = state_machine().to_json();
+    assert!(json.starts_with(
+        "{\n  \"states\": [\n    {\"name\": \"start\", \"accept\": null, \"scanner\": null},\n"
+    ));
+    assert!(json.contains(
+        "{\"name\": \"raw_str\", \"accept\": \"Literal.RawStr\", \"scanner\": \"raw_string\"}"
+    ));
+    assert!(json.contains("\"class\": {\"any_except\": [\"\\n\"]}"));
+    assert!(json.contains("\"not_followed_by\": [{\"char\": \".\"}, {\"named\": \"id_start\"}]"));
+    assert!(json.ends_with("  ]\n}\n"));
+}
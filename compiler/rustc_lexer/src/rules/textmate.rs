@@ -0,0 +1,217 @@
+//! Generation of a TextMate grammar for the tokens which hand-written editor
+//! grammars usually get wrong: strings with their escapes, raw strings
+//! delimited by any number of `#`s, nested block comments and numeric
+//! literals.
+//!
+//! Regular expressions use the Oniguruma syntax understood by TextMate
+//! grammar engines. Lookaheads and char classes are taken from
+//! [`state_machine`](super::state_machine), so the grammar follows changes to
+//! the lexer rules.
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use alloc::{format, vec};
+
+use super::{push_json_str, state_machine, CharClass};
+
+#[cfg(test)]
+mod tests;
+
+/// Suffixes of integer literals.
+const INT_SUFFIX: &str = "(?:[iu](?:8|16|32|64|128|size))?";
+
+/// Suffixes of float literals.
+const FLOAT_SUFFIX: &str = "(?:f32|f64)?";
+
+/// The escapes valid in strings and chars.
+const ESCAPE: &str = r#"\\(?:[nrt\\0'"]|x[0-9A-Fa-f]{2}|u\{[0-9A-Fa-f_]{1,6}\})"#;
+
+/// A rule of the grammar's repository.
+enum Rule {
+    Match { scope: &'static str, regex: String },
+    BeginEnd { scope: &'static str, begin: String, end: String, includes: &'static [&'static str] },
+    Include(&'static [&'static str]),
+}
+
+/// Returns a TextMate grammar with scope `source.rust`, as JSON.
+pub fn textmate_grammar() -> String {
+    let ident =
+        format!("{}{}*", class_regex(&CharClass::IdStart), class_regex(&CharClass::IdContinue));
+
+    let rules: Vec<(&str, Rule)> = vec![
+        ("comments", Rule::Include(&["doc_comment", "line_comment", "block_comment"])),
+        (
+            "doc_comment",
+            Rule::Match {
+                scope: "comment.line.documentation.rust",
+                regex: "//(?:!|/(?!/)).*$".to_string(),
+            },
+        ),
+        (
+            "line_comment",
+            Rule::Match { scope: "comment.line.double-slash.rust", regex: "//.*$".to_string() },
+        ),
+        (
+            "block_comment",
+            Rule::BeginEnd {
+                scope: "comment.block.rust",
+                begin: r"/\*".to_string(),
+                end: r"\*/".to_string(),
+                // Block comments nest.
+                includes: &["block_comment"],
+            },
+        ),
+        ("strings", Rule::Include(&["raw_string", "string", "char", "lifetime"])),
+        (
+            "raw_string",
+            Rule::BeginEnd {
+                scope: "string.quoted.double.raw.rust",
+                // The closing quote has to be followed by as many `#`s as
+                // the opening one is preceded by.
+                begin: r#"\b(b?r)(#*)""#.to_string(),
+                end: format!(r#""\2(?:{})?"#, ident),
+                includes: &[],
+            },
+        ),
+        (
+            "string",
+            Rule::BeginEnd {
+                scope: "string.quoted.double.rust",
+                begin: r#"b?""#.to_string(),
+                end: format!(r#""(?:{})?"#, ident),
+                includes: &["escape"],
+            },
+        ),
+        (
+            "escape",
+            Rule::Match {
+                scope: "constant.character.escape.rust",
+                regex: format!("{}|\\\\$", ESCAPE),
+            },
+        ),
+        (
+            "char",
+            Rule::Match {
+                scope: "string.quoted.single.rust",
+                regex: format!(r"b?'(?:[^'\\\n]|{})'", ESCAPE),
+            },
+        ),
+        (
+            "lifetime",
+            Rule::Match {
+                scope: "storage.modifier.lifetime.rust",
+                regex: format!("'{}(?!')", ident),
+            },
+        ),
+        ("numbers", Rule::Include(&["float", "integer"])),
+        (
+            "float",
+            Rule::Match {
+                scope: "constant.numeric.float.rust",
+                regex: format!(
+                    r"\b[0-9][0-9_]*(?:\.[0-9][0-9_]*(?:[eE][+-]?_*[0-9][0-9_]*)?{s}|[eE][+-]?_*[0-9][0-9_]*{s}|\.{l})",
+                    s = FLOAT_SUFFIX,
+                    l = float_dot_lookahead(),
+                ),
+            },
+        ),
+        (
+            "integer",
+            Rule::Match {
+                scope: "constant.numeric.integer.rust",
+                regex: format!(
+                    r"\b(?:0x[0-9A-Fa-f_]+|0o[0-7_]+|0b[01_]+|[0-9][0-9_]*){}\b",
+                    INT_SUFFIX
+                ),
+            },
+        ),
+    ];
+
+    let mut out = String::from("{\n  \"name\": \"Rust\",\n  \"scopeName\": \"source.rust\",\n");
+    out.push_str("  \"patterns\": [");
+    push_includes(&mut out, &["comments", "strings", "numbers"]);
+    out.push_str("],\n  \"repository\": {\n");
+    for (i, (name, rule)) in rules.iter().enumerate() {
+        out.push_str("    ");
+        push_json_str(&mut out, name);
+        out.push_str(": {");
+        match rule {
+            Rule::Match { scope, regex } => {
+                out.push_str("\"name\": ");
+                push_json_str(&mut out, scope);
+                out.push_str(", \"match\": ");
+                push_json_str(&mut out, regex);
+            }
+            Rule::BeginEnd { scope, begin, end, includes } => {
+                out.push_str("\"name\": ");
+                push_json_str(&mut out, scope);
+                out.push_str(", \"begin\": ");
+                push_json_str(&mut out, begin);
+                out.push_str(", \"end\": ");
+                push_json_str(&mut out, end);
+                out.push_str(", \"patterns\": [");
+                push_includes(&mut out, includes);
+                out.push(']');
+            }
+            Rule::Include(includes) => {
+                out.push_str("\"patterns\": [");
+                push_includes(&mut out, includes);
+                out.push(']');
+            }
+        }
+        out.push('}');
+        out.push_str(if i + 1 < rules.len() { ",\n" } else { "\n" });
+    }
+    out.push_str("  }\n}\n");
+    out
+}
+
+fn push_includes(out: &mut String, names: &[&str]) {
+    for (i, name) in names.iter().enumerate() {
+        if i > 0 {
+            out.push_str(", ");
+        }
+        out.push_str("{\"include\": ");
+        push_json_str(out, &format!("#{}", name));
+        out.push('}');
+    }
+}
+
+/// The negative lookahead after the `.` of a float like `1.`, which keeps
+/// `1..2` and `1.foo()` from being floats.
+fn float_dot_lookahead() -> String {
+    let machine = state_machine();
+    let dot = machine.states.iter().position(|state| state.name == "float_dot").unwrap();
+    let transition = machine.transitions.iter().find(|t| t.to == dot).unwrap();
+    let classes: Vec<_> = transition.not_followed_by.iter().map(class_regex).collect();
+    format!("(?!{})", classes.join("|"))
+}
+
+/// Translates a char class into a regular expression matching one char.
+fn class_regex(class: &CharClass) -> String {
+    match *class {
+        CharClass::Char(c) => escape_char(c),
+        CharClass::Range(start, end) => format!("[{}-{}]", escape_char(start), escape_char(end)),
+        CharClass::Whitespace => r"\s".to_string(),
+        // Approximations of XID_Start and XID_Continue with general
+        // categories, which all Oniguruma versions support.
+        CharClass::IdStart => r"[\p{L}\p{Nl}_]".to_string(),
+        CharClass::IdContinue => r"[\p{L}\p{Nl}\p{Mn}\p{Mc}\p{Nd}\p{Pc}]".to_string(),
+        CharClass::AnyExcept(chars) => {
+            let chars: String = chars.iter().map(|&c| escape_char(c)).collect();
+            if chars.is_empty() {
+                "(?:.|\\n)".to_string()
+            } else {
+                format!("[^{}]", chars)
+            }
+        }
+    }
+}
+
+fn escape_char(c: char) -> String {
+    match c {
+        '\n' => r"\n".to_string(),
+        c if c.is_ascii_punctuation() => format!("\\{}", c),
+        c => c.to_string(),
+    }
+}
@@ -0,0 +1,33 @@
+use super::*;
+use expect_test::expect;
+
+#[test]
+fn test_float_dot_lookahead() {
+    assert_eq!(float_dot_lookahead(), r"(?!\.|[\p{L}\p{Nl}_])");
+}
+
+#[test]
+fn test_textmate_grammar() {
+    expect![[r##"
+        {
+          "name": "Rust",
+          "scopeName": "source.rust",
+          "patterns": [{"include": "#comments"}, {"include": "#strings"}, {"include": "#numbers"}],
+          "repository": {
+            "comments": {"patterns": [{"include": "#doc_comment"}, {"include": "#line_comment"}, {"include": "#block_comment"}]},
+            "doc_comment": {"name": "comment.line.documentation.rust", "match": "//(?:!|/(?!/)).*$"},
+            "line_comment": {"name": "comment.line.double-slash.rust", "match": "//.*$"},
+            "block_comment": {"name": "comment.block.rust", "begin": "/\\*", "end": "\\*/", "patterns": [{"include": "#block_comment"}]},
+            "strings": {"patterns": [{"include": "#raw_string"}, {"include": "#string"}, {"include": "#char"}, {"include": "#lifetime"}]},
+            "raw_string": {"name": "string.quoted.double.raw.rust", "begin": "\\b(b?r)(#*)\"", "end": "\"\\2(?:[\\p{L}\\p{Nl}_][\\p{L}\\p{Nl}\\p{Mn}\\p{Mc}\\p{Nd}\\p{Pc}]*)?", "patterns": []},
+            "string": {"name": "string.quoted.double.rust", "begin": "b?\"", "end": "\"(?:[\\p{L}\\p{Nl}_][\\p{L}\\p{Nl}\\p{Mn}\\p{Mc}\\p{Nd}\\p{Pc}]*)?", "patterns": [{"include": "#escape"}]},
+            "escape": {"name": "constant.character.escape.rust", "match": "\\\\(?:[nrt\\\\0'\"]|x[0-9A-Fa-f]{2}|u\\{[0-9A-Fa-f_]{1,6}\\})|\\\\$"},
+            "char": {"name": "string.quoted.single.rust", "match": "b?'(?:[^'\\\\\\n]|\\\\(?:[nrt\\\\0'\"]|x[0-9A-Fa-f]{2}|u\\{[0-9A-Fa-f_]{1,6}\\}))'"},
+            "lifetime": {"name": "storage.modifier.lifetime.rust", "match": "'[\\p{L}\\p{Nl}_][\\p{L}\\p{Nl}\\p{Mn}\\p{Mc}\\p{Nd}\\p{Pc}]*(?!')"},
+            "numbers": {"patterns": [{"include": "#float"}, {"include": "#integer"}]},
+            "float": {"name": "constant.numeric.float.rust", "match": "\\b[0-9][0-9_]*(?:\\.[0-9][0-9_]*(?:[eE][+-]?_*[0-9][0-9_]*)?(?:f32|f64)?|[eE][+-]?_*[0-9][0-9_]*(?:f32|f64)?|\\.(?!\\.|[\\p{L}\\p{Nl}_]))"},
+            "integer": {"name": "constant.numeric.integer.rust", "match": "\\b(?:0x[0-9A-Fa-f_]+|0o[0-7_]+|0b[01_]+|[0-9][0-9_]*)(?:[iu](?:8|16|32|64|128|size))?\\b"}
+          }
+        }
+    "##]].assert_eq(&textmate_grammar());
+}
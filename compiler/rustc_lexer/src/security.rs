@@ -0,0 +1,80 @@
+//! Detection of identifiers which may deceive readers, following Unicode
+//! Technical Standard #39.
+//!
+//! [`check_idents`] reports identifiers which contain characters restricted
+//! from use in identifiers by the General Security Profile, which mix
+//! scripts, like a Latin `a` next to a Cyrillic `о`, or which look the same
+//! as an earlier identifier. These are the checks behind rustc's
+//! `uncommon_codepoints`, `mixed_script_confusables` and `confusable_idents`
+//! lints, done on the token stream.
+
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use unicode_security::{GeneralSecurityProfile, MixedScript};
+
+use crate::{tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// An identifier with at least one problem, see [`check_idents`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IdentFinding {
+    /// Byte range of the identifier in the source, without the `r#` of raw
+    /// identifiers.
+    pub range: Range<usize>,
+    /// The confusable skeleton of the identifier. Identifiers with the same
+    /// skeleton are considered to look the same.
+    pub skeleton: String,
+    /// Byte ranges of the chars which are restricted from use in
+    /// identifiers.
+    pub restricted: Vec<Range<usize>>,
+    /// Whether the identifier is not single-script.
+    pub mixed_script: bool,
+    /// The range of the first identifier which has the same skeleton but is
+    /// spelled differently, if any.
+    pub confusable_with: Option<Range<usize>>,
+}
+
+/// Checks the identifiers and lifetimes in `src`, returning the ones with a
+/// problem in source order.
+pub fn check_idents(src: &str) -> Vec<IdentFinding> {
+    let mut res = Vec::new();
+    // The first identifier with each skeleton.
+    let mut skeletons: BTreeMap<String, Range<usize>> = BTreeMap::new();
+    for (mut range, kind) in tokenize_with_offsets(src) {
+        match kind {
+            TokenKind::Ident => {}
+            TokenKind::RawIdent => range.start += 2,
+            TokenKind::Lifetime { .. } => range.start += 1,
+            TokenKind::RawLifetime => range.start += 3,
+            _ => continue,
+        }
+        let ident = &src[range.clone()];
+        let skeleton: String = unicode_security::skeleton(ident).collect();
+        let first = skeletons.entry(skeleton.clone()).or_insert_with(|| range.clone()).clone();
+        // Like rustc, only pairs with a non-ASCII identifier are confusable,
+        // which keeps `rn` and `m` apart.
+        let confusable_with = Some(first).filter(|first| {
+            let first = &src[first.clone()];
+            first != ident && !(first.is_ascii() && ident.is_ascii())
+        });
+        if ident.is_ascii() && confusable_with.is_none() {
+            continue;
+        }
+        let restricted: Vec<_> = ident
+            .char_indices()
+            .filter(|&(_, c)| !c.identifier_allowed())
+            .map(|(i, c)| range.start + i..range.start + i + c.len_utf8())
+            .collect();
+        let mixed_script = !ident.is_single_script();
+        if restricted.is_empty() && !mixed_script && confusable_with.is_none() {
+            continue;
+        }
+        res.push(IdentFinding { range, skeleton, restricted, mixed_script, confusable_with });
+    }
+    res
+}
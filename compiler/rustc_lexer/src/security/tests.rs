@@ -0,0 +1,47 @@
+use super::*;
+use expect_test::expect;
+
+#[test]
+fn test_check_idents() {
+    // U+043E CYRILLIC SMALL LETTER O, U+0430 CYRILLIC SMALL LETTER A and
+    // U+01C9 LATIN SMALL LETTER LJ, which is restricted.
+    let src =
+        "let foo = ao; let f\u{43e}o = 2; let \u{430}\u{43e} = r#\u{1c9}; let ok = \u{43e}\u{43e};";
+    expect![[r#"
+        [
+            IdentFinding {
+                range: 18..22,
+                skeleton: "foo",
+                restricted: [],
+                mixed_script: true,
+                confusable_with: Some(
+                    4..7,
+                ),
+            },
+            IdentFinding {
+                range: 32..36,
+                skeleton: "ao",
+                restricted: [],
+                mixed_script: false,
+                confusable_with: Some(
+                    10..12,
+                ),
+            },
+            IdentFinding {
+                range: 41..43,
+                skeleton: "lj",
+                restricted: [
+                    41..43,
+                ],
+                mixed_script: false,
+                confusable_with: None,
+            },
+        ]
+    "#]]
+    .assert_debug_eq(&check_idents(src));
+}
+
+#[test]
+fn test_ascii_confusables() {
+    assert_eq!(check_idents("let rn = 1; let m = rn; let cl = 2; let d = cl;"), []);
+}
@@ -0,0 +1,77 @@
+//! Lexing of text assembled from several origins.
+//!
+//! Tools which splice generated code with user code need to lex the combined
+//! text, but report positions in the pieces it was made of. Tokens which
+//! straddle the join of two pieces have no single origin and are reported as
+//! such, since they usually indicate a bug in the code generator.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// A piece of the text to lex.
+#[derive(Clone, Debug)]
+pub struct Segment<'a, O> {
+    pub text: &'a str,
+    /// Identifies where the text comes from, e.g. a file id.
+    pub origin: O,
+    /// Offset of `text` in its origin.
+    pub origin_offset: usize,
+}
+
+/// A token of the combined text, see [`tokenize_segments`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentedToken<O> {
+    pub kind: TokenKind,
+    /// Range of the token in the combined text.
+    pub range: Range<usize>,
+    pub origin: TokenOrigin<O>,
+}
+
+/// Where a [`SegmentedToken`] comes from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenOrigin<O> {
+    /// The token lies within a single segment, at `range` of its origin.
+    Resolved { origin: O, range: Range<usize> },
+    /// The token starts in segment `first` and ends in segment `last` (both
+    /// indices into the segment list), so it has no single origin.
+    SpansJoin { first: usize, last: usize },
+}
+
+/// Lexes the concatenation of `segments`, resolving the position of every
+/// token to the segment it comes from.
+pub fn tokenize_segments<O: Clone>(segments: &[Segment<'_, O>]) -> Vec<SegmentedToken<O>> {
+    let mut text = String::new();
+    let mut starts = Vec::with_capacity(segments.len());
+    for segment in segments {
+        starts.push(text.len());
+        text.push_str(segment.text);
+    }
+    // Index of the segment containing the byte at `offset`. Empty segments
+    // contain no bytes, so the last segment starting at or before `offset`
+    // is the one.
+    let segment_at = |offset: usize| starts.partition_point(|&start| start <= offset) - 1;
+
+    let mut res = Vec::new();
+    for (range, kind) in tokenize_with_offsets(&text) {
+        let first = segment_at(range.start);
+        let last = segment_at(range.end - 1);
+        let origin = if first == last {
+            let segment = &segments[first];
+            let start = segment.origin_offset + range.start - starts[first];
+            TokenOrigin::Resolved {
+                origin: segment.origin.clone(),
+                range: start..start + range.len(),
+            }
+        } else {
+            TokenOrigin::SpansJoin { first, last }
+        };
+        res.push(SegmentedToken { kind, range, origin });
+    }
+    res
+}
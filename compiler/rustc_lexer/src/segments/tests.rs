@@ -0,0 +1,43 @@
+use super::*;
+
+#[test]
+fn test_tokenize_segments() {
+    let segments = [
+        Segment { text: "fn f() {", origin: "generated", origin_offset: 0 },
+        Segment { text: "", origin: "empty", origin_offset: 0 },
+        Segment { text: " x + 1 ", origin: "user", origin_offset: 100 },
+        Segment { text: "}", origin: "generated", origin_offset: 8 },
+    ];
+    let tokens = tokenize_segments(&segments);
+    let origins: Vec<_> = tokens
+        .iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace { .. }))
+        .map(|token| token.origin.clone())
+        .collect();
+    assert_eq!(
+        origins,
+        [
+            TokenOrigin::Resolved { origin: "generated", range: 0..2 },
+            TokenOrigin::Resolved { origin: "generated", range: 3..4 },
+            TokenOrigin::Resolved { origin: "generated", range: 4..5 },
+            TokenOrigin::Resolved { origin: "generated", range: 5..6 },
+            TokenOrigin::Resolved { origin: "generated", range: 7..8 },
+            TokenOrigin::Resolved { origin: "user", range: 101..102 },
+            TokenOrigin::Resolved { origin: "user", range: 103..104 },
+            TokenOrigin::Resolved { origin: "user", range: 105..106 },
+            TokenOrigin::Resolved { origin: "generated", range: 8..9 },
+        ]
+    );
+}
+
+#[test]
+fn test_token_spanning_join() {
+    let segments = [
+        Segment { text: "let x = \"abc", origin: 0, origin_offset: 0 },
+        Segment { text: "def\";", origin: 1, origin_offset: 0 },
+    ];
+    let tokens = tokenize_segments(&segments);
+    assert_eq!(tokens[6].range, 8..16);
+    assert_eq!(tokens[6].origin, TokenOrigin::SpansJoin { first: 0, last: 1 });
+    assert_eq!(tokens[7].origin, TokenOrigin::Resolved { origin: 1, range: 4..5 });
+}
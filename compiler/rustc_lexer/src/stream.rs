@@ -0,0 +1,198 @@
+//! Lexing of input which arrives in chunks, e.g. from a pipe or a socket.
+//!
+//! [`StreamLexer`] buffers only the end of the input which may still lex
+//! differently once more input arrives: the lexer looks at most two chars
+//! past the end of a token to decide its kind and length, so every token
+//! followed by two chars is final. The exception are hexadecimal floats like
+//! `0x1.8p3`, which only become one token once the binary exponent has a
+//! digit, so a hexadecimal literal is held back until that is decided.
+//!
+//! [`tokenize_read`] builds on it to lex from a reader without reading the
+//! whole input into memory.
+
+#[cfg(feature = "std")]
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::mem;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
+
+use crate::{tokenize, Base, Cursor, LiteralKind, Token, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// A push-based lexer, see the [module documentation](self).
+///
+/// ```ignore (illustrative)
+/// let mut lexer = StreamLexer::new();
+/// for chunk in chunks {
+///     tokens.extend(lexer.feed(&chunk));
+/// }
+/// tokens.extend(lexer.finish());
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StreamLexer {
+    /// The input which has not been lexed into final tokens yet.
+    buf: String,
+    /// Offset of `buf` in the whole input.
+    offset: usize,
+}
+
+impl StreamLexer {
+    pub fn new() -> StreamLexer {
+        StreamLexer::default()
+    }
+
+    /// Offset in the whole input of the first token which has not been
+    /// returned yet.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Appends `chunk` to the input and returns the tokens which are final.
+    ///
+    /// Tokens which are not final are lexed again on the next call, so a
+    /// token spanning many chunks, like a huge string literal, is lexed once
+    /// per chunk.
+    pub fn feed(&mut self, chunk: &str) -> Vec<Token> {
+        self.buf.push_str(chunk);
+        let mut tokens = Vec::new();
+        let mut cursor = Cursor::new(&self.buf);
+        let mut pos = 0;
+        while !cursor.is_eof() {
+            let token = cursor.advance_token();
+            let end = pos + token.len;
+            if self.buf[end..].chars().nth(1).is_none()
+                || may_become_hex_float(token.kind, &self.buf[pos..])
+            {
+                break;
+            }
+            pos = end;
+            tokens.push(token);
+        }
+        self.buf.drain(..pos);
+        self.offset += pos;
+        tokens
+    }
+
+    /// Returns the remaining tokens at the end of the input.
+    pub fn finish(self) -> Vec<Token> {
+        tokenize(&self.buf).collect()
+    }
+}
+
+/// Returns whether the token of `kind` at the start of `text` may still be
+/// the start of a hexadecimal float like `0x1.8p3` once more input arrives,
+/// that is whether `text` ends before the binary exponent has a digit.
+fn may_become_hex_float(kind: TokenKind, text: &str) -> bool {
+    match kind {
+        TokenKind::Literal { kind: LiteralKind::Int { base: Base::Hexadecimal, .. }, .. }
+        | TokenKind::Literal {
+            kind: LiteralKind::Float { base: Base::Hexadecimal, hex_exponent: None, .. },
+            ..
+        } => {}
+        _ => return false,
+    }
+    let is_hex_digit = |c: char| c.is_ascii_hexdigit() || c == '_';
+    let rest = text[2..].trim_start_matches(is_hex_digit);
+    let rest = match rest.strip_prefix('.') {
+        Some(rest) if !rest.starts_with('.') => rest,
+        _ => return false,
+    };
+    let rest = rest.trim_start_matches(is_hex_digit);
+    let rest = match rest.strip_prefix(|c| c == 'p' || c == 'P') {
+        Some(rest) => rest,
+        None => return rest.is_empty(),
+    };
+    let rest = rest.strip_prefix(|c| c == '+' || c == '-').unwrap_or(rest);
+    rest.is_empty()
+}
+
+/// Lexes the UTF-8 text read from `reader`, see [`ReadTokens`].
+#[cfg(feature = "std")]
+pub fn tokenize_read<R: BufRead>(reader: R) -> ReadTokens<R> {
+    ReadTokens {
+        reader,
+        lexer: StreamLexer::new(),
+        incomplete_char: Vec::new(),
+        tokens: VecDeque::new(),
+        pos: 0,
+        done: false,
+    }
+}
+
+/// Iterator over the tokens read from a reader along with their offsets in
+/// the whole input, see [`tokenize_read`].
+///
+/// Only the tokens of the last read chunk and the not yet final tokens are
+/// held in memory. Reading stops at the first error; input which is not
+/// valid UTF-8 is reported as [`io::ErrorKind::InvalidData`].
+#[cfg(feature = "std")]
+pub struct ReadTokens<R> {
+    reader: R,
+    lexer: StreamLexer,
+    /// The bytes of a char split between two chunks.
+    incomplete_char: Vec<u8>,
+    tokens: VecDeque<Token>,
+    /// Offset of the first token in `tokens`.
+    pos: usize,
+    done: bool,
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> ReadTokens<R> {
+    /// Reads the next chunk and lexes it. Returns `false` at the end of the
+    /// input.
+    fn read_chunk(&mut self) -> io::Result<bool> {
+        let buf = self.reader.fill_buf()?;
+        if buf.is_empty() {
+            if !self.incomplete_char.is_empty() {
+                return Err(invalid_utf8());
+            }
+            self.tokens.extend(mem::take(&mut self.lexer).finish());
+            return Ok(false);
+        }
+        let read = buf.len();
+        self.incomplete_char.extend_from_slice(buf);
+        self.reader.consume(read);
+        let bytes = mem::take(&mut self.incomplete_char);
+        let valid_len = match core::str::from_utf8(&bytes) {
+            Ok(_) => bytes.len(),
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => return Err(invalid_utf8()),
+        };
+        let chunk = core::str::from_utf8(&bytes[..valid_len]).unwrap();
+        self.tokens.extend(self.lexer.feed(chunk));
+        self.incomplete_char.extend_from_slice(&bytes[valid_len..]);
+        Ok(true)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<R: BufRead> Iterator for ReadTokens<R> {
+    type Item = io::Result<(usize, Token)>;
+
+    fn next(&mut self) -> Option<io::Result<(usize, Token)>> {
+        while self.tokens.is_empty() && !self.done {
+            match self.read_chunk() {
+                Ok(more) => self.done = !more,
+                Err(err) => {
+                    self.done = true;
+                    return Some(Err(err));
+                }
+            }
+        }
+        let token = self.tokens.pop_front()?;
+        let pos = self.pos;
+        self.pos += token.len;
+        Some(Ok((pos, token)))
+    }
+}
+
+#[cfg(feature = "std")]
+fn invalid_utf8() -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")
+}
@@ -0,0 +1,87 @@
+use super::*;
+
+/// Feeds `src` in chunks of `chunk_len` bytes, checking the tokens against
+/// lexing it at once.
+fn check_chunked(src: &str, chunk_len: usize) {
+    let mut lexer = StreamLexer::new();
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    while start < src.len() {
+        let mut end = (start + chunk_len).min(src.len());
+        while !src.is_char_boundary(end) {
+            end += 1;
+        }
+        tokens.extend(lexer.feed(&src[start..end]));
+        assert_eq!(lexer.offset(), tokens.iter().map(|token| token.len).sum::<usize>());
+        start = end;
+    }
+    tokens.extend(lexer.finish());
+    assert_eq!(tokens, tokenize(src).collect::<Vec<_>>(), "chunks of {}", chunk_len);
+}
+
+#[test]
+fn test_stream_lexer() {
+    let src = "fn main() { let r = r#\"a\"#; 'a: loop { x = 1.; y = 1..2; z = 'c'; } \
+               /* /* nested */ */ r#ident b'x' \"ü\" 1e+5 foo#bar }";
+    for chunk_len in 1..8 {
+        check_chunked(src, chunk_len);
+    }
+}
+
+#[test]
+fn test_buffering() {
+    let mut lexer = StreamLexer::new();
+    // Only the identifier is followed by two chars.
+    assert_eq!(lexer.feed("ab '"), [Token { kind: crate::TokenKind::Ident, len: 2 }]);
+    assert_eq!(lexer.offset(), 2);
+    // `"unterminated` may still be closed.
+    assert_eq!(lexer.feed("a' \"unterminated").len(), 3);
+    assert_eq!(lexer.offset(), 7);
+    assert_eq!(lexer.finish().len(), 1);
+}
+
+#[test]
+fn test_hex_float() {
+    let mut lexer = StreamLexer::new();
+    let mut tokens = lexer.feed("x = 0x1.ab");
+    tokens.extend(lexer.feed("p3;"));
+    tokens.extend(lexer.finish());
+    let lens: Vec<_> = tokens.iter().map(|token| token.len).collect();
+    assert_eq!(lens, [1, 1, 1, 1, 8, 1]);
+    for chunk_len in 1..8 {
+        check_chunked("x = 0x1.ab_cp-3f32 + 0x1.ab.c + 0x1.5e3p1 + 0x1.", chunk_len);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_tokenize_read() {
+    let src = "let ü = \"ä\\u{1F600}\"; // 😀\nx";
+    for capacity in 1..6 {
+        let reader = io::BufReader::with_capacity(capacity, src.as_bytes());
+        let tokens: Vec<_> = tokenize_read(reader).collect::<io::Result<_>>().unwrap();
+        let mut pos = 0;
+        let expected: Vec<_> = tokenize(src)
+            .map(|token| {
+                pos += token.len;
+                (pos - token.len, token)
+            })
+            .collect();
+        assert_eq!(tokens, expected);
+    }
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_tokenize_read_invalid_utf8() {
+    let read = |bytes: &'static [u8]| {
+        let reader = io::BufReader::with_capacity(2, bytes);
+        tokenize_read(reader).map(|result| result.map_err(|err| err.kind())).collect::<Vec<_>>()
+    };
+    let ident = Token { kind: crate::TokenKind::Ident, len: 1 };
+    let ws =
+        Token { kind: crate::TokenKind::Whitespace { line_endings: None, counts: None }, len: 1 };
+    assert_eq!(read(b"a b \xFF"), [Ok((0, ident)), Ok((1, ws)), Err(io::ErrorKind::InvalidData)]);
+    // A char cut off by the end of the input.
+    assert_eq!(read(b"a\xC3").last(), Some(&Err(io::ErrorKind::InvalidData)));
+}
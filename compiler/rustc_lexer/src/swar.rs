@@ -0,0 +1,81 @@
+//! Word-at-a-time scanning of byte classes, used by the fast paths of the
+//! `simd` feature.
+//!
+//! Each function looks at eight bytes at a time and classifies all of them
+//! with a few integer operations, see
+//! <https://graphics.stanford.edu/~seander/bithacks.html#ValueInWord>. The
+//! per-byte tests are exact, i.e. unlike the classic zero-byte test they never
+//! flag a byte because of a borrow out of its neighbour.
+
+use core::convert::TryInto;
+
+#[cfg(test)]
+mod tests;
+
+const LO: u64 = 0x0101_0101_0101_0101;
+const HI: u64 = 0x8080_8080_8080_8080;
+
+/// Sets the high bit of every ASCII byte of `word` in `lo..=hi`.
+///
+/// With the high bit of every byte set, subtracting `lo` can't borrow from the
+/// next byte, and the high bit stays set exactly for the bytes `>= lo`.
+fn in_range(word: u64, lo: u8, hi: u8) -> u64 {
+    let x = word | HI;
+    let ge_lo = x.wrapping_sub(LO * u64::from(lo));
+    let gt_hi = x.wrapping_sub(LO * u64::from(hi + 1));
+    ge_lo & !gt_hi & !word & HI
+}
+
+/// Returns the length of the longest prefix of `bytes` for which `class`,
+/// given a word, sets the high bit of each byte in the class, and `is_in`
+/// holds for the remaining bytes.
+fn prefix_len(bytes: &[u8], class: impl Fn(u64) -> u64, is_in: impl Fn(u8) -> bool) -> usize {
+    let mut chunks = bytes.chunks_exact(8);
+    let mut len = 0;
+    for chunk in &mut chunks {
+        let outside = !class(u64::from_le_bytes(chunk.try_into().unwrap())) & HI;
+        if outside != 0 {
+            return len + outside.trailing_zeros() as usize / 8;
+        }
+        len += 8;
+    }
+    let rest = chunks.remainder();
+    len + rest.iter().position(|&b| !is_in(b)).unwrap_or(rest.len())
+}
+
+/// Returns the length of the longest prefix of `bytes` consisting of ASCII
+/// whitespace as defined by [`is_whitespace`](crate::is_whitespace).
+pub(crate) fn whitespace_len(bytes: &[u8]) -> usize {
+    prefix_len(
+        bytes,
+        |word| in_range(word, b'\t', b'\r') | in_range(word, b' ', b' '),
+        |b| matches!(b, b'\t'..=b'\r' | b' '),
+    )
+}
+
+/// Returns the length of the longest prefix of `bytes` consisting of ASCII
+/// letters, digits and `_`.
+pub(crate) fn ascii_id_continue_len(bytes: &[u8]) -> usize {
+    prefix_len(
+        bytes,
+        |word| {
+            // Setting the 0x20 bit maps uppercase letters to lowercase ones and
+            // no other byte to a letter.
+            in_range(word | (LO * 0x20), b'a', b'z')
+                | in_range(word, b'0', b'9')
+                | in_range(word, b'_', b'_')
+        },
+        |b| b.is_ascii_alphanumeric() || b == b'_',
+    )
+}
+
+/// Returns the offset of the first byte of `bytes` in `needles`, or the
+/// length of `bytes` if there is none. The needles must be ASCII.
+pub(crate) fn find_bytes(bytes: &[u8], needles: &[u8]) -> usize {
+    debug_assert!(needles.is_ascii());
+    prefix_len(
+        bytes,
+        |word| !needles.iter().fold(0, |found, &needle| found | in_range(word, needle, needle)),
+        |b| !needles.contains(&b),
+    )
+}
@@ -0,0 +1,38 @@
+use super::*;
+
+/// Checks `prefix_len` against `is_in` for every byte at every position of
+/// the word and the remainder.
+fn check(prefix_len: impl Fn(&[u8]) -> usize, is_in: impl Fn(u8) -> bool, filler: u8) {
+    for b in 0..=u8::MAX {
+        for pos in 0..19 {
+            let mut bytes = vec![filler; 19];
+            bytes[pos] = b;
+            let expected = if is_in(b) { bytes.len() } else { pos };
+            assert_eq!(prefix_len(&bytes), expected, "byte {:#x} at {}", b, pos);
+        }
+    }
+}
+
+#[test]
+fn test_whitespace_len() {
+    check(whitespace_len, |b| crate::is_whitespace(b as char) && b.is_ascii(), b' ');
+    assert_eq!(whitespace_len(b" \t\r\n\x0b\x0c  \n\n  x "), 12);
+    // U+0085 NEXT LINE is whitespace, but not ASCII.
+    assert_eq!(whitespace_len("  \u{85}".as_bytes()), 2);
+}
+
+#[test]
+fn test_ascii_id_continue_len() {
+    check(ascii_id_continue_len, |b| crate::is_id_continue(b as char) && b.is_ascii(), b'_');
+    assert_eq!(ascii_id_continue_len(b"foo_bar_Baz9000 "), 15);
+    assert_eq!(ascii_id_continue_len("caf\u{e9}".as_bytes()), 3);
+}
+
+#[test]
+fn test_find_bytes() {
+    check(|bytes| find_bytes(bytes, b"\n"), |b| b != b'\n', b'/');
+    check(|bytes| find_bytes(bytes, b"\"\\"), |b| b != b'"' && b != b'\\', b'a');
+    assert_eq!(find_bytes("// \u{1F980} crab\nfn".as_bytes(), b"\n"), 12);
+    assert_eq!(find_bytes(b"a\\b\"", b"\"\\"), 1);
+    assert_eq!(find_bytes(b"", b"\n"), 0);
+}
@@ -2,7 +2,7 @@
 
 use expect_test::{expect, Expect};
 
-fn check_raw_str(s: &str, expected_hashes: u16, expected_err: Option<RawStrError>) {
+fn check_raw_str(s: &str, expected_hashes: u32, expected_err: Option<RawStrError>) {
     let s = &format!("r{}", s);
     let mut cursor = Cursor::new(s);
     cursor.bump();
@@ -51,6 +51,30 @@ fn test_unterminated() {
     )
 }
 
+#[test]
+fn test_unterminated_plausible_terminator() {
+    // `"#a` is followed by an identifier, so `"#;` is the better candidate.
+    check_raw_str(
+        r###"##"x = "#a"; y"#;"###,
+        2,
+        Some(RawStrError::NoTerminator {
+            expected: 2,
+            found: 1,
+            possible_terminator_offset: Some(15),
+        }),
+    );
+    // More `#`s win over a plausible end.
+    check_raw_str(
+        r####"###"a"##b"#;"####,
+        3,
+        Some(RawStrError::NoTerminator {
+            expected: 3,
+            found: 2,
+            possible_terminator_offset: Some(6),
+        }),
+    );
+}
+
 #[test]
 fn test_invalid_start() {
     check_raw_str(r##"#~"abc"#"##, 1, Some(RawStrError::InvalidStarter { bad_char: '~' }));
@@ -68,8 +92,8 @@ fn test_unterminated_no_pound() {
 
 #[test]
 fn test_too_many_hashes() {
-    let max_count = u16::MAX;
-    let mut hashes: String = "#".repeat(max_count.into());
+    let max_count = LexerConfig::DEFAULT.max_raw_str_hashes;
+    let mut hashes: String = "#".repeat(max_count as usize);
 
     // Valid number of hashes (65535 = 2^16 - 1), but invalid string.
     check_raw_str(&hashes, max_count, Some(RawStrError::InvalidStarter { bad_char: '\u{0}' }));
@@ -79,7 +103,40 @@ fn test_too_many_hashes() {
     check_raw_str(
         &hashes,
         0,
-        Some(RawStrError::TooManyDelimiters { found: usize::from(max_count) + 1 }),
+        Some(RawStrError::TooManyDelimiters { found: max_count + 1, max: max_count }),
+    );
+}
+
+#[test]
+fn test_max_raw_str_hashes() {
+    let config = LexerConfig { max_raw_str_hashes: 2, ..LexerConfig::DEFAULT };
+    let kinds: Vec<_> = tokenize_with_config(r####"r##"a"## r###"b"###"####, &config)
+        .map(|token| token.kind)
+        .collect();
+    assert_eq!(
+        kinds[0],
+        Literal { kind: RawStr { n_hashes: 2, err: None, first_newline: None }, suffix_start: 8 }
+    );
+    let err = RawStrError::TooManyDelimiters { found: 3, max: 2 };
+    assert_eq!(
+        kinds[2],
+        Literal {
+            kind: RawStr { n_hashes: 0, err: Some(err), first_newline: None },
+            suffix_start: 10
+        }
+    );
+
+    let hashes = "#".repeat(70_000);
+    let input = format!("r{}\"a\"{}", hashes, hashes);
+    let config = LexerConfig { max_raw_str_hashes: u32::MAX, ..LexerConfig::DEFAULT };
+    let token = tokenize_with_config(&input, &config).next().unwrap();
+    assert_eq!(token.len, input.len());
+    assert_eq!(
+        token.kind,
+        Literal {
+            kind: RawStr { n_hashes: 70_000, err: None, first_newline: None },
+            suffix_start: input.len()
+        }
     );
 }
 
@@ -87,57 +144,137 @@ fn test_too_many_hashes() {
 fn test_valid_shebang() {
     // https://github.com/rust-lang/rust/issues/70528
     let input = "#!/usr/bin/rustrun\nlet x = 5;";
-    assert_eq!(strip_shebang(input), Some(18));
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), Some(18));
 }
 
 #[test]
 fn test_invalid_shebang_valid_rust_syntax() {
     // https://github.com/rust-lang/rust/issues/70528
     let input = "#!    [bad_attribute]";
-    assert_eq!(strip_shebang(input), None);
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), None);
 }
 
 #[test]
 fn test_shebang_second_line() {
     // Because shebangs are interpreted by the kernel, they must be on the first line
     let input = "\n#!/bin/bash";
-    assert_eq!(strip_shebang(input), None);
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), None);
 }
 
 #[test]
 fn test_shebang_space() {
     let input = "#!    /bin/bash";
-    assert_eq!(strip_shebang(input), Some(input.len()));
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), Some(input.len()));
 }
 
 #[test]
 fn test_shebang_empty_shebang() {
     let input = "#!    \n[attribute(foo)]";
-    assert_eq!(strip_shebang(input), None);
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), None);
 }
 
 #[test]
 fn test_invalid_shebang_comment() {
     let input = "#!//bin/ami/a/comment\n[";
-    assert_eq!(strip_shebang(input), None)
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), None)
 }
 
 #[test]
 fn test_invalid_shebang_another_comment() {
     let input = "#!/*bin/ami/a/comment*/\n[attribute";
-    assert_eq!(strip_shebang(input), None)
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), None)
 }
 
 #[test]
 fn test_shebang_valid_rust_after() {
     let input = "#!/*bin/ami/a/comment*/\npub fn main() {}";
-    assert_eq!(strip_shebang(input), Some(23))
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), Some(23))
 }
 
 #[test]
 fn test_shebang_followed_by_attrib() {
     let input = "#!/bin/rust-scripts\n#![allow_unused(true)]";
-    assert_eq!(strip_shebang(input), Some(19));
+    assert_eq!(strip_shebang(input).map(|shebang| shebang.len), Some(19));
+}
+
+#[test]
+fn test_shebang_interpreter() {
+    let input = "#! /usr/bin/env run-cargo-script \r\nfn main() {}";
+    let shebang = strip_shebang(input).unwrap();
+    assert_eq!(&input[shebang.interpreter.clone()], "/usr/bin/env run-cargo-script");
+    assert_eq!(shebang, Shebang { len: 33, interpreter: 3..32, ends_with_newline: true });
+
+    let input = "#!/bin/rust-scripts";
+    let shebang = strip_shebang(input).unwrap();
+    assert_eq!(shebang, Shebang { len: 19, interpreter: 2..19, ends_with_newline: false });
+
+    let input = "#!  ";
+    let shebang = strip_shebang(input).unwrap();
+    assert_eq!(shebang, Shebang { len: 4, interpreter: 4..4, ends_with_newline: false });
+}
+
+#[test]
+fn test_strip_bom() {
+    assert_eq!(strip_bom("\u{FEFF}fn main() {}"), Some(3));
+    assert_eq!(strip_bom("fn main() {}"), None);
+    assert_eq!(strip_bom(""), None);
+    assert_eq!(strip_bom("\u{FEFF}"), Some(3));
+    assert_eq!(strip_bom("a\u{FEFF}"), None);
+}
+
+#[test]
+fn test_strip_bom_and_shebang() {
+    let input = "\u{FEFF}#!/usr/bin/env run-cargo-script\nfn main() {}";
+    let bom_len = strip_bom(input).unwrap_or(0);
+    assert_eq!(strip_shebang(&input[bom_len..]).map(|shebang| shebang.len), Some(31));
+}
+
+#[test]
+fn test_strip_frontmatter() {
+    let input = "---\n[dependencies]\n---\nfn main() {}";
+    assert_eq!(strip_frontmatter(input), Some(22));
+
+    let input = "#!/usr/bin/env -S cargo -Zscript\n\n----cargo\na = 1\n---\n----  \r\nfn main() {}";
+    assert_eq!(strip_frontmatter(input), Some(61));
+    assert_eq!(&input[61..], "\nfn main() {}");
+
+    let input = "---\n---";
+    assert_eq!(strip_frontmatter(input), Some(7));
+}
+
+#[test]
+fn test_no_frontmatter() {
+    assert_eq!(strip_frontmatter("fn main() {}"), None);
+    assert_eq!(strip_frontmatter("--\n--\n"), None);
+    assert_eq!(strip_frontmatter("---\n[dependencies]\n"), None);
+    assert_eq!(strip_frontmatter("---"), None);
+    assert_eq!(strip_frontmatter(" ---\n---\n"), None);
+    assert_eq!(strip_frontmatter("fn main() {}\n---\n---\n"), None);
+    assert_eq!(strip_frontmatter("#!/bin/sh ---\n---\n"), None);
+}
+
+#[test]
+fn test_strip_prelude() {
+    let input = "fn main() {}";
+    let prelude = strip_prelude(input);
+    assert_eq!(prelude, Prelude { bom: false, shebang: None, frontmatter: None, start: 0 });
+
+    let input = "\u{FEFF}#!/usr/bin/env -S cargo -Zscript\n---\n[dependencies]\n---\nfn main() {}";
+    let prelude = strip_prelude(input);
+    let shebang = prelude.shebang.as_ref().unwrap();
+    assert!(prelude.bom);
+    assert_eq!(&input[shebang.interpreter.clone()], "/usr/bin/env -S cargo -Zscript");
+    assert_eq!(&input[prelude.frontmatter.clone().unwrap()], "---\n[dependencies]\n---");
+    assert_eq!(&input[prelude.start..], "\nfn main() {}");
+
+    let input = "\u{FEFF}---\n---\nfn main() {}";
+    let prelude = strip_prelude(input);
+    assert_eq!(prelude, Prelude { bom: true, shebang: None, frontmatter: Some(3..10), start: 10 });
+
+    // A shebang has to come first.
+    let input = "---\n---\n#!/bin/sh\n";
+    let prelude = strip_prelude(input);
+    assert_eq!((prelude.shebang, prelude.frontmatter, prelude.start), (None, Some(0..7), 7));
 }
 
 fn check_lexing(src: &str, expect: Expect) {
@@ -151,24 +288,24 @@ fn smoke_test() {
         "/* my source file */ fn main() { println!(\"zebra\"); }\n",
         expect![[r#"
             Token { kind: BlockComment { doc_style: None, terminated: true }, len: 20 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Ident, len: 2 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Ident, len: 4 }
             Token { kind: OpenParen, len: 1 }
             Token { kind: CloseParen, len: 1 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: OpenBrace, len: 1 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Ident, len: 7 }
             Token { kind: Bang, len: 1 }
             Token { kind: OpenParen, len: 1 }
-            Token { kind: Literal { kind: Str { terminated: true }, suffix_start: 7 }, len: 7 }
+            Token { kind: Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 7 }, len: 7 }
             Token { kind: CloseParen, len: 1 }
             Token { kind: Semi, len: 1 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: CloseBrace, len: 1 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
         "#]],
     )
 }
@@ -188,25 +325,25 @@ fn comment_flavors() {
 /*! inner doc block */
 ",
         expect![[r#"
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: LineComment { doc_style: None }, len: 7 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: LineComment { doc_style: None }, len: 17 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: LineComment { doc_style: Some(Outer) }, len: 18 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: LineComment { doc_style: Some(Inner) }, len: 18 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: BlockComment { doc_style: None, terminated: true }, len: 11 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: BlockComment { doc_style: None, terminated: true }, len: 4 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: BlockComment { doc_style: None, terminated: true }, len: 18 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: BlockComment { doc_style: Some(Outer), terminated: true }, len: 22 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: BlockComment { doc_style: Some(Inner), terminated: true }, len: 22 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
         "#]],
     )
 }
@@ -228,9 +365,9 @@ fn characters() {
         "'a' ' ' '\\n'",
         expect![[r#"
             Token { kind: Literal { kind: Char { terminated: true }, suffix_start: 3 }, len: 3 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Literal { kind: Char { terminated: true }, suffix_start: 3 }, len: 3 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Literal { kind: Char { terminated: true }, suffix_start: 4 }, len: 4 }
         "#]],
     );
@@ -251,7 +388,7 @@ fn raw_string() {
     check_lexing(
         "r###\"\"#a\\b\x00c\"\"###",
         expect![[r#"
-            Token { kind: Literal { kind: RawStr { n_hashes: 3, err: None }, suffix_start: 17 }, len: 17 }
+            Token { kind: Literal { kind: RawStr { n_hashes: 3, err: None, first_newline: None }, suffix_start: 17 }, len: 17 }
         "#]],
     )
 }
@@ -274,31 +411,918 @@ fn literal_suffixes() {
 br###"raw"###suffix
 "####,
         expect![[r#"
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Literal { kind: Char { terminated: true }, suffix_start: 3 }, len: 3 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
             Token { kind: Literal { kind: Byte { terminated: true }, suffix_start: 4 }, len: 4 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Str { terminated: true }, suffix_start: 3 }, len: 3 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: ByteStr { terminated: true }, suffix_start: 4 }, len: 4 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Int { base: Decimal, empty_int: false }, suffix_start: 4 }, len: 4 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Int { base: Binary, empty_int: false }, suffix_start: 5 }, len: 5 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Int { base: Hexadecimal, empty_int: false }, suffix_start: 5 }, len: 5 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Float { base: Decimal, empty_exponent: false }, suffix_start: 3 }, len: 3 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Float { base: Decimal, empty_exponent: false }, suffix_start: 6 }, len: 6 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: Int { base: Decimal, empty_int: false }, suffix_start: 1 }, len: 3 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: RawStr { n_hashes: 3, err: None }, suffix_start: 12 }, len: 18 }
-            Token { kind: Whitespace, len: 1 }
-            Token { kind: Literal { kind: RawByteStr { n_hashes: 3, err: None }, suffix_start: 13 }, len: 19 }
-            Token { kind: Whitespace, len: 1 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }, len: 3 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: ByteStr { terminated: true, first_newline: None }, suffix_start: 4 }, len: 4 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 4 }, len: 4 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Binary, empty_int: None, underscore_after_prefix: false }, suffix_start: 5 }, len: 5 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Hexadecimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 5 }, len: 5 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Float { base: Decimal, empty_exponent: None, hex_exponent: None }, suffix_start: 3 }, len: 3 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Float { base: Decimal, empty_exponent: None, hex_exponent: None }, suffix_start: 6 }, len: 6 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 1 }, len: 3 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: RawStr { n_hashes: 3, err: None, first_newline: None }, suffix_start: 12 }, len: 18 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: RawByteStr { n_hashes: 3, err: None, first_newline: None }, suffix_start: 13 }, len: 19 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        "#]],
+    )
+}
+
+#[test]
+fn test_explain_strip_shebang() {
+    let trace = explain_strip_shebang("fn main() {}");
+    assert_eq!((trace.stripped, trace.rule), (None, ShebangRule::NoShebangPrefix));
+    assert!(trace.peeked.is_empty());
+
+    let trace = explain_strip_shebang("#! /* comment */ [allow(unused)]");
+    assert_eq!((trace.stripped, trace.rule), (None, ShebangRule::InnerAttribute));
+    let peeked: Vec<_> = trace.peeked.iter().map(|token| (token.kind, token.len)).collect();
+    assert_eq!(
+        peeked,
+        [
+            (TokenKind::Whitespace { line_endings: None, counts: None }, 1),
+            (TokenKind::BlockComment { doc_style: None, terminated: true }, 13),
+            (TokenKind::Whitespace { line_endings: None, counts: None }, 1),
+            (TokenKind::OpenBracket, 1),
+        ]
+    );
+
+    let trace = explain_strip_shebang("#!/usr/bin/env run-cargo-script\nfn main() {}");
+    assert_eq!(
+        (trace.stripped.map(|shebang| shebang.len), trace.rule),
+        (Some(31), ShebangRule::Shebang)
+    );
+    assert_eq!(trace.peeked.first().map(|token| token.kind), Some(TokenKind::Slash));
+}
+
+#[test]
+fn test_cursor_resume() {
+    let src = "fn main() { let s = \"ü\"; }";
+    let mut cursor = Cursor::new(src);
+    for _ in 0..6 {
+        cursor.advance_token();
+    }
+    let state = CursorState::from_bytes(cursor.state().to_bytes()).unwrap();
+    assert_eq!(state.pos(), 10);
+
+    let mut resumed = Cursor::resume(src, state).unwrap();
+    while !cursor.is_eof() {
+        let (expected, actual) = (cursor.advance_token(), resumed.advance_token());
+        assert_eq!((expected.kind, expected.len), (actual.kind, actual.len));
+    }
+    assert!(resumed.is_eof());
+
+    // Not a char boundary.
+    assert!(Cursor::resume(src, CursorState::new(22, None)).is_none());
+    // Out of bounds.
+    assert!(Cursor::resume(src, CursorState::new(100, None)).is_none());
+
+    let mut bytes = state.to_bytes();
+    bytes[8] = 8;
+    assert_eq!(CursorState::from_bytes(bytes), None);
+}
+
+#[test]
+fn test_cursor_resume_inside_literal() {
+    let src = "r#\"a\"b\n\"# x";
+    let whole: Vec<Token> = tokenize(src).collect();
+    // Split the raw string anywhere in its contents before the newline.
+    for split in 3..7 {
+        let mut head = Cursor::new(&src[..split]);
+        let first = head.advance_token();
+        assert!(head.is_eof());
+        let continuation = Continuation::RawStr { n_hashes: 1 };
+        let state = CursorState::new(split, Some(continuation));
+        let state = CursorState::from_bytes(state.to_bytes()).unwrap();
+        assert_eq!(state.continuation(), Some(continuation));
+
+        let mut cursor = Cursor::resume(src, state).unwrap();
+        let mut tail = Vec::new();
+        while !cursor.is_eof() {
+            tail.push(cursor.advance_token());
+        }
+        // The rest of the raw string has the kind of the whole token, with
+        // offsets relative to the split.
+        let rest = tail.remove(0);
+        let kind = match rest.kind {
+            TokenKind::Literal {
+                kind: LiteralKind::RawStr { n_hashes, err, first_newline },
+                suffix_start,
+            } => TokenKind::Literal {
+                kind: LiteralKind::RawStr {
+                    n_hashes,
+                    err,
+                    first_newline: first_newline.map(|offset| offset + first.len as u32),
+                },
+                suffix_start: suffix_start + first.len,
+            },
+            kind => panic!("{:?}", kind),
+        };
+        tail.insert(0, Token::new(kind, first.len + rest.len));
+        assert_eq!(tail, whole, "split at {}", split);
+    }
+}
+
+#[test]
+fn custom_literal_prefixes() {
+    let config = LexerConfig { literal_prefixes: &["sql", "f"], ..LexerConfig::DEFAULT };
+    let actual: String = tokenize_with_config(r#"sql"select 1"x f'a' g"b" f#"#, &config)
+        .map(|token| format!("{:?}\n", token))
+        .collect();
+    expect![[r#"
+        Token { kind: PrefixedLiteral { prefix: 0, kind: Str { terminated: true, first_newline: None }, suffix_start: 13 }, len: 14 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: PrefixedLiteral { prefix: 1, kind: Char { terminated: true }, suffix_start: 4 }, len: 4 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: UnknownPrefix { followed_by: Str }, len: 1 }
+        Token { kind: Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }, len: 3 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: UnknownPrefix { followed_by: Pound }, len: 1 }
+        Token { kind: Pound, len: 1 }
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn custom_ident_policy() {
+    fn is_dsl_id_continue(c: char) -> bool {
+        is_id_continue(c) || c == '-' || c == '?'
+    }
+    let config = LexerConfig {
+        ident_policy: IdentPolicy { is_start: is_id_start, is_continue: is_dsl_id_continue },
+        ..LexerConfig::DEFAULT
+    };
+    let actual: String = tokenize_with_config("is-empty? 'a-b 1u-8 -x", &config)
+        .map(|token| format!("{:?}\n", token))
+        .collect();
+    expect![[r#"
+        Token { kind: Ident, len: 9 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Lifetime { starts_with_number: false }, len: 4 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 1 }, len: 4 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Minus, len: 1 }
+        Token { kind: Ident, len: 1 }
+    "#]]
+    .assert_eq(&actual);
+
+    let config = LexerConfig { ident_policy: IdentPolicy::ASCII, ..LexerConfig::DEFAULT };
+    let actual: String =
+        tokenize_with_config("héllo r#é", &config).map(|token| format!("{:?}\n", token)).collect();
+    expect![[r#"
+        Token { kind: Ident, len: 1 }
+        Token { kind: Unknown, len: 2 }
+        Token { kind: Ident, len: 3 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Literal { kind: RawStr { n_hashes: 1, err: Some(InvalidStarter { bad_char: 'é' }), first_newline: None }, suffix_start: 4 }, len: 4 }
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn classify_line_endings() {
+    let config = LexerConfig { classify_line_endings: true, ..LexerConfig::DEFAULT };
+    let actual: String = tokenize_with_config("a \n\t b\r\n\r\nc\r d \r\r\n\n", &config)
+        .map(|token| format!("{:?}\n", token))
+        .collect();
+    expect![[r#"
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: Some(LineEndingKinds { lf: true, crlf: false, cr: false }), counts: None }, len: 4 }
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: Some(LineEndingKinds { lf: false, crlf: true, cr: false }), counts: None }, len: 4 }
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: Some(LineEndingKinds { lf: false, crlf: false, cr: true }), counts: None }, len: 2 }
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: Some(LineEndingKinds { lf: true, crlf: true, cr: true }), counts: None }, len: 5 }
+    "#]].assert_eq(&actual);
+}
+
+#[test]
+fn count_whitespace() {
+    let config = LexerConfig { count_whitespace: true, ..LexerConfig::DEFAULT };
+    let actual: String = tokenize_with_config("a \n\t b  \r\n\r\n\tc\r\x0c", &config)
+        .map(|token| format!("{:?}\n", token))
+        .collect();
+    expect![[r#"
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: None, counts: Some(WhitespaceCounts { spaces: 2, tabs: 1, newlines: 1 }) }, len: 4 }
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: None, counts: Some(WhitespaceCounts { spaces: 2, tabs: 1, newlines: 2 }) }, len: 7 }
+        Token { kind: Ident, len: 1 }
+        Token { kind: Whitespace { line_endings: None, counts: Some(WhitespaceCounts { spaces: 0, tabs: 0, newlines: 1 }) }, len: 2 }
+    "#]].assert_eq(&actual);
+}
+
+#[test]
+fn reserved_prefixes_by_edition() {
+    let src = "f'a' k#x z\"s\" b\"s\" 👍\"s\"";
+    let lex = |edition| {
+        tokenize_with_edition(src, edition)
+            .filter(|token| !token.kind.is_trivia())
+            .map(|token| format!("{:?}\n", token.kind))
+            .collect::<String>()
+    };
+    expect![[r#"
+        Ident
+        Literal { kind: Char { terminated: true }, suffix_start: 3 }
+        Ident
+        Pound
+        Ident
+        Ident
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }
+        Literal { kind: ByteStr { terminated: true, first_newline: None }, suffix_start: 4 }
+        InvalidIdent
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }
+    "#]]
+    .assert_eq(&lex(Edition::Edition2018));
+    expect![[r#"
+        UnknownPrefix { followed_by: Char }
+        Literal { kind: Char { terminated: true }, suffix_start: 3 }
+        UnknownPrefix { followed_by: Pound }
+        Pound
+        Ident
+        UnknownPrefix { followed_by: Str }
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }
+        Literal { kind: ByteStr { terminated: true, first_newline: None }, suffix_start: 4 }
+        UnknownPrefix { followed_by: Str }
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }
+    "#]]
+    .assert_eq(&lex(Edition::Edition2021));
+}
+
+#[test]
+fn c_string_literals() {
+    let src = "c\"a\\\"\" cr#\"b\"# cr\"y\"_s c\"x";
+    let lex = |edition| {
+        tokenize_with_edition(src, edition)
+            .filter(|token| !token.kind.is_trivia())
+            .map(|token| format!("{:?} {}\n", token.kind, token.len))
+            .collect::<String>()
+    };
+    expect![[r#"
+        Ident 1
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 5 } 5
+        Ident 2
+        Pound 1
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } 3
+        Pound 1
+        Ident 2
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } 5
+        Ident 1
+        Literal { kind: Str { terminated: false, first_newline: None }, suffix_start: 2 } 2
+    "#]]
+    .assert_eq(&lex(Edition::Edition2018));
+    expect![[r#"
+        Literal { kind: CStr { terminated: true, first_newline: None }, suffix_start: 6 } 6
+        Literal { kind: RawCStr { n_hashes: 1, err: None, first_newline: None }, suffix_start: 7 } 7
+        Literal { kind: RawCStr { n_hashes: 0, err: None, first_newline: None }, suffix_start: 5 } 7
+        Literal { kind: CStr { terminated: false, first_newline: None }, suffix_start: 3 } 3
+    "#]]
+    .assert_eq(&lex(Edition::Edition2021));
+}
+
+#[test]
+fn guarded_strings() {
+    let src = "#\"a\\\"\"# ##\"b\"### #[attr] #x \"c\"#_s #\"d";
+    let lex = |edition| {
+        tokenize_with_edition(src, edition)
+            .filter(|token| !token.kind.is_trivia())
+            .map(|token| format!("{:?} {}\n", token.kind, token.len))
+            .collect::<String>()
+    };
+    expect![[r#"
+        Pound 1
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 5 } 5
+        Pound 1
+        Pound 1
+        Pound 1
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } 3
+        Pound 1
+        Pound 1
+        Pound 1
+        Pound 1
+        OpenBracket 1
+        Ident 4
+        CloseBracket 1
+        Pound 1
+        Ident 1
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } 3
+        Pound 1
+        Ident 2
+        Pound 1
+        Literal { kind: Str { terminated: false, first_newline: None }, suffix_start: 2 } 2
+    "#]]
+    .assert_eq(&lex(Edition::Edition2021));
+    expect![[r#"
+        GuardedStr { n_hashes: 1, terminated: true } 7
+        GuardedStr { n_hashes: 2, terminated: true } 7
+        Pound 1
+        Pound 1
+        OpenBracket 1
+        Ident 4
+        CloseBracket 1
+        Pound 1
+        Ident 1
+        Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } 3
+        Pound 1
+        Ident 2
+        GuardedStr { n_hashes: 1, terminated: false } 3
+    "#]]
+    .assert_eq(&lex(Edition::Edition2024));
+}
+
+#[test]
+fn raw_lifetimes() {
+    let src = "'r#fn 'r#a'b 'r 'r# 'r#1 'r'";
+    let lex = |edition| {
+        tokenize_with_edition(src, edition)
+            .filter(|token| !token.kind.is_trivia())
+            .map(|token| format!("{:?} {}\n", token.kind, token.len))
+            .collect::<String>()
+    };
+    expect![[r#"
+        Lifetime { starts_with_number: false } 2
+        Pound 1
+        Ident 2
+        Lifetime { starts_with_number: false } 2
+        Pound 1
+        Ident 1
+        Lifetime { starts_with_number: false } 2
+        Lifetime { starts_with_number: false } 2
+        Lifetime { starts_with_number: false } 2
+        Pound 1
+        Lifetime { starts_with_number: false } 2
+        Pound 1
+        Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 1 } 1
+        Literal { kind: Char { terminated: true }, suffix_start: 3 } 3
+    "#]].assert_eq(&lex(Edition::Edition2018));
+    expect![[r#"
+        RawLifetime 5
+        RawLifetime 4
+        Lifetime { starts_with_number: false } 2
+        Lifetime { starts_with_number: false } 2
+        Lifetime { starts_with_number: false } 2
+        Pound 1
+        Lifetime { starts_with_number: false } 2
+        Pound 1
+        Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 1 } 1
+        Literal { kind: Char { terminated: true }, suffix_start: 3 } 3
+    "#]].assert_eq(&lex(Edition::Edition2021));
+}
+
+#[test]
+fn custom_comment_syntax() {
+    let config = LexerConfig {
+        line_comment_starts: &["#", "--"],
+        nested_block_comments: false,
+        ..LexerConfig::DEFAULT
+    };
+    let actual: String = tokenize_with_config("# a\n\"#\" x--y\n- 1 /* /* */ */", &config)
+        .map(|token| format!("{:?}\n", token))
+        .collect();
+    expect![[r#"
+        Token { kind: LineComment { doc_style: None }, len: 3 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 }, len: 3 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Ident, len: 1 }
+        Token { kind: LineComment { doc_style: None }, len: 3 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Minus, len: 1 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 1 }, len: 1 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: BlockComment { doc_style: None, terminated: true }, len: 8 }
+        Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+        Token { kind: Star, len: 1 }
+        Token { kind: Slash, len: 1 }
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn test_char_close_quote_offset() {
+    assert_eq!(char_close_quote_offset("'a"), Some(2));
+    assert_eq!(char_close_quote_offset("'é;"), Some(3));
+    assert_eq!(char_close_quote_offset("b'a"), Some(3));
+    assert_eq!(char_close_quote_offset(r"'\n;"), Some(3));
+    assert_eq!(char_close_quote_offset(r"'\x7f;"), Some(5));
+    assert_eq!(char_close_quote_offset(r"'\u{1F980};"), Some(10));
+    assert_eq!(char_close_quote_offset(r"'\u{12;"), Some(6));
+    assert_eq!(char_close_quote_offset("'"), None);
+    assert_eq!(char_close_quote_offset("a'"), None);
+
+    // The offset is relative to the token text.
+    let src = "let c = 'a;";
+    let start = src.find('\'').unwrap();
+    let token = first_token(&src[start..]);
+    assert_eq!(token.kind, Lifetime { starts_with_number: false });
+    assert_eq!(char_close_quote_offset(&src[start..start + token.len]), Some(2));
+}
+
+#[test]
+fn test_tokenize_with_offsets() {
+    let input = "let s = \"a\";";
+    let tokens: Vec<_> = tokenize_with_offsets(input)
+        .map(|(range, kind)| format!("{:?} {:?} {:?}", range.clone(), kind, &input[range]))
+        .collect();
+    expect![[r#"
+        0..3 Ident "let"
+        3..4 Whitespace { line_endings: None, counts: None } " "
+        4..5 Ident "s"
+        5..6 Whitespace { line_endings: None, counts: None } " "
+        6..7 Eq "="
+        7..8 Whitespace { line_endings: None, counts: None } " "
+        8..11 Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 3 } "\"a\""
+        11..12 Semi ";""#]].assert_eq(&tokens.join("\n"));
+}
+
+#[test]
+fn test_tokenize_with_text() {
+    // The shebang is lexed like any other text.
+    let src = "#!x\nfn f() {}";
+    let tokens: Vec<_> = tokenize_with_text(src)
+        .map(|(token, text)| format!("{:?} {:?}", token.kind, text))
+        .collect();
+    expect![[r##"
+        Pound "#"
+        Bang "!"
+        Ident "x"
+        Whitespace { line_endings: None, counts: None } "\n"
+        Ident "fn"
+        Whitespace { line_endings: None, counts: None } " "
+        Ident "f"
+        OpenParen "("
+        CloseParen ")"
+        Whitespace { line_endings: None, counts: None } " "
+        OpenBrace "{"
+        CloseBrace "}""##]]
+    .assert_eq(&tokens.join("\n"));
+
+    let (_, first) = tokenize_with_text(src).next().unwrap();
+    assert_eq!(first.as_ptr(), src.as_ptr());
+}
+
+#[test]
+fn test_filter_trivia() {
+    let src = "/// doc\nfn /* a */ f() {} // end\n";
+    let mut tokens = tokenize(src).filter_trivia();
+    let mut pos = 0;
+    let mut actual = Vec::new();
+    for (trivia_len, token) in &mut tokens {
+        pos += trivia_len;
+        actual.push(format!("{:?} {:?}", token.kind, &src[pos..pos + token.len]));
+        pos += token.len;
+    }
+    assert_eq!(pos + tokens.pending_trivia_len(), src.len());
+    expect![[r#"
+        LineComment { doc_style: Some(Outer) } "/// doc"
+        Ident "fn"
+        Ident "f"
+        OpenParen "("
+        CloseParen ")"
+        OpenBrace "{"
+        CloseBrace "}""#]]
+    .assert_eq(&actual.join("\n"));
+}
+
+#[test]
+fn test_token_kind_display() {
+    let src = "/// a\nr#\"b\"# ( 'c '\\n' 1.0 /*! d */ №";
+    let actual: Vec<_> = tokenize(src)
+        .filter(|token| !matches!(token.kind, Whitespace { .. }))
+        .map(|token| token.kind.to_string())
+        .collect();
+    expect![[r#"
+        doc comment
+        raw string literal
+        open parenthesis
+        lifetime
+        character literal
+        float literal
+        inner block doc comment
+        unknown token"#]]
+    .assert_eq(&actual.join("\n"));
+}
+
+#[test]
+fn test_peek_token() {
+    let mut cursor = Cursor::new("a::b");
+    let kinds = |cursor: &Cursor<'_>| {
+        (0..5).map(|n| cursor.peek_token(n).map(|token| token.kind)).collect::<Vec<_>>()
+    };
+    assert_eq!(
+        kinds(&cursor),
+        [
+            Some(TokenKind::Ident),
+            Some(TokenKind::Colon),
+            Some(TokenKind::Colon),
+            Some(TokenKind::Ident),
+            None
+        ]
+    );
+    assert_eq!(cursor.peek_token(0), Some(cursor.clone().advance_token()));
+    cursor.advance_token();
+    cursor.advance_token();
+    assert_eq!(kinds(&cursor)[..3], [Some(TokenKind::Colon), Some(TokenKind::Ident), None]);
+    assert_eq!(cursor.len_consumed(), 1);
+}
+
+#[test]
+fn test_checkpoint() {
+    let mut cursor = Cursor::new("r#abc rest");
+    let checkpoint = cursor.checkpoint();
+    // Speculatively lex a raw string prefix.
+    cursor.bump();
+    cursor.bump();
+    assert_eq!(cursor.first(), 'a');
+    cursor.rewind(checkpoint);
+    assert_eq!(cursor.len_consumed(), 0);
+    assert_eq!(cursor.advance_token(), Token { kind: TokenKind::RawIdent, len: 5 });
+
+    // A checkpoint in the middle of a token keeps the consumed part.
+    cursor.reset_len_consumed();
+    cursor.bump();
+    let checkpoint = cursor.checkpoint();
+    cursor.eat_while(|c| c != 's');
+    cursor.rewind(checkpoint);
+    assert_eq!(cursor.len_consumed(), 1);
+    assert_eq!(cursor.first(), 'r');
+}
+
+#[test]
+fn test_fingerprints() {
+    let fingerprints: Vec<_> =
+        tokenize_with_fingerprints("a b a 'a").map(|(_, fingerprint)| fingerprint).collect();
+    assert_eq!(fingerprints[0], fingerprints[4]);
+    assert_eq!(fingerprints[1], fingerprints[3]);
+    assert_ne!(fingerprints[0], fingerprints[2]);
+    assert_ne!(fingerprints[0], fingerprints[6]);
+
+    // Fingerprints must not change between runs, platforms and versions.
+    assert_eq!(Fingerprint::of(Ident, "foo"), Fingerprint(0x0773_e700_007c_5b7e));
+    let kind = Literal { kind: Str { terminated: true, first_newline: Some(2) }, suffix_start: 6 };
+    assert_eq!(Fingerprint::of(kind, "\"a\nb\""), Fingerprint(0x5de7_428c_99dc_142d));
+}
+
+#[test]
+fn test_tokens_restore() {
+    let mut tokens = tokenize("a + b * c");
+    tokens.next();
+    let fork = tokens.clone();
+    let state = tokens.state();
+    assert_eq!(state.pos(), 1);
+
+    let rest: Vec<_> = tokens.by_ref().map(|token| token.kind).collect();
+    assert_eq!(fork.map(|token| token.kind).collect::<Vec<_>>(), rest);
+    assert!(tokens.next().is_none());
+
+    tokens.restore(state);
+    assert_eq!(tokens.map(|token| token.kind).collect::<Vec<_>>(), rest);
+}
+
+#[test]
+fn test_cursor_primitives() {
+    // A lexer for a DSL with `@name` variables and Rust strings.
+    let src = "@foo \"a\\\"b\" @_ \"x";
+    let mut cursor = Cursor::new(src);
+    let mut tokens = Vec::new();
+    while !cursor.is_eof() {
+        cursor.reset_len_consumed();
+        let kind = match cursor.bump().unwrap() {
+            '@' => {
+                cursor.eat_identifier();
+                "var"
+            }
+            '"' if cursor.double_quoted_string() => "str",
+            '"' => "unterminated str",
+            c if is_whitespace(c) => {
+                cursor.eat_while(is_whitespace);
+                continue;
+            }
+            _ => "unknown",
+        };
+        tokens.push((kind, cursor.len_consumed()));
+    }
+    assert_eq!(tokens, [("var", 4), ("str", 6), ("var", 2), ("unterminated str", 2)]);
+}
+
+#[test]
+fn test_ascii_tables() {
+    for b in 0..128u8 {
+        let c = char::from(b);
+        assert_eq!(ASCII_WHITESPACE[usize::from(b)], is_whitespace(c), "{:?}", c);
+        assert_eq!(ASCII_ID_START[usize::from(b)], is_id_start(c), "{:?}", c);
+        assert_eq!(ASCII_ID_CONTINUE[usize::from(b)], is_id_continue(c), "{:?}", c);
+        let s = c.to_string();
+        let token = first_token(&s);
+        let is_punctuation = !matches!(
+            token.kind,
+            Whitespace { .. }
+                | Ident
+                | UnknownPrefix { .. }
+                | Literal { .. }
+                | Unknown
+                | Lifetime { .. }
+        );
+        assert_eq!(ASCII_PUNCTUATION[usize::from(b)], is_punctuation, "{:?}", c);
+    }
+}
+
+#[test]
+fn underscore_after_base_prefix() {
+    check_lexing(
+        "0b_101 0x_FF 0o7 0_1 0x_",
+        expect![[r#"
+            Token { kind: Literal { kind: Int { base: Binary, empty_int: None, underscore_after_prefix: true }, suffix_start: 6 }, len: 6 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Hexadecimal, empty_int: None, underscore_after_prefix: true }, suffix_start: 5 }, len: 5 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Octal, empty_int: None, underscore_after_prefix: false }, suffix_start: 3 }, len: 3 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Decimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 3 }, len: 3 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Int { base: Hexadecimal, empty_int: Some(MissingDigits { start: 0, expected_at: 3 }), underscore_after_prefix: true }, suffix_start: 3 }, len: 3 }
+        "#]],
+    )
+}
+
+#[test]
+fn hex_floats() {
+    let src = "0x1.8p3 0x1.0p-3f32 0x1.ap3 0x1.a 0x1p3 0x1.5";
+    let actual: String = tokenize(src)
+        .filter(|token| !token.kind.is_trivia())
+        .map(|token| format!("{:?} {}\n", token.kind, token.len))
+        .collect();
+    expect![[r#"
+        Literal { kind: Float { base: Hexadecimal, empty_exponent: None, hex_exponent: Some(5) }, suffix_start: 7 } 7
+        Literal { kind: Float { base: Hexadecimal, empty_exponent: None, hex_exponent: Some(5) }, suffix_start: 8 } 11
+        Literal { kind: Float { base: Hexadecimal, empty_exponent: None, hex_exponent: Some(5) }, suffix_start: 7 } 7
+        Literal { kind: Int { base: Hexadecimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 3 } 3
+        Dot 1
+        Ident 1
+        Literal { kind: Int { base: Hexadecimal, empty_int: None, underscore_after_prefix: false }, suffix_start: 3 } 5
+        Literal { kind: Float { base: Hexadecimal, empty_exponent: None, hex_exponent: None }, suffix_start: 5 } 5
+    "#]]
+    .assert_eq(&actual);
+}
+
+#[test]
+fn multiline_strings() {
+    check_lexing(
+        "\"a\nb\nc\" r#\"\n\"# b\"\\\n\" \"ab\"",
+        expect![[r#"
+            Token { kind: Literal { kind: Str { terminated: true, first_newline: Some(2) }, suffix_start: 7 }, len: 7 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: RawStr { n_hashes: 1, err: None, first_newline: Some(3) }, suffix_start: 6 }, len: 6 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: ByteStr { terminated: true, first_newline: Some(3) }, suffix_start: 5 }, len: 5 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 4 }, len: 4 }
         "#]],
     )
 }
+
+#[test]
+fn test_literal_content_range() {
+    fn check(text: &str, expected: &str) {
+        let token = first_token(text);
+        assert_eq!(token.len, text.len(), "{:?}", text);
+        let (kind, suffix_start) = match token.kind {
+            Literal { kind, suffix_start } => (kind, suffix_start),
+            kind => panic!("{:?} is not a literal but {:?}", text, kind),
+        };
+        assert_eq!(&text[kind.content_range(text, suffix_start)], expected, "{:?}", text);
+    }
+    check("'a'", "a");
+    check("'\\n'u8", "\\n");
+    check("'\\n", "\\n");
+    check("b'a'", "a");
+    check("\"abc\"suffix", "abc");
+    check("\"abc", "abc");
+    check("b\"abc\"", "abc");
+    check("r\"abc\"", "abc");
+    check("r##\"a\"#b\"##x", "a\"#b");
+    check("br#\"abc\"#", "abc");
+    check("r##\"abc\"#", "abc\"#");
+    check("r#\"", "");
+    check("r#~", "");
+    check("0x1F_u8", "1F_");
+    check("1.5e3f64", "1.5e3");
+    check("0b", "");
+}
+
+#[test]
+fn test_tokenize_continuing() {
+    fn check(src: &str, continuation: Continuation, expect: Expect) {
+        let actual: String =
+            tokenize_continuing(src, continuation).map(|token| format!("{:?}\n", token)).collect();
+        expect.assert_eq(&actual)
+    }
+    check(
+        "abc\" x",
+        Continuation::Str,
+        expect![[r#"
+            Token { kind: Literal { kind: Str { terminated: true, first_newline: None }, suffix_start: 4 }, len: 4 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Ident, len: 1 }
+        "#]],
+    );
+    check(
+        "a\\\"b",
+        Continuation::ByteStr,
+        expect![[r#"
+        Token { kind: Literal { kind: ByteStr { terminated: false, first_newline: None }, suffix_start: 4 }, len: 4 }
+    "#]],
+    );
+    check(
+        "a\"#\n\"## x",
+        Continuation::RawStr { n_hashes: 2 },
+        expect![[r#"
+            Token { kind: Literal { kind: RawStr { n_hashes: 2, err: None, first_newline: Some(3) }, suffix_start: 7 }, len: 7 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Ident, len: 1 }
+        "#]],
+    );
+    check(
+        "a */ */ x",
+        Continuation::BlockComment { doc_style: None, depth: 2 },
+        expect![[r#"
+            Token { kind: BlockComment { doc_style: None, terminated: true }, len: 7 }
+            Token { kind: Whitespace { line_endings: None, counts: None }, len: 1 }
+            Token { kind: Ident, len: 1 }
+        "#]],
+    );
+    check("", Continuation::Str, expect![[r#""#]]);
+}
+
+#[test]
+fn test_tokenize_into_vec() {
+    let src = "let x = 0x; 'a: loop { '1a } \"abc";
+    let (tokens, summary) = tokenize_into_vec(src);
+    let (expected_tokens, errors) = tokenize_checked(src);
+    assert_eq!(tokens, expected_tokens);
+    assert_eq!(summary.error_count, 3);
+    assert_eq!(summary.first_error, Some(errors[0].clone()));
+    assert_eq!(errors[0], LexError { range: 8..10, kind: LexErrorKind::EmptyInt });
+
+    let (tokens, summary) = tokenize_into_vec("");
+    assert!(tokens.is_empty());
+    assert!(!summary.has_errors());
+}
+
+#[test]
+fn test_tokenize_bounded() {
+    let src = "let s = \"a long string\";";
+    let (first, state) = tokenize_bounded(src, 10);
+    // The string literal crosses the budget and is included as a whole.
+    assert_eq!(first.len(), 7);
+    let state = state.unwrap();
+    assert_eq!(state.pos(), 23);
+
+    let mut rest = tokenize(src);
+    rest.restore(state);
+    assert_eq!(rest.map(|token| token.kind).collect::<Vec<_>>(), [Semi]);
+
+    let (all, state) = tokenize_bounded(src, 100);
+    assert_eq!(all.len(), 8);
+    assert_eq!(state, None);
+    let (none, state) = tokenize_bounded(src, 0);
+    assert!(none.is_empty());
+    assert_eq!(state.unwrap().pos(), 0);
+}
+
+#[test]
+fn test_validate_raw_str() {
+    assert_eq!(
+        validate_raw_str(r###"r##"a"#b"##suffix"###),
+        Ok(RawStrParts { n_hashes: 2, content: 4..8, len: 11 })
+    );
+    assert_eq!(
+        validate_raw_str(r#"br"abc""#),
+        Ok(RawStrParts { n_hashes: 0, content: 3..6, len: 7 })
+    );
+    assert_eq!(
+        validate_raw_str(r##"cr#"abc"#"##),
+        Ok(RawStrParts { n_hashes: 1, content: 4..7, len: 9 })
+    );
+    assert_eq!(
+        validate_raw_str(r###"r##"abc"#"###),
+        Err(RawStrError::NoTerminator {
+            expected: 2,
+            found: 1,
+            possible_terminator_offset: Some(8),
+        })
+    );
+    assert_eq!(validate_raw_str("r#~\"\"#"), Err(RawStrError::InvalidStarter { bad_char: '~' }));
+    assert_eq!(validate_raw_str("\"abc\""), Err(RawStrError::InvalidStarter { bad_char: '"' }));
+    assert_eq!(validate_raw_str("c\"abc\""), Err(RawStrError::InvalidStarter { bad_char: '"' }));
+}
+
+#[test]
+fn test_terminator_fix() {
+    let cases = [
+        (r###"r##"abc"#"###, 9, "#"),
+        (r##"r#"abc"##, 6, "\"#"),
+        (r##"r#"abc""##, 7, "#"),
+        (r#"br"abc"#, 6, "\""),
+        (r###"r###"a"#b"##c"###, 12, "#"),
+    ];
+    for (text, offset, insert) in cases {
+        let err = validate_raw_str(text).unwrap_err();
+        assert_eq!(err.terminator_fix(text.len()), Some((offset, insert.to_string())), "{}", text);
+        let fixed = format!("{}{}{}", &text[..offset], insert, &text[offset..]);
+        assert!(validate_raw_str(&fixed).is_ok(), "{}", fixed);
+    }
+    assert_eq!(RawStrError::InvalidStarter { bad_char: '~' }.terminator_fix(0), None);
+}
+
+#[test]
+fn test_raw_str_terminator_candidates() {
+    let text = r####"r###"a"#b "#, "##x" "#"####;
+    let candidates: Vec<_> = raw_str_terminator_candidates(text)
+        .into_iter()
+        .map(|candidate| (candidate.offset, candidate.found))
+        .collect();
+    assert_eq!(candidates, [(15, 2), (11, 1), (21, 1), (7, 1)]);
+    match validate_raw_str(text) {
+        Err(RawStrError::NoTerminator { possible_terminator_offset, .. }) => {
+            assert_eq!(possible_terminator_offset, Some(15));
+        }
+        res => panic!("{:?}", res),
+    }
+    assert!(raw_str_terminator_candidates(r#"r"abc"#).is_empty());
+    assert!(raw_str_terminator_candidates(r#"r#"abc"#).is_empty());
+    let candidates = raw_str_terminator_candidates(r###"r##"a"#b""###);
+    assert_eq!(
+        candidates,
+        [TerminatorCandidate { offset: 6, found: 1 }, TerminatorCandidate { offset: 9, found: 0 }]
+    );
+    assert!(raw_str_terminator_candidates(r#""abc"#).is_empty());
+    for prefix in ["br", "cr"] {
+        let text = format!(r###"{}##"a"#b"###, prefix);
+        assert_eq!(
+            raw_str_terminator_candidates(&text),
+            [TerminatorCandidate { offset: 7, found: 1 }]
+        );
+    }
+}
+
+#[test]
+fn test_split_suffix() {
+    assert_eq!(split_suffix("1u8", 1), ("1", Some("u8")));
+    assert_eq!(split_suffix(r#""a"suffix"#, 3), (r#""a""#, Some("suffix")));
+    assert_eq!(split_suffix("1.5", 3), ("1.5", None));
+    assert_eq!(split_suffix("'a'", 3), ("'a'", None));
+    assert!(is_valid_suffix("u8"));
+    assert!(is_valid_suffix("_suffix"));
+    assert!(is_valid_suffix("é"));
+    assert!(!is_valid_suffix(""));
+    assert!(!is_valid_suffix("_"));
+    assert!(!is_valid_suffix("1a"));
+    assert!(!is_valid_suffix("a-b"));
+}
+
+#[cfg(feature = "serde")]
+fn check_serde_round_trip<T>(value: &T)
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + fmt::Debug,
+{
+    let json = serde_json::to_string(value).unwrap();
+    assert_eq!(&serde_json::from_str::<T>(&json).unwrap(), value, "{}", json);
+}
+
+#[test]
+#[cfg(feature = "serde")]
+fn test_serde_round_trip() {
+    let src = "1.5e3f32 0x_ b\"x\\n\" br#\"\n\"# /** doc */ r#~ r##\"a\"# ";
+    for token in tokenize(src) {
+        check_serde_round_trip(&token);
+    }
+    let config = LexerConfig { max_raw_str_hashes: 1, ..LexerConfig::DEFAULT };
+    let token = tokenize_with_config("r##\"\"##", &config).next().unwrap();
+    assert!(matches!(
+        token.kind,
+        Literal { kind: RawStr { err: Some(RawStrError::TooManyDelimiters { .. }), .. }, .. }
+    ));
+    check_serde_round_trip(&token);
+
+    let diagnostics = lex_diagnostics("\"\\q\" 0x br\"\u{e9}\" \"\\\n\n\" \u{FEFF}");
+    assert_eq!(diagnostics.len(), 5);
+    for diagnostic in &diagnostics {
+        check_serde_round_trip(diagnostic);
+    }
+}
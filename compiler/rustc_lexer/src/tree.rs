@@ -0,0 +1,249 @@
+//! Grouping of tokens into trees of matching delimiters.
+//!
+//! The builder is iterative rather than recursive and stops building nested
+//! groups beyond a configurable depth, so hostile inputs like `((((((…` can
+//! neither overflow the stack while building nor while dropping the result.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::{tokenize_with_offsets, TokenKind};
+
+#[cfg(test)]
+mod tests;
+
+/// Maximum nesting depth used by [`token_trees`].
+pub const DEFAULT_MAX_DEPTH: usize = 512;
+
+/// A kind of delimiter.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Delimiter {
+    /// `( ... )`
+    Paren,
+    /// `{ ... }`
+    Brace,
+    /// `[ ... ]`
+    Bracket,
+}
+
+impl Delimiter {
+    /// Returns the delimiter opened by `kind`, if any.
+    pub fn from_open(kind: TokenKind) -> Option<Delimiter> {
+        match kind {
+            TokenKind::OpenParen => Some(Delimiter::Paren),
+            TokenKind::OpenBrace => Some(Delimiter::Brace),
+            TokenKind::OpenBracket => Some(Delimiter::Bracket),
+            _ => None,
+        }
+    }
+
+    /// Returns the delimiter closed by `kind`, if any.
+    pub fn from_close(kind: TokenKind) -> Option<Delimiter> {
+        match kind {
+            TokenKind::CloseParen => Some(Delimiter::Paren),
+            TokenKind::CloseBrace => Some(Delimiter::Brace),
+            TokenKind::CloseBracket => Some(Delimiter::Bracket),
+            _ => None,
+        }
+    }
+}
+
+/// A token or a delimited group of token trees. Trees are lossless: every
+/// token of the input, including whitespace and comments, is part of exactly
+/// one tree.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenTree {
+    /// A token which is not a delimiter.
+    Token { kind: TokenKind, range: Range<usize> },
+    /// A group of token trees enclosed in delimiters.
+    Delimited {
+        delim: Delimiter,
+        /// Range of the opening delimiter.
+        open: Range<usize>,
+        /// Range of the closing delimiter, or `None` if the group is unclosed.
+        close: Option<Range<usize>>,
+        children: Vec<TokenTree>,
+    },
+    /// A closing delimiter without a matching opening one.
+    UnmatchedClose { delim: Delimiter, range: Range<usize> },
+    /// A group nested deeper than the maximum depth. Its contents are not
+    /// built; `range` covers the whole group. If it is unclosed, it ends
+    /// before the delimiter closing an enclosing group, or at the end of input.
+    TooDeep { range: Range<usize> },
+}
+
+/// Builds the token trees of `src`, nesting at most [`DEFAULT_MAX_DEPTH`]
+/// groups.
+pub fn token_trees(src: &str) -> Vec<TokenTree> {
+    token_trees_with_max_depth(src, DEFAULT_MAX_DEPTH)
+}
+
+/// Builds the token trees of `src`. Groups nested deeper than `max_depth` are
+/// replaced by [`TokenTree::TooDeep`] nodes.
+pub fn token_trees_with_max_depth(src: &str, max_depth: usize) -> Vec<TokenTree> {
+    let mut builder = TreeBuilder::new(max_depth);
+    for (range, kind) in tokenize_with_offsets(src) {
+        builder.push((kind, range));
+    }
+    builder.finish(src.len())
+}
+
+/// A node of the trees built by [`TreeBuilder`], which allows building trees
+/// of other token types than [`TokenTree`], like those of the
+/// [pipeline](crate::pipeline).
+pub(crate) trait Node: Sized {
+    /// A token as it is stored in the tree.
+    type Leaf;
+
+    fn kind(leaf: &Self::Leaf) -> TokenKind;
+    fn range(leaf: &Self::Leaf) -> Range<usize>;
+
+    fn leaf(leaf: Self::Leaf) -> Self;
+    fn delimited(
+        delim: Delimiter,
+        open: Self::Leaf,
+        close: Option<Self::Leaf>,
+        children: Vec<Self>,
+    ) -> Self;
+    fn unmatched_close(delim: Delimiter, close: Self::Leaf) -> Self;
+    fn too_deep(range: Range<usize>) -> Self;
+}
+
+impl Node for TokenTree {
+    type Leaf = (TokenKind, Range<usize>);
+
+    fn kind(leaf: &Self::Leaf) -> TokenKind {
+        leaf.0
+    }
+
+    fn range(leaf: &Self::Leaf) -> Range<usize> {
+        leaf.1.clone()
+    }
+
+    fn leaf((kind, range): Self::Leaf) -> Self {
+        TokenTree::Token { kind, range }
+    }
+
+    fn delimited(
+        delim: Delimiter,
+        open: Self::Leaf,
+        close: Option<Self::Leaf>,
+        children: Vec<Self>,
+    ) -> Self {
+        TokenTree::Delimited { delim, open: open.1, close: close.map(|close| close.1), children }
+    }
+
+    fn unmatched_close(delim: Delimiter, close: Self::Leaf) -> Self {
+        TokenTree::UnmatchedClose { delim, range: close.1 }
+    }
+
+    fn too_deep(range: Range<usize>) -> Self {
+        TokenTree::TooDeep { range }
+    }
+}
+
+struct Frame<N: Node> {
+    delim: Delimiter,
+    open: N::Leaf,
+    children: Vec<N>,
+}
+
+/// State of skipping over a group which is nested too deeply.
+struct Skipping {
+    start: usize,
+    /// The delimiters of the groups opened since `start` which are not
+    /// closed yet, so delimiters are matched like when building trees.
+    open: Vec<Delimiter>,
+}
+
+pub(crate) struct TreeBuilder<N: Node> {
+    max_depth: usize,
+    stack: Vec<Frame<N>>,
+    root: Vec<N>,
+    skipping: Option<Skipping>,
+}
+
+impl<N: Node> TreeBuilder<N> {
+    pub(crate) fn new(max_depth: usize) -> Self {
+        TreeBuilder { max_depth, stack: Vec::new(), root: Vec::new(), skipping: None }
+    }
+
+    pub(crate) fn push(&mut self, leaf: N::Leaf) {
+        let kind = N::kind(&leaf);
+        if let Some(skipping) = &mut self.skipping {
+            if let Some(delim) = Delimiter::from_open(kind) {
+                skipping.open.push(delim);
+                return;
+            }
+            let delim = match Delimiter::from_close(kind) {
+                Some(delim) => delim,
+                None => return,
+            };
+            match skipping.open.iter().rposition(|&open| open == delim) {
+                Some(idx) => {
+                    skipping.open.truncate(idx);
+                    if skipping.open.is_empty() {
+                        let range = skipping.start..N::range(&leaf).end;
+                        self.skipping = None;
+                        self.children().push(N::too_deep(range));
+                    }
+                    return;
+                }
+                // The skipped group is unclosed, the delimiter closes a group
+                // around it.
+                None if self.stack.iter().any(|frame| frame.delim == delim) => {
+                    let range = skipping.start..N::range(&leaf).start;
+                    self.skipping = None;
+                    self.children().push(N::too_deep(range));
+                }
+                // An unmatched closing delimiter inside the skipped group.
+                None => return,
+            }
+        }
+
+        if let Some(delim) = Delimiter::from_open(kind) {
+            if self.stack.len() >= self.max_depth {
+                let open = vec![delim];
+                self.skipping = Some(Skipping { start: N::range(&leaf).start, open });
+            } else {
+                self.stack.push(Frame { delim, open: leaf, children: Vec::new() });
+            }
+        } else if let Some(delim) = Delimiter::from_close(kind) {
+            match self.stack.iter().rposition(|frame| frame.delim == delim) {
+                Some(idx) => {
+                    // Everything opened after the matching delimiter is unclosed.
+                    while self.stack.len() > idx + 1 {
+                        self.close(None);
+                    }
+                    self.close(Some(leaf));
+                }
+                None => self.children().push(N::unmatched_close(delim, leaf)),
+            }
+        } else {
+            self.children().push(N::leaf(leaf));
+        }
+    }
+
+    pub(crate) fn finish(mut self, len: usize) -> Vec<N> {
+        if let Some(skipping) = self.skipping.take() {
+            self.children().push(N::too_deep(skipping.start..len));
+        }
+        while !self.stack.is_empty() {
+            self.close(None);
+        }
+        self.root
+    }
+
+    fn children(&mut self) -> &mut Vec<N> {
+        match self.stack.last_mut() {
+            Some(frame) => &mut frame.children,
+            None => &mut self.root,
+        }
+    }
+
+    fn close(&mut self, close: Option<N::Leaf>) {
+        let Frame { delim, open, children } = self.stack.pop().unwrap();
+        self.children().push(N::delimited(delim, open, close, children));
+    }
+}
@@ -0,0 +1,129 @@
+use super::*;
+
+fn token(kind: TokenKind, start: usize) -> TokenTree {
+    TokenTree::Token { kind, range: start..start + 1 }
+}
+
+#[test]
+fn test_token_trees() {
+    assert_eq!(
+        token_trees("a(b[])"),
+        [
+            token(TokenKind::Ident, 0),
+            TokenTree::Delimited {
+                delim: Delimiter::Paren,
+                open: 1..2,
+                close: Some(5..6),
+                children: vec![
+                    token(TokenKind::Ident, 2),
+                    TokenTree::Delimited {
+                        delim: Delimiter::Bracket,
+                        open: 3..4,
+                        close: Some(4..5),
+                        children: vec![],
+                    },
+                ],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_unbalanced_delimiters() {
+    assert_eq!(
+        token_trees("}({)"),
+        [
+            TokenTree::UnmatchedClose { delim: Delimiter::Brace, range: 0..1 },
+            TokenTree::Delimited {
+                delim: Delimiter::Paren,
+                open: 1..2,
+                close: Some(3..4),
+                children: vec![TokenTree::Delimited {
+                    delim: Delimiter::Brace,
+                    open: 2..3,
+                    close: None,
+                    children: vec![],
+                }],
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_max_depth() {
+    assert_eq!(
+        token_trees_with_max_depth("[(())]x", 1),
+        [
+            TokenTree::Delimited {
+                delim: Delimiter::Bracket,
+                open: 0..1,
+                close: Some(5..6),
+                children: vec![TokenTree::TooDeep { range: 1..5 }],
+            },
+            token(TokenKind::Ident, 6),
+        ]
+    );
+    assert_eq!(token_trees_with_max_depth("((", 0), [TokenTree::TooDeep { range: 0..2 }]);
+}
+
+/// Replaces the groups nested `depth` deep in `trees` by `TooDeep` nodes.
+fn prune(trees: Vec<TokenTree>, depth: usize) -> Vec<TokenTree> {
+    fn end(tree: &TokenTree) -> usize {
+        match tree {
+            TokenTree::Token { range, .. }
+            | TokenTree::UnmatchedClose { range, .. }
+            | TokenTree::TooDeep { range } => range.end,
+            TokenTree::Delimited { open, close, children, .. } => match close {
+                Some(close) => close.end,
+                None => children.last().map_or(open.end, end),
+            },
+        }
+    }
+    trees
+        .into_iter()
+        .map(|tree| match tree {
+            TokenTree::Delimited { ref open, .. } if depth == 0 => {
+                TokenTree::TooDeep { range: open.start..end(&tree) }
+            }
+            TokenTree::Delimited { delim, open, close, children } => {
+                TokenTree::Delimited { delim, open, close, children: prune(children, depth - 1) }
+            }
+            tree => tree,
+        })
+        .collect()
+}
+
+#[test]
+fn test_max_depth_mismatched() {
+    for src in ["[(]", "[( ]x", "[(}]", "{[(])}", "[({)]x", "(]", "(()]", "[[(])"] {
+        for max_depth in 0..3 {
+            assert_eq!(
+                token_trees_with_max_depth(src, max_depth),
+                prune(token_trees(src), max_depth),
+                "{:?} with max depth {}",
+                src,
+                max_depth
+            );
+        }
+    }
+}
+
+#[test]
+fn test_hostile_nesting() {
+    let src = "(".repeat(1_000_000);
+    let trees = token_trees(&src);
+    assert_eq!(trees.len(), 1);
+    drop(trees);
+
+    let src = format!("{}{}", "(".repeat(1_000_000), ")".repeat(1_000_000));
+    let trees = token_trees_with_max_depth(&src, 1);
+    assert_eq!(
+        trees,
+        [TokenTree::Delimited {
+            delim: Delimiter::Paren,
+            open: 0..1,
+            close: Some(1_999_999..2_000_000),
+            children: vec![TokenTree::TooDeep { range: 1..1_999_999 }],
+        }]
+    );
+}
@@ -1,14 +1,20 @@
 //! Utilities for validating string and char literals and turning them into
 //! values they represent.
 
-use std::ops::Range;
-use std::str::Chars;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+use core::str::Chars;
+
+pub use crate::Severity;
 
 #[cfg(test)]
 mod tests;
 
 /// Errors and warnings that can occur during string unescaping.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EscapeError {
     /// Expected 1 char, but 0 were found.
     ZeroChars,
@@ -56,6 +62,8 @@ pub enum EscapeError {
     NonAsciiCharInByte,
     /// Non-ascii character in byte string literal.
     NonAsciiCharInByteString,
+    /// A NUL, escaped or not, in a C string literal.
+    NulInCStr,
 
     /// After a line ending with '\', the next line contains whitespace
     /// characters that are not skipped.
@@ -68,21 +76,144 @@ pub enum EscapeError {
 impl EscapeError {
     /// Returns true for actual errors, as opposed to warnings.
     pub fn is_fatal(&self) -> bool {
-        !matches!(
-            self,
-            EscapeError::UnskippedWhitespaceWarning | EscapeError::MultipleSkippedLinesWarning
-        )
+        self.severity() == Severity::Error
+    }
+
+    /// Classifies the error as a hard error or a warning.
+    pub fn severity(&self) -> Severity {
+        // Intentionally exhaustive, so new variants have to be classified.
+        match self {
+            EscapeError::ZeroChars
+            | EscapeError::MoreThanOneChar
+            | EscapeError::LoneSlash
+            | EscapeError::InvalidEscape
+            | EscapeError::BareCarriageReturn
+            | EscapeError::BareCarriageReturnInRawString
+            | EscapeError::EscapeOnlyChar
+            | EscapeError::TooShortHexEscape
+            | EscapeError::InvalidCharInHexEscape
+            | EscapeError::OutOfRangeHexEscape
+            | EscapeError::NoBraceInUnicodeEscape
+            | EscapeError::InvalidCharInUnicodeEscape
+            | EscapeError::EmptyUnicodeEscape
+            | EscapeError::UnclosedUnicodeEscape
+            | EscapeError::LeadingUnderscoreUnicodeEscape
+            | EscapeError::OverlongUnicodeEscape
+            | EscapeError::LoneSurrogateUnicodeEscape
+            | EscapeError::OutOfRangeUnicodeEscape
+            | EscapeError::UnicodeEscapeInByte
+            | EscapeError::NonAsciiCharInByte
+            | EscapeError::NonAsciiCharInByteString
+            | EscapeError::NulInCStr => Severity::Error,
+            EscapeError::UnskippedWhitespaceWarning | EscapeError::MultipleSkippedLinesWarning => {
+                Severity::Warning
+            }
+        }
+    }
+
+    /// Long-form help text explaining the error and how to fix it, in the
+    /// style of `rustc --explain`.
+    pub fn explain(&self) -> &'static str {
+        match self {
+            EscapeError::ZeroChars => {
+                "A character or byte literal must contain exactly one character, but this one \
+                 is empty. If you meant to write a quote character, escape it as `'\\''`."
+            }
+            EscapeError::MoreThanOneChar => {
+                "A character or byte literal must contain exactly one character, but this one \
+                 contains several. Use a string literal (`\"...\"`) for text consisting of more \
+                 than one character."
+            }
+            EscapeError::LoneSlash => {
+                "A backslash starts an escape sequence, but nothing follows it. To write a \
+                 literal backslash, escape it as `\\\\`."
+            }
+            EscapeError::InvalidEscape => {
+                "Only the escapes `\\n`, `\\r`, `\\t`, `\\\\`, `\\0`, `\\'`, `\\\"`, \
+                 `\\x..` and `\\u{..}` are supported. To write a literal backslash, escape it \
+                 as `\\\\`, or use a raw string, in which backslashes have no special meaning."
+            }
+            EscapeError::BareCarriageReturn | EscapeError::BareCarriageReturnInRawString => {
+                "A carriage return which is not part of a `\\r\\n` line ending is not allowed \
+                 in literals, as it is invisible in most editors. Use the `\\r` escape instead, \
+                 or remove the character."
+            }
+            EscapeError::EscapeOnlyChar => {
+                "This character has to be escaped in this kind of literal: a quote which would \
+                 otherwise end the literal, or a control character like a tab or newline in a \
+                 character literal. Write it as an escape, e.g. `\\'` or `\\t`."
+            }
+            EscapeError::TooShortHexEscape | EscapeError::InvalidCharInHexEscape => {
+                "A `\\x` escape must be followed by exactly two hexadecimal digits, e.g. \
+                 `\\x7f`."
+            }
+            EscapeError::OutOfRangeHexEscape => {
+                "In character and string literals, `\\x` escapes can only encode ASCII \
+                 characters, i.e. values up to `\\x7f`. Use a `\\u{..}` escape for other \
+                 characters, or a byte string if you meant to write a raw byte."
+            }
+            EscapeError::NoBraceInUnicodeEscape
+            | EscapeError::InvalidCharInUnicodeEscape
+            | EscapeError::EmptyUnicodeEscape
+            | EscapeError::UnclosedUnicodeEscape
+            | EscapeError::LeadingUnderscoreUnicodeEscape
+            | EscapeError::OverlongUnicodeEscape => {
+                "A unicode escape consists of `\\u` followed by one to six hexadecimal digits in \
+                 braces, e.g. `\\u{1F980}`. Underscores may separate the digits, but can't \
+                 come first."
+            }
+            EscapeError::LoneSurrogateUnicodeEscape => {
+                "Values in the range `D800` to `DFFF` are UTF-16 surrogates, which are not \
+                 valid characters and can't be escaped with `\\u{..}`."
+            }
+            EscapeError::OutOfRangeUnicodeEscape => {
+                "The highest valid character is `\\u{10FFFF}`, larger values don't encode a \
+                 character."
+            }
+            EscapeError::UnicodeEscapeInByte => {
+                "Byte and byte string literals consist of bytes, not characters, so `\\u{..}` \
+                 escapes are not allowed. Use `\\x..` escapes for the individual bytes, e.g. \
+                 of the UTF-8 encoding of the character."
+            }
+            EscapeError::NonAsciiCharInByte | EscapeError::NonAsciiCharInByteString => {
+                "Byte and byte string literals can only contain ASCII characters. Use `\\x..` \
+                 escapes for other bytes, e.g. of the UTF-8 encoding of the character."
+            }
+            EscapeError::NulInCStr => {
+                "C strings are terminated by a NUL byte, so they can't contain one. Use a byte \
+                 string if the data contains NUL bytes."
+            }
+            EscapeError::UnskippedWhitespaceWarning => {
+                "A backslash at the end of a line skips the line break and the whitespace at the \
+                 start of the next line. Whitespace which is not considered whitespace by this \
+                 rule, like a non-breaking space, is kept in the string, which is likely not \
+                 intended."
+            }
+            EscapeError::MultipleSkippedLinesWarning => {
+                "A backslash at the end of a line skips the line break and all whitespace which \
+                 follows it, including further empty lines. If the empty lines are meant to be \
+                 part of the string, write them as `\\n` escapes."
+            }
+        }
     }
 }
 
 /// Takes a contents of a literal (without quotes) and produces a
 /// sequence of escaped characters or errors.
 /// Values are returned through invoking of the provided callback.
+///
+/// For C strings, bytes written as `\x80` to `\xFF` are produced as the char
+/// with the same value. Use [`unescape_c_string`] to tell them apart.
 pub fn unescape_literal<F>(literal_text: &str, mode: Mode, callback: &mut F)
 where
     F: FnMut(Range<usize>, Result<char, EscapeError>),
 {
     match mode {
+        Mode::CStr | Mode::RawCStr => {
+            unescape_c_string(literal_text, mode, &mut |range, result| {
+                callback(range, result.map(MixedUnit::to_char_lossy))
+            })
+        }
         Mode::Char | Mode::Byte => {
             let mut chars = literal_text.chars();
             let result = unescape_char_or_byte(&mut chars, mode);
@@ -111,6 +242,60 @@ pub fn unescape_byte_literal<F>(literal_text: &str, mode: Mode, callback: &mut F
     })
 }
 
+/// A unit of the contents of a C string, see [`unescape_c_string`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MixedUnit {
+    /// A char, which stands for its UTF-8 encoding.
+    Char(char),
+    /// A single byte which is not valid UTF-8 on its own, written as an escape
+    /// from `\x80` to `\xFF`.
+    HighByte(u8),
+}
+
+impl MixedUnit {
+    /// Appends the bytes of the unit to `out`.
+    pub fn push_to(self, out: &mut Vec<u8>) {
+        match self {
+            MixedUnit::Char(c) => out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            MixedUnit::HighByte(b) => out.push(b),
+        }
+    }
+
+    fn to_char_lossy(self) -> char {
+        match self {
+            MixedUnit::Char(c) => c,
+            MixedUnit::HighByte(b) => char::from(b),
+        }
+    }
+}
+
+/// Takes the contents of a C string or raw C string literal (without quotes)
+/// and produces a sequence of units or errors. NULs are reported as
+/// [`EscapeError::NulInCStr`].
+/// Values are returned through invoking of the provided callback.
+pub fn unescape_c_string<F>(literal_text: &str, mode: Mode, callback: &mut F)
+where
+    F: FnMut(Range<usize>, Result<MixedUnit, EscapeError>),
+{
+    let mut callback = |range: Range<usize>, result: Result<char, EscapeError>| {
+        let result = result.and_then(|c| match c {
+            '\0' => Err(EscapeError::NulInCStr),
+            // `\x` escapes are the only way to get a char in this range
+            // which doesn't stand for its UTF-8 encoding.
+            '\u{80}'..='\u{FF}' if literal_text[range.clone()].starts_with("\\x") => {
+                Ok(MixedUnit::HighByte(c as u8))
+            }
+            c => Ok(MixedUnit::Char(c)),
+        });
+        callback(range, result)
+    };
+    match mode {
+        Mode::CStr => unescape_str_or_byte_str(literal_text, mode, &mut callback),
+        Mode::RawCStr => unescape_raw_str_or_byte_str(literal_text, mode, &mut callback),
+        _ => panic!("not a C string mode: {:?}", mode),
+    }
+}
+
 /// Takes a contents of a char literal (without quotes), and returns an
 /// unescaped char or an error
 pub fn unescape_char(literal_text: &str) -> Result<char, (usize, EscapeError)> {
@@ -128,6 +313,244 @@ pub fn unescape_byte(literal_text: &str) -> Result<u8, (usize, EscapeError)> {
         .map_err(|err| (literal_text.len() - chars.as_str().len(), err))
 }
 
+/// The value of a literal after unescaping.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CookedValue {
+    Char(char),
+    Byte(u8),
+    /// The value of a string or raw string literal.
+    Str(String),
+    /// The value of a byte string or raw byte string literal.
+    ByteStr(Vec<u8>),
+    /// The value of a C string or raw C string literal, without the
+    /// terminating NUL.
+    CStr(Vec<u8>),
+}
+
+/// Takes a contents of a literal (without quotes) and returns its value
+/// together with every error and warning found in it.
+///
+/// The value is `None` if any of the errors is fatal (see
+/// [`EscapeError::is_fatal`]), warnings don't prevent producing a value.
+pub fn unescape_collect(
+    literal_text: &str,
+    mode: Mode,
+) -> (Option<CookedValue>, Vec<(Range<usize>, EscapeError)>) {
+    let mut chars = String::new();
+    let mut errors = Vec::new();
+    if let Mode::CStr | Mode::RawCStr = mode {
+        let mut bytes = Vec::with_capacity(literal_text.len());
+        unescape_c_string(literal_text, mode, &mut |range, result| match result {
+            Ok(unit) => unit.push_to(&mut bytes),
+            Err(err) => errors.push((range, err)),
+        });
+        let fatal = errors.iter().any(|(_, err)| err.is_fatal());
+        return (if fatal { None } else { Some(CookedValue::CStr(bytes)) }, errors);
+    }
+    match mode {
+        #[cfg(feature = "simd")]
+        Mode::Str | Mode::ByteStr => {
+            unescape_str_or_byte_str_into(literal_text, mode, &mut chars, &mut errors)
+        }
+        _ => unescape_literal(literal_text, mode, &mut |range, result| match result {
+            Ok(c) => chars.push(c),
+            Err(err) => errors.push((range, err)),
+        }),
+    }
+    if errors.iter().any(|(_, err)| err.is_fatal()) {
+        return (None, errors);
+    }
+    let value = match mode {
+        Mode::Char => CookedValue::Char(chars.chars().next().unwrap()),
+        Mode::Byte => CookedValue::Byte(byte_from_char(chars.chars().next().unwrap())),
+        Mode::Str | Mode::RawStr => CookedValue::Str(chars),
+        Mode::ByteStr | Mode::RawByteStr => {
+            CookedValue::ByteStr(chars.chars().map(byte_from_char).collect())
+        }
+        Mode::CStr | Mode::RawCStr => unreachable!("handled above"),
+    };
+    (Some(value), errors)
+}
+
+/// Takes the contents of a string or raw string literal (without quotes)
+/// and returns its value, or the first fatal error together with its range.
+/// Warnings are ignored.
+pub fn unescape_str_to_string(
+    literal_text: &str,
+    mode: Mode,
+) -> Result<String, (Range<usize>, EscapeError)> {
+    assert!(matches!(mode, Mode::Str | Mode::RawStr), "not a string mode: {:?}", mode);
+    let mut res = String::with_capacity(literal_text.len());
+    let mut first_error = None;
+    unescape_literal(literal_text, mode, &mut |range, result| match result {
+        Ok(c) => res.push(c),
+        Err(err) => keep_first_fatal(&mut first_error, range, err),
+    });
+    first_error.map_or(Ok(res), Err)
+}
+
+/// Like [`unescape_str_to_string`], but for byte strings and raw byte strings.
+pub fn unescape_byte_str_to_vec(
+    literal_text: &str,
+    mode: Mode,
+) -> Result<Vec<u8>, (Range<usize>, EscapeError)> {
+    assert!(matches!(mode, Mode::ByteStr | Mode::RawByteStr), "not a byte string mode: {:?}", mode);
+    let mut res = Vec::with_capacity(literal_text.len());
+    let mut first_error = None;
+    unescape_byte_literal(literal_text, mode, &mut |range, result| match result {
+        Ok(b) => res.push(b),
+        Err(err) => keep_first_fatal(&mut first_error, range, err),
+    });
+    first_error.map_or(Ok(res), Err)
+}
+
+/// Like [`unescape_str_to_string`], but for C strings and raw C strings. The
+/// value doesn't include the terminating NUL.
+pub fn unescape_c_str_to_vec(
+    literal_text: &str,
+    mode: Mode,
+) -> Result<Vec<u8>, (Range<usize>, EscapeError)> {
+    let mut res = Vec::with_capacity(literal_text.len());
+    let mut first_error = None;
+    unescape_c_string(literal_text, mode, &mut |range, result| match result {
+        Ok(unit) => unit.push_to(&mut res),
+        Err(err) => keep_first_fatal(&mut first_error, range, err),
+    });
+    first_error.map_or(Ok(res), Err)
+}
+
+/// Takes the contents of a literal (without quotes) and returns its value on
+/// a best-effort basis, together with every error and warning found in it.
+///
+/// Unlike [`unescape_collect`], errors don't prevent producing a value: each
+/// char or escape with a fatal error is replaced by U+FFFD REPLACEMENT
+/// CHARACTER. Chars are produced like by [`unescape_literal`], so the bytes
+/// of byte strings and C strings become the chars with the same value.
+pub fn unescape_lossy(
+    literal_text: &str,
+    mode: Mode,
+) -> (String, Vec<(Range<usize>, EscapeError)>) {
+    unescape_lossy_with(literal_text, mode, char::REPLACEMENT_CHARACTER)
+}
+
+/// Like [`unescape_lossy`], but replaces the chars and escapes with errors by
+/// `placeholder`.
+pub fn unescape_lossy_with(
+    literal_text: &str,
+    mode: Mode,
+    placeholder: char,
+) -> (String, Vec<(Range<usize>, EscapeError)>) {
+    let mut res = String::with_capacity(literal_text.len());
+    let mut errors = Vec::new();
+    unescape_literal(literal_text, mode, &mut |range, result| match result {
+        Ok(c) => res.push(c),
+        Err(err) => {
+            if err.is_fatal() {
+                res.push(placeholder);
+            }
+            errors.push((range, err));
+        }
+    });
+    (res, errors)
+}
+
+/// Stores `err` in `first_error` if it is the first fatal error.
+fn keep_first_fatal(
+    first_error: &mut Option<(Range<usize>, EscapeError)>,
+    range: Range<usize>,
+    err: EscapeError,
+) {
+    if err.is_fatal() && first_error.is_none() {
+        *first_error = Some((range, err));
+    }
+}
+
+/// Produces replacement text for the part of a literal an error was reported
+/// for, for errors with an obvious mechanical fix.
+///
+/// `original` is the slice of the literal contents covered by the error's
+/// range, as reported by [`unescape_literal`]. Returns `None` if there is no
+/// unambiguous fix for the error.
+pub fn suggest_replacement(error: &EscapeError, mode: Mode, original: &str) -> Option<String> {
+    let last = original.chars().next_back()?;
+    match error {
+        // `\z` was probably meant as a literal backslash followed by `z`.
+        EscapeError::InvalidEscape if original.starts_with('\\') && last != '\r' => {
+            Some(format!("\\{}", original))
+        }
+        EscapeError::BareCarriageReturn => Some("\\r".to_string()),
+        EscapeError::EscapeOnlyChar => Some(last.escape_default().to_string()),
+        // `\xFF` in a string means U+00FF, which needs a unicode escape.
+        EscapeError::OutOfRangeHexEscape => {
+            let digits = original.strip_prefix("\\x")?;
+            let value = u32::from_str_radix(digits, 16).ok()?;
+            Some(format!("\\u{{{:x}}}", value))
+        }
+        EscapeError::NonAsciiCharInByte if (last as u32) <= 0xFF => {
+            Some(format!("\\x{:X}", last as u32))
+        }
+        EscapeError::NonAsciiCharInByte if matches!(mode, Mode::ByteStr) => {
+            let mut utf8 = [0; 4];
+            Some(last.encode_utf8(&mut utf8).bytes().map(|b| format!("\\x{:X}", b)).collect())
+        }
+        _ => None,
+    }
+}
+
+/// Takes the contents of a raw byte string literal (without quotes) and
+/// returns the equivalent byte string literal, including the prefix and the
+/// quotes.
+///
+/// Used to suggest a fix for non-ASCII characters in raw byte strings, which
+/// have no escapes to write them with.
+pub fn to_escaped_byte_str_literal(literal_text: &str) -> String {
+    let mut res = String::with_capacity(literal_text.len() + 3);
+    res.push_str("b\"");
+    for byte in literal_text.bytes() {
+        match byte {
+            b'\\' => res.push_str("\\\\"),
+            b'"' => res.push_str("\\\""),
+            b'\r' => res.push_str("\\r"),
+            _ if byte.is_ascii() => res.push(char::from(byte)),
+            _ => res.push_str(&format!("\\x{:X}", byte)),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Takes a contents of a char or byte literal (without quotes) and returns
+/// the equivalent string or byte string literal, including the quotes.
+///
+/// Used to suggest a string literal for char literals with more than one
+/// character in them; escapes are adjusted for the change of quotes.
+pub fn to_str_literal(literal_text: &str, mode: Mode) -> String {
+    assert!(mode.in_single_quotes());
+    let mut res = String::with_capacity(literal_text.len() + 3);
+    if mode.is_bytes() {
+        res.push('b');
+    }
+    res.push('"');
+    let mut chars = literal_text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => res.push('\''),
+                Some(c) => {
+                    res.push('\\');
+                    res.push(c);
+                }
+                None => res.push_str("\\\\"),
+            },
+            '"' => res.push_str("\\\""),
+            '\r' => res.push_str("\\r"),
+            _ => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
 /// What kind of literal do we parse.
 #[derive(Debug, Clone, Copy)]
 pub enum Mode {
@@ -137,13 +560,20 @@ pub enum Mode {
     ByteStr,
     RawStr,
     RawByteStr,
+    CStr,
+    RawCStr,
 }
 
 impl Mode {
     pub fn in_single_quotes(self) -> bool {
         match self {
             Mode::Char | Mode::Byte => true,
-            Mode::Str | Mode::ByteStr | Mode::RawStr | Mode::RawByteStr => false,
+            Mode::Str
+            | Mode::ByteStr
+            | Mode::RawStr
+            | Mode::RawByteStr
+            | Mode::CStr
+            | Mode::RawCStr => false,
         }
     }
 
@@ -154,7 +584,7 @@ pub fn in_double_quotes(self) -> bool {
     pub fn is_bytes(self) -> bool {
         match self {
             Mode::Byte | Mode::ByteStr | Mode::RawByteStr => true,
-            Mode::Char | Mode::Str | Mode::RawStr => false,
+            Mode::Char | Mode::Str | Mode::RawStr | Mode::CStr | Mode::RawCStr => false,
         }
     }
 }
@@ -184,8 +614,9 @@ fn scan_escape(chars: &mut Chars<'_>, mode: Mode) -> Result<char, EscapeError> {
 
             let value = hi * 16 + lo;
 
-            // For a byte literal verify that it is within ASCII range.
-            if !mode.is_bytes() && !is_ascii(value) {
+            // For a byte literal verify that it is within ASCII range. C
+            // strings can contain any byte.
+            if !mode.is_bytes() && !matches!(mode, Mode::CStr) && !is_ascii(value) {
                 return Err(EscapeError::OutOfRangeHexEscape);
             }
             let value = value as u8;
@@ -225,7 +656,7 @@ fn scan_escape(chars: &mut Chars<'_>, mode: Mode) -> Result<char, EscapeError> {
                             return Err(EscapeError::UnicodeEscapeInByte);
                         }
 
-                        break std::char::from_u32(value).ok_or_else(|| {
+                        break core::char::from_u32(value).ok_or_else(|| {
                             if value > 0x10FFFF {
                                 EscapeError::OutOfRangeUnicodeEscape
                             } else {
@@ -283,35 +714,58 @@ fn unescape_str_or_byte_str<F>(src: &str, mode: Mode, callback: &mut F)
     F: FnMut(Range<usize>, Result<char, EscapeError>),
 {
     assert!(mode.in_double_quotes());
-    let initial_len = src.len();
     let mut chars = src.chars();
-    while let Some(first_char) = chars.next() {
-        let start = initial_len - chars.as_str().len() - first_char.len_utf8();
-
-        let unescaped_char = match first_char {
-            '\\' => {
-                let second_char = chars.clone().next();
-                match second_char {
-                    Some('\n') => {
-                        // Rust language specification requires us to skip whitespaces
-                        // if unescaped '\' character is followed by '\n'.
-                        // For details see [Rust language reference]
-                        // (https://doc.rust-lang.org/reference/tokens.html#string-literals).
-                        skip_ascii_whitespace(&mut chars, start, callback);
-                        continue;
-                    }
-                    _ => scan_escape(&mut chars, mode),
-                }
+    while !chars.as_str().is_empty() {
+        // Chars which unescape to themselves are found a word at a time.
+        #[cfg(feature = "simd")]
+        {
+            let tail = chars.as_str();
+            let clean = clean_prefix_len(tail.as_bytes(), mode.is_bytes());
+            let start = src.len() - tail.len();
+            for (i, c) in tail[..clean].char_indices() {
+                callback(start + i..start + i + c.len_utf8(), Ok(c));
             }
-            '\n' => Ok('\n'),
-            '\t' => Ok('\t'),
-            '"' => Err(EscapeError::EscapeOnlyChar),
-            '\r' => Err(EscapeError::BareCarriageReturn),
-            _ => ascii_check(first_char, mode),
-        };
-        let end = initial_len - chars.as_str().len();
-        callback(start..end, unescaped_char);
+            chars = tail[clean..].chars();
+        }
+        unescape_str_unit(src.len(), &mut chars, mode, callback);
     }
+}
+
+/// Unescapes the char or escape sequence at the start of `chars`, which
+/// iterates over the tail of a string of length `initial_len`.
+fn unescape_str_unit<F>(initial_len: usize, chars: &mut Chars<'_>, mode: Mode, callback: &mut F)
+where
+    F: FnMut(Range<usize>, Result<char, EscapeError>),
+{
+    let first_char = match chars.next() {
+        Some(c) => c,
+        None => return,
+    };
+    let start = initial_len - chars.as_str().len() - first_char.len_utf8();
+
+    let unescaped_char = match first_char {
+        '\\' => {
+            let second_char = chars.clone().next();
+            match second_char {
+                Some('\n') => {
+                    // Rust language specification requires us to skip whitespaces
+                    // if unescaped '\' character is followed by '\n'.
+                    // For details see [Rust language reference]
+                    // (https://doc.rust-lang.org/reference/tokens.html#string-literals).
+                    skip_ascii_whitespace(chars, start, callback);
+                    return;
+                }
+                _ => scan_escape(chars, mode),
+            }
+        }
+        '\n' => Ok('\n'),
+        '\t' => Ok('\t'),
+        '"' => Err(EscapeError::EscapeOnlyChar),
+        '\r' => Err(EscapeError::BareCarriageReturn),
+        _ => ascii_check(first_char, mode),
+    };
+    let end = initial_len - chars.as_str().len();
+    callback(start..end, unescaped_char);
 
     fn skip_ascii_whitespace<F>(chars: &mut Chars<'_>, start: usize, callback: &mut F)
     where
@@ -340,6 +794,67 @@ fn skip_ascii_whitespace<F>(chars: &mut Chars<'_>, start: usize, callback: &mut
     }
 }
 
+/// Like [`unescape_str_or_byte_str`], but appends the unescaped chars to
+/// `out`, copying stretches without escapes in bulk.
+#[cfg(feature = "simd")]
+fn unescape_str_or_byte_str_into(
+    src: &str,
+    mode: Mode,
+    out: &mut String,
+    errors: &mut Vec<(Range<usize>, EscapeError)>,
+) {
+    assert!(mode.in_double_quotes());
+    let mut chars = src.chars();
+    loop {
+        let tail = chars.as_str();
+        let clean = clean_prefix_len(tail.as_bytes(), mode.is_bytes());
+        out.push_str(&tail[..clean]);
+        chars = tail[clean..].chars();
+        if chars.as_str().is_empty() {
+            break;
+        }
+        unescape_str_unit(src.len(), &mut chars, mode, &mut |range, result| match result {
+            Ok(c) => out.push(c),
+            Err(err) => errors.push((range, err)),
+        });
+    }
+}
+
+/// Returns the length of the longest prefix of `bytes` which unescapes to
+/// itself, i.e. doesn't contain a backslash, a quote, a carriage return or,
+/// for byte strings, a non-ASCII byte.
+///
+/// Looks at eight bytes at a time, see
+/// <https://graphics.stanford.edu/~seander/bithacks.html#ValueInWord>.
+#[cfg(feature = "simd")]
+fn clean_prefix_len(bytes: &[u8], is_bytes: bool) -> usize {
+    const LO: u64 = 0x0101_0101_0101_0101;
+    const HI: u64 = 0x8080_8080_8080_8080;
+    let is_special = |b: u8| b == b'\\' || b == b'"' || b == b'\r' || (is_bytes && !b.is_ascii());
+    // Sets the high bit of the first special byte, bits above it may be set
+    // spuriously.
+    let special_mask = |word: u64| {
+        let eq = |b: u8| {
+            let x = word ^ (LO * u64::from(b));
+            x.wrapping_sub(LO) & !x & HI
+        };
+        let non_ascii = if is_bytes { word & HI } else { 0 };
+        eq(b'\\') | eq(b'"') | eq(b'\r') | non_ascii
+    };
+
+    let mut chunks = bytes.chunks_exact(8);
+    let mut len = 0;
+    for chunk in &mut chunks {
+        let mask = special_mask(u64::from_le_bytes(chunk.try_into().unwrap()));
+        if mask != 0 {
+            return len + mask.trailing_zeros() as usize / 8;
+        }
+        len += 8;
+    }
+    let rest = chunks.remainder();
+    len + rest.iter().position(|&b| is_special(b)).unwrap_or(rest.len())
+}
+
 /// Takes a contents of a string literal (without quotes) and produces a
 /// sequence of characters or errors.
 /// NOTE: Raw strings do not perform any explicit character escaping, here we
@@ -295,3 +295,225 @@ fn check(literal: &str, expected: &[(Range<usize>, Result<u8, EscapeError>)]) {
         &[(0..4, Err(EscapeError::NonAsciiCharInByteString)), (4..5, Ok(byte_from_char('a')))],
     );
 }
+
+#[test]
+fn test_unescape_c_string() {
+    fn check(
+        literal: &str,
+        mode: Mode,
+        expected: &[(Range<usize>, Result<MixedUnit, EscapeError>)],
+    ) {
+        let mut unescaped = Vec::with_capacity(literal.len());
+        unescape_c_string(literal, mode, &mut |range, res| unescaped.push((range, res)));
+        assert_eq!(unescaped, expected);
+    }
+
+    check(
+        r"a\xff\u{e9}",
+        Mode::CStr,
+        &[
+            (0..1, Ok(MixedUnit::Char('a'))),
+            (1..5, Ok(MixedUnit::HighByte(0xff))),
+            (5..11, Ok(MixedUnit::Char('é'))),
+        ],
+    );
+    check(r"\x7f", Mode::CStr, &[(0..4, Ok(MixedUnit::Char('\x7f')))]);
+    check(r"\0", Mode::CStr, &[(0..2, Err(EscapeError::NulInCStr))]);
+    check(
+        r"a\0",
+        Mode::RawCStr,
+        &[
+            (0..1, Ok(MixedUnit::Char('a'))),
+            (1..2, Ok(MixedUnit::Char('\\'))),
+            (2..3, Ok(MixedUnit::Char('0'))),
+        ],
+    );
+    check("\0", Mode::RawCStr, &[(0..1, Err(EscapeError::NulInCStr))]);
+}
+
+#[test]
+fn test_unescape_to_value() {
+    assert_eq!(unescape_str_to_string(r"a\tb\u{e9}", Mode::Str), Ok("a\tbé".to_string()));
+    assert_eq!(unescape_str_to_string(r"a\n", Mode::RawStr), Ok(r"a\n".to_string()));
+    assert_eq!(
+        unescape_str_to_string("a\rb", Mode::RawStr),
+        Err((1..2, EscapeError::BareCarriageReturnInRawString))
+    );
+    // Warnings don't prevent producing a value.
+    assert_eq!(unescape_str_to_string("a\\\n\n b", Mode::Str), Ok("ab".to_string()));
+    // Only the first error is returned.
+    assert_eq!(
+        unescape_str_to_string(r"a\q\u{}", Mode::Str),
+        Err((1..3, EscapeError::InvalidEscape))
+    );
+
+    assert_eq!(unescape_byte_str_to_vec(r"a\xff", Mode::ByteStr), Ok(vec![b'a', 0xff]));
+    assert_eq!(unescape_byte_str_to_vec(r"\x", Mode::RawByteStr), Ok(b"\\x".to_vec()));
+    assert_eq!(
+        unescape_byte_str_to_vec("aé", Mode::ByteStr),
+        Err((1..3, EscapeError::NonAsciiCharInByte))
+    );
+
+    assert_eq!(unescape_c_str_to_vec(r"\xffé", Mode::CStr), Ok(vec![0xff, 0xc3, 0xa9]));
+    assert_eq!(unescape_c_str_to_vec(r"a\0", Mode::CStr), Err((1..3, EscapeError::NulInCStr)));
+}
+
+#[test]
+fn test_unescape_lossy() {
+    assert_eq!(unescape_lossy(r"a\tb", Mode::Str), ("a\tb".to_string(), vec![]));
+    assert_eq!(
+        unescape_lossy(r"a\qb\u{}c", Mode::Str),
+        (
+            "a\u{FFFD}b\u{FFFD}c".to_string(),
+            vec![(1..3, EscapeError::InvalidEscape), (4..8, EscapeError::EmptyUnicodeEscape)]
+        )
+    );
+    // Warnings don't replace anything.
+    assert_eq!(
+        unescape_lossy("a\\\n\n b\r", Mode::Str),
+        (
+            "ab\u{FFFD}".to_string(),
+            vec![
+                (1..5, EscapeError::MultipleSkippedLinesWarning),
+                (6..7, EscapeError::BareCarriageReturn)
+            ]
+        )
+    );
+    assert_eq!(
+        unescape_lossy_with("aé", Mode::ByteStr, '?'),
+        ("a?".to_string(), vec![(1..3, EscapeError::NonAsciiCharInByte)])
+    );
+    assert_eq!(
+        unescape_lossy_with("ab", Mode::Char, '?'),
+        ("?".to_string(), vec![(0..2, EscapeError::MoreThanOneChar)])
+    );
+}
+
+#[test]
+fn test_unescape_collect() {
+    assert_eq!(unescape_collect(r"\n", Mode::Char), (Some(CookedValue::Char('\n')), vec![]));
+    assert_eq!(unescape_collect(r"\x7f", Mode::Byte), (Some(CookedValue::Byte(0x7f)), vec![]));
+    assert_eq!(unescape_collect("", Mode::Char), (None, vec![(0..0, EscapeError::ZeroChars)]));
+    assert_eq!(
+        unescape_collect(r"a\tb", Mode::Str),
+        (Some(CookedValue::Str("a\tb".to_string())), vec![])
+    );
+    assert_eq!(
+        unescape_collect(r"\xff\0", Mode::ByteStr),
+        (Some(CookedValue::ByteStr(vec![0xff, 0])), vec![])
+    );
+    assert_eq!(
+        unescape_collect(r"\xffé", Mode::CStr),
+        (Some(CookedValue::CStr(vec![0xff, 0xc3, 0xa9])), vec![])
+    );
+    assert_eq!(unescape_collect(r"a\0", Mode::CStr), (None, vec![(1..3, EscapeError::NulInCStr)]));
+
+    // Every error is reported.
+    assert_eq!(
+        unescape_collect("\\q a \\u{} \r", Mode::Str),
+        (
+            None,
+            vec![
+                (0..2, EscapeError::InvalidEscape),
+                (5..9, EscapeError::EmptyUnicodeEscape),
+                (10..11, EscapeError::BareCarriageReturn),
+            ]
+        )
+    );
+
+    // Warnings don't prevent cooking the value.
+    assert_eq!(
+        unescape_collect("\\\n\n x", Mode::Str),
+        (
+            Some(CookedValue::Str("x".to_string())),
+            vec![(0..4, EscapeError::MultipleSkippedLinesWarning)]
+        )
+    );
+}
+
+#[test]
+#[cfg(feature = "simd")]
+fn test_unescape_str_matches_scalar() {
+    let fragments =
+        ["a", "\\n", "\\x7f", "\"", "\r", "\r\n", "é", "\\u{e9}", "\\\n  ", "\\q", "\t", "~"];
+    for mode in [Mode::Str, Mode::ByteStr, Mode::CStr] {
+        for (i, fragment) in fragments.iter().enumerate() {
+            for pad in 0..17 {
+                let other = fragments[(i + pad) % fragments.len()];
+                let src =
+                    format!("{}{}{}{}", "x".repeat(pad), fragment, "y".repeat(17 - pad), other);
+                // The scalar loop, without the word-at-a-time fast path.
+                let mut expected = Vec::new();
+                let mut chars = src.chars();
+                while !chars.as_str().is_empty() {
+                    unescape_str_unit(src.len(), &mut chars, mode, &mut |range, result| {
+                        expected.push((range, result))
+                    });
+                }
+                let mut actual = Vec::new();
+                unescape_str_or_byte_str(&src, mode, &mut |range, result| {
+                    actual.push((range, result))
+                });
+                assert_eq!(actual, expected, "{:?}", src);
+
+                let mut chars = String::new();
+                let mut errors = Vec::new();
+                unescape_str_or_byte_str_into(&src, mode, &mut chars, &mut errors);
+                let expected_chars: String =
+                    expected.iter().filter_map(|(_, result)| result.ok()).collect();
+                let expected_errors: Vec<_> = expected
+                    .into_iter()
+                    .filter_map(|(range, result)| Some((range, result.err()?)))
+                    .collect();
+                assert_eq!((chars, errors), (expected_chars, expected_errors), "{:?}", src);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_severity() {
+    assert_eq!(EscapeError::InvalidEscape.severity(), Severity::Error);
+    assert_eq!(EscapeError::BareCarriageReturnInRawString.severity(), Severity::Error);
+    assert_eq!(EscapeError::UnskippedWhitespaceWarning.severity(), Severity::Warning);
+    assert!(!EscapeError::MultipleSkippedLinesWarning.is_fatal());
+    assert!(Severity::Warning < Severity::Error);
+}
+
+#[test]
+fn test_suggest_replacement() {
+    fn check(literal: &str, mode: Mode, expected: &[&str]) {
+        let (_, errors) = unescape_collect(literal, mode);
+        let fixes: Vec<_> = errors
+            .iter()
+            .filter_map(|(range, err)| suggest_replacement(err, mode, &literal[range.clone()]))
+            .collect();
+        assert_eq!(fixes, expected);
+    }
+
+    check(r"\d+", Mode::Str, &[r"\\d"]);
+    check(r"\z", Mode::Char, &[r"\\z"]);
+    check("a\rb", Mode::Str, &[r"\r"]);
+    check("\r", Mode::Char, &[r"\r"]);
+    check(r"\xFF", Mode::Str, &[r"\u{ff}"]);
+    check(r"\x80", Mode::Char, &[r"\u{80}"]);
+    check("\t", Mode::Char, &[r"\t"]);
+    check("'", Mode::Byte, &[r"\'"]);
+    check("é", Mode::Byte, &[r"\xE9"]);
+    check("🦀", Mode::ByteStr, &[r"\xF0\x9F\xA6\x80"]);
+
+    // No unambiguous fix.
+    check("🦀", Mode::Byte, &[]);
+    check(r"\u{}", Mode::Str, &[]);
+    check("\r", Mode::RawStr, &[]);
+}
+
+#[test]
+fn test_to_str_literal() {
+    assert_eq!(to_str_literal("ab", Mode::Char), r#""ab""#);
+    assert_eq!(to_str_literal("ab", Mode::Byte), r#"b"ab""#);
+    assert_eq!(to_str_literal(r#"\'"#, Mode::Char), r#""'""#);
+    assert_eq!(to_str_literal(r#"a""#, Mode::Char), r#""a\"""#);
+    assert_eq!(to_str_literal(r"\n\u{1F980}", Mode::Char), r#""\n\u{1F980}""#);
+    assert_eq!(to_str_literal("a\r", Mode::Char), r#""a\r""#);
+}
@@ -0,0 +1,72 @@
+//! Lexing of UTF-16 encoded input.
+//!
+//! Editors often hold documents as UTF-16. The types in this module decode
+//! the input on the fly while lexing, so no transcoded copy of the document is
+//! made, and all offsets and lengths reported by the lexer (token lengths,
+//! `suffix_start`, etc.) are measured in UTF-16 code units.
+
+use crate::cursor::Source;
+use crate::{Cursor, LexerConfig, Token};
+
+#[cfg(test)]
+mod tests;
+
+/// Iterator over the chars of UTF-16 encoded input.
+///
+/// Unpaired surrogates are decoded as `U+FFFD REPLACEMENT CHARACTER`, which the
+/// lexer reports as an `Unknown` token.
+#[derive(Clone, Debug)]
+pub struct Utf16Chars<'a> {
+    units: &'a [u16],
+}
+
+impl<'a> Utf16Chars<'a> {
+    pub fn new(units: &'a [u16]) -> Utf16Chars<'a> {
+        Utf16Chars { units }
+    }
+}
+
+impl Iterator for Utf16Chars<'_> {
+    type Item = char;
+
+    fn next(&mut self) -> Option<char> {
+        let (&first, rest) = self.units.split_first()?;
+        if !(0xD800..0xE000).contains(&first) {
+            self.units = rest;
+            // Not a surrogate, so this is a valid scalar value.
+            return Some(char::from_u32(u32::from(first)).unwrap());
+        }
+        match rest.first() {
+            Some(&second) if first < 0xDC00 && (0xDC00..0xE000).contains(&second) => {
+                self.units = &rest[1..];
+                let c =
+                    0x10000 + ((u32::from(first) - 0xD800) << 10) + (u32::from(second) - 0xDC00);
+                Some(char::from_u32(c).unwrap())
+            }
+            _ => {
+                self.units = rest;
+                Some(char::REPLACEMENT_CHARACTER)
+            }
+        }
+    }
+}
+
+impl Source for Utf16Chars<'_> {
+    fn remaining_len(&self) -> usize {
+        self.units.len()
+    }
+}
+
+impl<'a> Cursor<'a, Utf16Chars<'a>> {
+    /// Creates a cursor at the start of UTF-16 encoded `input`.
+    pub fn new_utf16(input: &'a [u16]) -> Cursor<'a, Utf16Chars<'a>> {
+        Cursor::from_source(Utf16Chars::new(input), &LexerConfig::DEFAULT)
+    }
+}
+
+/// Creates an iterator that produces tokens from UTF-16 encoded input. Token
+/// lengths are measured in UTF-16 code units.
+pub fn tokenize_utf16(input: &[u16]) -> impl Iterator<Item = Token> + '_ {
+    let mut cursor = Cursor::new_utf16(input);
+    core::iter::from_fn(move || if cursor.is_eof() { None } else { Some(cursor.advance_token()) })
+}
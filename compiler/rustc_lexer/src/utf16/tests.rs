@@ -0,0 +1,40 @@
+use super::*;
+use crate::{tokenize, TokenKind};
+
+fn utf16(s: &str) -> Vec<u16> {
+    s.encode_utf16().collect()
+}
+
+#[test]
+fn test_decoding() {
+    let src = "a\u{e9}\u{1F980}";
+    assert_eq!(Utf16Chars::new(&utf16(src)).collect::<String>(), src);
+
+    // Unpaired surrogates.
+    let chars: String = Utf16Chars::new(&[0xDC00, 0x61, 0xD800]).collect();
+    assert_eq!(chars, "\u{FFFD}a\u{FFFD}");
+}
+
+#[test]
+fn test_tokenize_utf16() {
+    let src = "let s = \"\u{1F980}\u{e9}\"; // \u{1F980}\nr#\"\u{1F980}\"#";
+    let units = utf16(src);
+    let mut pos = 0;
+    let mut utf16_pos = 0;
+    let tokens: Vec<_> = tokenize_utf16(&units).collect();
+    let expected: Vec<_> = tokenize(src).collect();
+    assert_eq!(tokens.len(), expected.len());
+    for (token, expected) in tokens.iter().zip(&expected) {
+        let text = &src[pos..pos + expected.len];
+        assert_eq!(token.len, text.encode_utf16().count());
+        if let TokenKind::Literal { suffix_start, .. } = expected.kind {
+            let suffix_start = src[pos..pos + suffix_start].encode_utf16().count();
+            assert!(
+                matches!(token.kind, TokenKind::Literal { suffix_start: s, .. } if s == suffix_start)
+            );
+        }
+        pos += expected.len;
+        utf16_pos += token.len;
+    }
+    assert_eq!(utf16_pos, units.len());
+}
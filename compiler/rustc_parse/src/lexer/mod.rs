@@ -70,8 +70,8 @@ fn next_token(&mut self) -> (Spacing, Token) {
         let text: &str = &self.src[start_src_index..self.end_src_index];
         let is_beginning_of_file = self.pos == self.start_pos;
         if is_beginning_of_file {
-            if let Some(shebang_len) = rustc_lexer::strip_shebang(text) {
-                self.pos = self.pos + BytePos::from_usize(shebang_len);
+            if let Some(shebang) = rustc_lexer::strip_shebang(text) {
+                self.pos = self.pos + BytePos::from_usize(shebang.len);
                 spacing = Spacing::Alone;
             }
         }
@@ -86,7 +86,7 @@ fn next_token(&mut self) -> (Spacing, Token) {
                 return (spacing, Token::new(token::Eof, span));
             }
 
-            let token = rustc_lexer::first_token(text);
+            let token = self.split_unsupported_token(rustc_lexer::first_token(text), text);
 
             let start = self.pos;
             self.pos = self.pos + BytePos::from_usize(token.len);
@@ -153,6 +153,46 @@ fn lint_unicode_text_flow(&self, start: BytePos) {
         }
     }
 
+    /// Splits tokens which rustc doesn't support yet back into the tokens they
+    /// were lexed as before `rustc_lexer` learned them, so that macros keep
+    /// seeing the same token trees: `'r#a` is `'r`, `#` and `a`, `0x1.ap1` is
+    /// `0x1`, `.` and `ap1`, and before Rust 2021, `c"a"` is the identifier `c`
+    /// followed by the string `"a"`.
+    fn split_unsupported_token(
+        &self,
+        token: rustc_lexer::Token,
+        text: &str,
+    ) -> rustc_lexer::Token {
+        match token.kind {
+            rustc_lexer::TokenKind::RawLifetime => rustc_lexer::Token {
+                kind: rustc_lexer::TokenKind::Lifetime { starts_with_number: false },
+                len: 2,
+            },
+            rustc_lexer::TokenKind::Literal {
+                kind:
+                    rustc_lexer::LiteralKind::CStr { .. } | rustc_lexer::LiteralKind::RawCStr { .. },
+                ..
+            } if !self.mk_sp(self.pos, self.pos).rust_2021() => {
+                let len = if text.as_bytes()[1] == b'r' { 2 } else { 1 };
+                let followed_by = if text.as_bytes()[len] == b'#' {
+                    rustc_lexer::PrefixFollower::Pound
+                } else {
+                    rustc_lexer::PrefixFollower::Str
+                };
+                rustc_lexer::Token {
+                    kind: rustc_lexer::TokenKind::UnknownPrefix { followed_by },
+                    len,
+                }
+            }
+            // The text up to the `p` lexes as the first of the old tokens.
+            rustc_lexer::TokenKind::Literal {
+                kind: rustc_lexer::LiteralKind::Float { hex_exponent: Some(hex_exponent), .. },
+                ..
+            } => rustc_lexer::first_token(&text[..hex_exponent as usize]),
+            _ => token,
+        }
+    }
+
     /// Turns simple `rustc_lexer::TokenKind` enum into a rich
     /// `rustc_ast::TokenKind`. This turns strings into interned
     /// symbols and runs additional validation.
@@ -197,12 +237,12 @@ fn cook_lexer_token(&self, token: rustc_lexer::TokenKind, start: BytePos) -> Opt
                 let content = self.str_from_to(content_start, content_end);
                 self.cook_doc_comment(content_start, content, CommentKind::Block, doc_style)
             }
-            rustc_lexer::TokenKind::Whitespace => return None,
+            rustc_lexer::TokenKind::Whitespace { .. } => return None,
             rustc_lexer::TokenKind::Ident
             | rustc_lexer::TokenKind::RawIdent
-            | rustc_lexer::TokenKind::UnknownPrefix => {
+            | rustc_lexer::TokenKind::UnknownPrefix { .. } => {
                 let is_raw_ident = token == rustc_lexer::TokenKind::RawIdent;
-                let is_unknown_prefix = token == rustc_lexer::TokenKind::UnknownPrefix;
+                let is_unknown_prefix = matches!(token, rustc_lexer::TokenKind::UnknownPrefix { .. });
                 let mut ident_start = start;
                 if is_raw_ident {
                     ident_start = ident_start + BytePos(2);
@@ -269,6 +309,12 @@ fn cook_lexer_token(&self, token: rustc_lexer::TokenKind, start: BytePos) -> Opt
                 };
                 token::Literal(token::Lit { kind, symbol, suffix })
             }
+            rustc_lexer::TokenKind::PrefixedLiteral { .. } => {
+                unreachable!("rustc doesn't register custom literal prefixes")
+            }
+            rustc_lexer::TokenKind::GuardedStr { .. } => {
+                unreachable!("guarded strings are only lexed for Rust 2024")
+            }
             rustc_lexer::TokenKind::Lifetime { starts_with_number } => {
                 // Include the leading `'` in the real identifier, for macro
                 // expansion purposes. See #12512 for the gory details of why
@@ -280,6 +326,9 @@ fn cook_lexer_token(&self, token: rustc_lexer::TokenKind, start: BytePos) -> Opt
                 let ident = Symbol::intern(lifetime_name);
                 token::Lifetime(ident)
             }
+            rustc_lexer::TokenKind::RawLifetime => {
+                unreachable!("raw lifetimes are split by `split_unsupported_token`")
+            }
             rustc_lexer::TokenKind::Semi => token::Semi,
             rustc_lexer::TokenKind::Comma => token::Comma,
             rustc_lexer::TokenKind::Dot => token::Dot,
@@ -383,7 +432,7 @@ fn cook_lexer_literal(
                 }
                 (token::Byte, Mode::Byte, 2, 1) // b' '
             }
-            rustc_lexer::LiteralKind::Str { terminated } => {
+            rustc_lexer::LiteralKind::Str { terminated, .. } => {
                 if !terminated {
                     self.sess.span_diagnostic.span_fatal_with_code(
                         self.mk_sp(start, suffix_start),
@@ -393,7 +442,7 @@ fn cook_lexer_literal(
                 }
                 (token::Str, Mode::Str, 1, 1) // " "
             }
-            rustc_lexer::LiteralKind::ByteStr { terminated } => {
+            rustc_lexer::LiteralKind::ByteStr { terminated, .. } => {
                 if !terminated {
                     self.sess.span_diagnostic.span_fatal_with_code(
                         self.mk_sp(start + BytePos(1), suffix_start),
@@ -403,22 +452,30 @@ fn cook_lexer_literal(
                 }
                 (token::ByteStr, Mode::ByteStr, 2, 1) // b" "
             }
-            rustc_lexer::LiteralKind::RawStr { n_hashes, err } => {
+            rustc_lexer::LiteralKind::RawStr { n_hashes, err, .. } => {
                 self.report_raw_str_error(start, err);
-                let n = u32::from(n_hashes);
-                (token::StrRaw(n_hashes), Mode::RawStr, 2 + n, 1 + n) // r##" "##
+                let n = n_hashes;
+                // The default configuration limits raw strings to `u16::MAX` hashes.
+                (token::StrRaw(n_hashes as u16), Mode::RawStr, 2 + n, 1 + n) // r##" "##
             }
-            rustc_lexer::LiteralKind::RawByteStr { n_hashes, err } => {
+            rustc_lexer::LiteralKind::RawByteStr { n_hashes, err, .. } => {
                 self.report_raw_str_error(start, err);
-                let n = u32::from(n_hashes);
-                (token::ByteStrRaw(n_hashes), Mode::RawByteStr, 3 + n, 1 + n) // br##" "##
+                let n = n_hashes;
+                // The default configuration limits raw strings to `u16::MAX` hashes.
+                (token::ByteStrRaw(n_hashes as u16), Mode::RawByteStr, 3 + n, 1 + n) // br##" "##
+            }
+            rustc_lexer::LiteralKind::CStr { .. } | rustc_lexer::LiteralKind::RawCStr { .. } => {
+                self.err_span_(start, suffix_start, "C string literals are not supported");
+                return (token::Err, self.symbol_from_to(start, suffix_start));
             }
-            rustc_lexer::LiteralKind::Int { base, empty_int } => {
-                return if empty_int {
+            rustc_lexer::LiteralKind::Int { base, empty_int, .. } => {
+                return if let Some(missing) = empty_int {
+                    let lo = start + BytePos(missing.start);
+                    let hi = start + BytePos(missing.expected_at);
                     self.sess
                         .span_diagnostic
                         .struct_span_err_with_code(
-                            self.mk_sp(start, suffix_start),
+                            self.mk_sp(lo, hi),
                             "no valid digits found for number",
                             error_code!(E0768),
                         )
@@ -429,9 +486,11 @@ fn cook_lexer_literal(
                     (token::Integer, self.symbol_from_to(start, suffix_start))
                 };
             }
-            rustc_lexer::LiteralKind::Float { base, empty_exponent } => {
-                if empty_exponent {
-                    self.err_span_(start, self.pos, "expected at least one digit in exponent");
+            rustc_lexer::LiteralKind::Float { base, empty_exponent, .. } => {
+                if let Some(missing) = empty_exponent {
+                    let lo = start + BytePos(missing.start);
+                    let hi = start + BytePos(missing.expected_at);
+                    self.err_span_(lo, hi, "expected at least one digit in exponent");
                 }
 
                 match base {
@@ -487,10 +546,19 @@ fn report_raw_str_error(&self, start: BytePos, opt_err: Option<RawStrError>) {
             Some(RawStrError::InvalidStarter { bad_char }) => {
                 self.report_non_started_raw_string(start, bad_char)
             }
-            Some(RawStrError::NoTerminator { expected, found, possible_terminator_offset }) => self
-                .report_unterminated_raw_string(start, expected, possible_terminator_offset, found),
-            Some(RawStrError::TooManyDelimiters { found }) => {
-                self.report_too_many_hashes(start, found)
+            Some(RawStrError::NoTerminator {
+                expected,
+                found,
+                possible_terminator_offset,
+                ..
+            }) => self.report_unterminated_raw_string(
+                start,
+                expected,
+                possible_terminator_offset,
+                found,
+            ),
+            Some(RawStrError::TooManyDelimiters { found, max }) => {
+                self.report_too_many_hashes(start, found, max)
             }
             None => (),
         }
@@ -510,9 +578,9 @@ fn report_non_started_raw_string(&self, start: BytePos, bad_char: char) -> ! {
     fn report_unterminated_raw_string(
         &self,
         start: BytePos,
-        n_hashes: usize,
-        possible_offset: Option<usize>,
-        found_terminators: usize,
+        n_hashes: u32,
+        possible_offset: Option<u32>,
+        found_terminators: u32,
     ) -> ! {
         let mut err = self.sess.span_diagnostic.struct_span_fatal_with_code(
             self.mk_sp(start, start),
@@ -525,18 +593,18 @@ fn report_unterminated_raw_string(
         if n_hashes > 0 {
             err.note(&format!(
                 "this raw string should be terminated with `\"{}`",
-                "#".repeat(n_hashes)
+                "#".repeat(n_hashes as usize)
             ));
         }
 
         if let Some(possible_offset) = possible_offset {
-            let lo = start + BytePos(possible_offset as u32);
-            let hi = lo + BytePos(found_terminators as u32);
+            let lo = start + BytePos(possible_offset);
+            let hi = lo + BytePos(found_terminators);
             let span = self.mk_sp(lo, hi);
             err.span_suggestion(
                 span,
                 "consider terminating the string here",
-                "#".repeat(n_hashes),
+                "#".repeat(n_hashes as usize),
                 Applicability::MaybeIncorrect,
             );
         }
@@ -591,14 +659,14 @@ fn report_unknown_prefix(&self, start: BytePos) {
 
     /// Note: It was decided to not add a test case, because it would be too big.
     /// <https://github.com/rust-lang/rust/pull/50296#issuecomment-392135180>
-    fn report_too_many_hashes(&self, start: BytePos, found: usize) -> ! {
+    fn report_too_many_hashes(&self, start: BytePos, found: u32, max: u32) -> ! {
         self.fatal_span_(
             start,
             self.pos,
             &format!(
                 "too many `#` symbols: raw strings may be delimited \
-                by up to 65535 `#` symbols, but found {}",
-                found
+                by up to {} `#` symbols, but found {}",
+                max, found
             ),
         )
         .raise();
@@ -4,7 +4,7 @@
 use std::ops::Range;
 
 use rustc_errors::{pluralize, Applicability, Handler};
-use rustc_lexer::unescape::{EscapeError, Mode};
+use rustc_lexer::unescape::{self, EscapeError, Mode};
 use rustc_span::{BytePos, Span};
 
 pub(crate) fn emit_unescape_error(
@@ -112,16 +112,16 @@ pub(crate) fn emit_unescape_error(
             }
 
             if !has_help {
-                let (prefix, msg) = if mode.is_bytes() {
-                    ("b", "if you meant to write a byte string literal, use double quotes")
+                let msg = if mode.is_bytes() {
+                    "if you meant to write a byte string literal, use double quotes"
                 } else {
-                    ("", "if you meant to write a `str` literal, use double quotes")
+                    "if you meant to write a `str` literal, use double quotes"
                 };
 
                 handler.span_suggestion(
                     span_with_quotes,
                     msg,
-                    format!("{}\"{}\"", prefix, lit),
+                    unescape::to_str_literal(lit, mode),
                     Applicability::MachineApplicable,
                 );
             }
@@ -351,6 +351,10 @@ pub(crate) fn emit_unescape_error(
             let msg = "empty character literal";
             handler.struct_span_err(span, msg).span_label(span, msg).emit();
         }
+        EscapeError::NulInCStr => {
+            let msg = "null characters in C string literals are not supported";
+            handler.struct_span_err(span, msg).span_label(span, msg).emit();
+        }
         EscapeError::LoneSlash => {
             let msg = "invalid trailing slash in literal";
             handler.struct_span_err(span, msg).span_label(span, msg).emit();
@@ -454,7 +454,7 @@ fn advance(
         let lookahead = self.peek();
         let no_highlight = |sink: &mut dyn FnMut(_)| sink(Highlight::Token { text, class: None });
         let class = match token {
-            TokenKind::Whitespace => return no_highlight(sink),
+            TokenKind::Whitespace { .. } => return no_highlight(sink),
             TokenKind::LineComment { doc_style } | TokenKind::BlockComment { doc_style, .. } => {
                 if doc_style.is_some() {
                     Class::DocComment
@@ -475,7 +475,7 @@ fn advance(
             // or a reference or pointer type. Unless, of course, it looks like
             // a logical and or a multiplication operator: `&&` or `* `.
             TokenKind::Star => match self.tokens.peek() {
-                Some((TokenKind::Whitespace, _)) => Class::Op,
+                Some((TokenKind::Whitespace { .. }, _)) => Class::Op,
                 Some((TokenKind::Ident, "mut")) => {
                     self.next();
                     sink(Highlight::Token { text: "*mut", class: Some(Class::RefKeyWord) });
@@ -499,7 +499,7 @@ fn advance(
                     sink(Highlight::Token { text: "&=", class: Some(Class::Op) });
                     return;
                 }
-                Some((TokenKind::Whitespace, _)) => Class::Op,
+                Some((TokenKind::Whitespace { .. }, _)) => Class::Op,
                 Some((TokenKind::Ident, "mut")) => {
                     self.next();
                     sink(Highlight::Token { text: "&mut", class: Some(Class::RefKeyWord) });
@@ -598,17 +598,22 @@ fn advance(
                 }
                 return no_highlight(sink);
             }
-            TokenKind::Literal { kind, .. } => match kind {
-                // Text literals.
-                LiteralKind::Byte { .. }
-                | LiteralKind::Char { .. }
-                | LiteralKind::Str { .. }
-                | LiteralKind::ByteStr { .. }
-                | LiteralKind::RawStr { .. }
-                | LiteralKind::RawByteStr { .. } => Class::String,
-                // Number literals.
-                LiteralKind::Float { .. } | LiteralKind::Int { .. } => Class::Number,
-            },
+            TokenKind::Literal { kind, .. } | TokenKind::PrefixedLiteral { kind, .. } => {
+                match kind {
+                    // Text literals.
+                    LiteralKind::Byte { .. }
+                    | LiteralKind::Char { .. }
+                    | LiteralKind::Str { .. }
+                    | LiteralKind::ByteStr { .. }
+                    | LiteralKind::RawStr { .. }
+                    | LiteralKind::RawByteStr { .. }
+                    | LiteralKind::CStr { .. }
+                    | LiteralKind::RawCStr { .. } => Class::String,
+                    // Number literals.
+                    LiteralKind::Float { .. } | LiteralKind::Int { .. } => Class::Number,
+                }
+            }
+            TokenKind::GuardedStr { .. } => Class::String,
             TokenKind::Ident | TokenKind::RawIdent if lookahead == Some(TokenKind::Bang) => {
                 self.in_macro = true;
                 sink(Highlight::EnterSpan { class: Class::Macro });
@@ -631,10 +636,10 @@ fn advance(
                 },
                 Some(c) => c,
             },
-            TokenKind::RawIdent | TokenKind::UnknownPrefix | TokenKind::InvalidIdent => {
+            TokenKind::RawIdent | TokenKind::UnknownPrefix { .. } | TokenKind::InvalidIdent => {
                 Class::Ident(self.new_span(before, text))
             }
-            TokenKind::Lifetime { .. } => Class::Lifetime,
+            TokenKind::Lifetime { .. } | TokenKind::RawLifetime => Class::Lifetime,
         };
         // Anything that didn't return above is the simple case where we the
         // class just spans a single token, so we can use the `string` method.
@@ -647,7 +652,7 @@ fn peek(&mut self) -> Option<TokenKind> {
 
     fn check_if_is_union_keyword(&mut self) -> bool {
         while let Some(kind) = self.tokens.peek_next().map(|(token_kind, _text)| token_kind) {
-            if *kind == TokenKind::Whitespace {
+            if matches!(kind, TokenKind::Whitespace { .. }) {
                 continue;
             }
             return *kind == TokenKind::Ident;
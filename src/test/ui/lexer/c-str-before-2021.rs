@@ -0,0 +1,20 @@
+// Before Rust 2021, `c"…"` is not a C string literal but the identifier `c`
+// followed by a string, so macros must keep seeing two tokens.
+
+// check-pass
+// revisions: edition2015 edition2018
+//[edition2018] edition:2018
+
+macro_rules! ident_then_str {
+    ($prefix:ident $s:literal) => {};
+}
+
+macro_rules! ident_then_raw_str {
+    ($prefix:ident # $s:literal #) => {};
+}
+
+fn main() {
+    ident_then_str!(c"hey");
+    ident_then_str!(cr"hey");
+    ident_then_raw_str!(cr#"hey"#);
+}
@@ -0,0 +1,13 @@
+// Rust has no hexadecimal float literals, so `0xDEAD.BEEFp-2` is the integer
+// `0xDEAD`, `.`, the identifier `BEEFp`, `-` and `2`, which macros accept.
+
+// check-pass
+
+macro_rules! int_dot_ident_minus_int {
+    ($int:literal . $frac:ident - $exp:literal) => {};
+}
+
+fn main() {
+    int_dot_ident_minus_int!(0xDEAD.BEEFp-2);
+    let _ = stringify!(0xDEAD.BEEFp-2f 0x1.ap1);
+}
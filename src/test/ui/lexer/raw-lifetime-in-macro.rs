@@ -0,0 +1,15 @@
+// `'r#a` is not a raw lifetime but the lifetime `'r` followed by `#` and `a`,
+// so macros must keep seeing three tokens.
+
+// check-pass
+// revisions: edition2015 edition2021
+//[edition2021] edition:2021
+
+macro_rules! lifetime_pound_ident {
+    ($lt:lifetime # $name:ident) => {};
+}
+
+fn main() {
+    lifetime_pound_ident!('r#a);
+    lifetime_pound_ident!('r#fn);
+}
@@ -780,7 +780,7 @@ fn span_contains_cfg(cx: &LateContext<'_>, s: Span) -> bool {
         let mut iter = iter.by_ref().skip_while(|(t, _)| {
             matches!(
                 t,
-                TokenKind::Whitespace | TokenKind::LineComment { .. } | TokenKind::BlockComment { .. }
+                TokenKind::Whitespace { .. } | TokenKind::LineComment { .. } | TokenKind::BlockComment { .. }
             )
         });
         if matches!(iter.next(), Some((TokenKind::OpenBracket, _)))
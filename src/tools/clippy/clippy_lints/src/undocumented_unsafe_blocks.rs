@@ -162,7 +162,7 @@ fn block_has_safety_comment(&mut self, tcx: TyCtxt<'_>, enclosing_hir_id: HirId,
                     }
                 },
                 // We need to add all whitespace to `pos` before checking the comment's line number
-                TokenKind::Whitespace => {},
+                TokenKind::Whitespace { .. } => {},
                 _ => {
                     if comment {
                         // Get the line number of the "comment" (really wherever the trailing whitespace ended)
@@ -145,7 +145,9 @@ fn eq_block(&mut self, left: &Block<'_>, right: &Block<'_>) -> bool {
                     .filter(|(t, _)| {
                         !matches!(
                             t.kind,
-                            TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } | TokenKind::Whitespace
+                            TokenKind::LineComment { .. }
+                                | TokenKind::BlockComment { .. }
+                                | TokenKind::Whitespace { .. }
                         )
                     })
                     .map(|(_, s)| s);
@@ -160,7 +162,9 @@ fn eq_block(&mut self, left: &Block<'_>, right: &Block<'_>) -> bool {
                     .filter(|(t, _)| {
                         !matches!(
                             t.kind,
-                            TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } | TokenKind::Whitespace
+                            TokenKind::LineComment { .. }
+                                | TokenKind::BlockComment { .. }
+                                | TokenKind::Whitespace { .. }
                         )
                     })
                     .map(|(_, s)| s);
@@ -437,7 +441,9 @@ fn reduce_exprkind<'hir>(cx: &LateContext<'_>, kind: &'hir ExprKind<'hir>) -> &'
                         .filter(|t| {
                             !matches!(
                                 t,
-                                TokenKind::LineComment { .. } | TokenKind::BlockComment { .. } | TokenKind::Whitespace
+                                TokenKind::LineComment { .. }
+                                    | TokenKind::BlockComment { .. }
+                                    | TokenKind::Whitespace { .. }
                             )
                         })
                         .ne([TokenKind::OpenBrace, TokenKind::CloseBrace].iter().copied()) =>